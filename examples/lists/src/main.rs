@@ -29,7 +29,7 @@ impl Display for Rectangle {
 fn create_db(db_name: &str) {
     let mut new_db = PickleDb::new(db_name, PickleDbDumpPolicy::AutoDump);
 
-    new_db.set("key1", &100);
+    new_db.set("key1", &100).unwrap();
 }
 
 fn main() {
@@ -47,23 +47,23 @@ fn main() {
     db.lcreate("list1");
 
     // add an integer item to the list
-    db.ladd("list1", &200);
+    db.ladd("list1", &200).unwrap();
 
     // add an floating point item to the list
-    db.ladd("list1", &2.1);
+    db.ladd("list1", &2.1).unwrap();
 
     // add a string to the list
-    db.ladd("list1", &String::from("my list"));
+    db.ladd("list1", &String::from("my list")).unwrap();
 
     // add a vector of chars to the list
-    db.ladd("list1", &vec!['a', 'b', 'c']);
+    db.ladd("list1", &vec!['a', 'b', 'c']).unwrap();
 
     // add multiple values to the list: add 3 rectangles 
     db.lextend("list1", &vec![
         Rectangle { width: 2, length: 4}, 
         Rectangle { width: 10, length: 22},
         Rectangle { width: 1, length: 22}, 
-        ]);
+        ]).unwrap();
 
     // print the list length
     println!("list1 length is: {}", db.llen("list1"));
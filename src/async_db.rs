@@ -0,0 +1,390 @@
+//! An asynchronous PickleDB for applications that run on the tokio runtime.
+//!
+//! Dumping a [PickleDb](../struct.PickleDb.html) writes the DB file on the calling thread, which blocks the executor
+//! when it's done from an asynchronous task. [AsyncPickleDb](struct.AsyncPickleDb.html) keeps the same data and the same
+//! dump policies, but its mutating methods return futures and the DB file is written by tokio's asynchronous file IO.
+//! This module is available only when the `tokio` feature is enabled.
+
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::Duration;
+use std::io;
+use serde::{de::DeserializeOwned, Serialize};
+use crate::backup;
+use crate::error::Error;
+use crate::{PickleDb, PickleDbDumpPolicy};
+use crate::policy::DumpPolicy;
+
+/// The DB and the number of changes made to it, which is used to tell whether the DB file is up to date
+struct State {
+    db: PickleDb,
+    version: u64,
+    dump_policy: PickleDbDumpPolicy,
+}
+
+struct Inner {
+    state: Mutex<State>,
+    /// Held while the DB file is written, and holds the version of the DB that was last written to it
+    dumped: tokio::sync::Mutex<u64>,
+    db_file_path: String,
+}
+
+impl Inner {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Write the DB to the file. Unless `force` is set, nothing is written if the file is already up to date,
+    /// so concurrent dumps of the same changes write the file only once
+    async fn dump(&self, force: bool) -> Result<(), Error> {
+        let mut dumped = self.dumped.lock().await;
+        let (contents, version, backups, stats) = {
+            let state = self.lock();
+            if !force && state.version == *dumped {
+                return Ok(());
+            }
+            (state.db.contents()?, state.version, state.db.backups_to_rotate(), state.db.dump_stats())
+        };
+        let location = self.db_file_path.clone();
+        // the file is replaced atomically like the one of PickleDb, so a failed dump leaves the previous file intact
+        let rotated = tokio::task::spawn_blocking(move || {
+            let rotated = backup::rotate(&location, backups);
+            backup::write_atomically(&location, &contents).map(|_| rotated)
+        }).await.map_err(io::Error::other)??;
+        self.lock().db.reset_dump_stats_of(&stats);
+        *dumped = version;
+        rotated?;
+        Ok(())
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // the last resort for changes that weren't dumped yet, which is the same as what dropping a PickleDb does
+        let dumped = *self.dumped.get_mut();
+        let state = self.state.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let PickleDbDumpPolicy::NeverDump = state.dump_policy {
+            return;
+        }
+        if state.version != dumped {
+            if let Ok(contents) = state.db.contents() {
+                let _ignore = backup::rotate(&self.db_file_path, state.db.backups_to_rotate());
+                let _ignore = backup::write_atomically(&self.db_file_path, &contents);
+            }
+        }
+    }
+}
+
+async fn periodic_dump(inner: Weak<Inner>, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    // the first tick completes immediately
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        match inner.upgrade() {
+            // a dump that failed is retried upon the next tick, since the file isn't marked as up to date
+            Some(inner) => {
+                let _ignore = inner.dump(false).await;
+            },
+            None => return,
+        }
+    }
+}
+
+/// A PickleDB with an asynchronous API.
+///
+/// The data is kept in memory exactly like in [PickleDb](../struct.PickleDb.html), so reading is synchronous and cheap,
+/// while the mutating methods are asynchronous and dump the DB according to the dump policy:
+/// * [PickleDbDumpPolicy::AutoDump](../enum.PickleDbDumpPolicy.html#variant.AutoDump) - every change is dumped before
+///   its future completes. Changes that are made concurrently are written to the file together
+/// * [PickleDbDumpPolicy::DumpUponRequest](../enum.PickleDbDumpPolicy.html#variant.DumpUponRequest) - changes are dumped
+///   only by [dump()](#method.dump)
+/// * [PickleDbDumpPolicy::PeriodicDump(Duration)](../enum.PickleDbDumpPolicy.html#variant.PeriodicDump) - a background task
+///   dumps the changes every Duration, if there are any
+/// * [PickleDbDumpPolicy::NeverDump](../enum.PickleDbDumpPolicy.html#variant.NeverDump) - changes are never dumped
+/// * The other policies are asked after every change, and a change they decide to dump is dumped before its future
+///   completes. A `PeriodicDump` inside [PickleDbDumpPolicy::Any](../enum.PickleDbDumpPolicy.html#variant.Any) is
+///   checked only upon changes, like in `PickleDb`, and doesn't have a background task
+///
+/// The DB file is written by tokio's asynchronous file IO, so it never blocks the executor. Like `PickleDb`, changes
+/// that weren't dumped yet are dumped when the DB is dropped, but since that is a blocking write it's better to call
+/// [dump()](#method.dump) before dropping the DB. The DB can be cloned cheaply, and all clones share the same data.
+///
+/// Features that aren't part of this API, such as namespaces, indexes and subscriptions, are available through
+/// [read()](#method.read) and [update()](#method.update).
+#[derive(Clone)]
+pub struct AsyncPickleDb {
+    inner: Arc<Inner>,
+}
+
+impl AsyncPickleDb {
+    fn from_db(mut db: PickleDb, location: &str, dump_policy: PickleDbDumpPolicy) -> AsyncPickleDb {
+        // the DB itself never dumps, so it counts the bytes of changes for the dump policy of this DB
+        db.set_count_bytes(dump_policy.uses_bytes());
+        let period = match dump_policy {
+            PickleDbDumpPolicy::PeriodicDump(period) => Some(period),
+            _ => None,
+        };
+        let inner = Arc::new(Inner {
+            state: Mutex::new(State { db, version: 0, dump_policy }),
+            dumped: tokio::sync::Mutex::new(0),
+            db_file_path: String::from(location),
+        });
+        if let Some(period) = period {
+            tokio::spawn(periodic_dump(Arc::downgrade(&inner), period));
+        }
+        AsyncPickleDb { inner }
+    }
+
+    /// Constructs a new `AsyncPickleDb` instance.
+    ///
+    /// Please see [PickleDb::new()](../struct.PickleDb.html#method.new) for more details. With
+    /// [PickleDbDumpPolicy::PeriodicDump](../enum.PickleDbDumpPolicy.html#variant.PeriodicDump) this method spawns
+    /// the background task that dumps the DB, so it has to be called from within a tokio runtime with time enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - a path where the DB will be stored
+    /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use pickledb::async_db::AsyncPickleDb;
+    ///
+    /// let db = AsyncPickleDb::new("example.db", PickleDbDumpPolicy::AutoDump);
+    /// db.set("key1", &100).await.unwrap();
+    /// ```
+    ///
+    pub fn new(location: &str, dump_policy: PickleDbDumpPolicy) -> AsyncPickleDb {
+        AsyncPickleDb::from_db(PickleDb::new(location, PickleDbDumpPolicy::NeverDump), location, dump_policy)
+    }
+
+    /// Load a DB from a file.
+    ///
+    /// The file is read by tokio's asynchronous file IO. Please see [PickleDb::load()](../struct.PickleDb.html#method.load)
+    /// and [new()](#method.new) for more details.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - a path where the DB is loaded from
+    /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file
+    ///
+    pub async fn load(location: &str, dump_policy: PickleDbDumpPolicy) -> Result<AsyncPickleDb, Error> {
+        let contents = tokio::fs::read(location).await?;
+        let db = PickleDb::from_contents(location, contents, PickleDbDumpPolicy::NeverDump)?;
+        Ok(AsyncPickleDb::from_db(db, location, dump_policy))
+    }
+
+    /// Load a DB from a file in read-only mode, meaning changes will not be saved to the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - a path where the DB is loaded from
+    ///
+    pub async fn load_read_only(location: &str) -> Result<AsyncPickleDb, Error> {
+        AsyncPickleDb::load(location, PickleDbDumpPolicy::NeverDump).await
+    }
+
+    /// Dump the data to the file.
+    ///
+    /// The data is dumped regardless of the dump policy, except for
+    /// [PickleDbDumpPolicy::NeverDump](../enum.PickleDbDumpPolicy.html#variant.NeverDump) in which nothing is dumped.
+    ///
+    pub async fn dump(&self) -> Result<(), Error> {
+        if let PickleDbDumpPolicy::NeverDump = self.inner.lock().dump_policy {
+            return Ok(());
+        }
+        self.inner.dump(true).await
+    }
+
+    /// Read the DB.
+    ///
+    /// The function receives the underlying [PickleDb](../struct.PickleDb.html), so every reading method of it can be used.
+    /// The DB is locked while the function runs, so it should be short.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - a function that reads the DB
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let adults = db.read(|db| db.query("$.age >= 18")).unwrap();
+    /// ```
+    ///
+    pub fn read<R, F>(&self, f: F) -> R
+        where
+            F: FnOnce(&PickleDb) -> R
+    {
+        f(&self.inner.lock().db)
+    }
+
+    /// Change the DB and dump it according to the dump policy.
+    ///
+    /// The function receives the underlying [PickleDb](../struct.PickleDb.html), so every method of it can be used, and
+    /// its result is returned once the change is dumped. The DB is locked while the function runs, so it should be short.
+    /// An error is returned if dumping fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - a function that changes the DB
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// db.update(|db| db.create_index("by_age", "$.age")).await.unwrap().unwrap();
+    /// ```
+    ///
+    pub async fn update<R, F>(&self, f: F) -> Result<R, Error>
+        where
+            F: FnOnce(&mut PickleDb) -> R
+    {
+        let (res, dump) = {
+            let mut guard = self.inner.lock();
+            let state = &mut *guard;
+            let writes = state.db.dump_stats().writes;
+            let res = f(&mut state.db);
+            // only changes count, and not calls that failed or changed nothing
+            if state.db.dump_stats().writes == writes {
+                return Ok(res);
+            }
+            state.version += 1;
+            (res, state.dump_policy.should_dump(&state.db.dump_stats()))
+        };
+        if dump {
+            self.inner.dump(false).await?;
+        }
+        Ok(res)
+    }
+
+    /// Set a key-value pair.
+    ///
+    /// Please see [PickleDb::set()](../struct.PickleDb.html#method.set) for more details.
+    ///
+    pub async fn set<V>(&self, key: &str, value: &V) -> Result<(), Error>
+        where
+            V: Serialize
+    {
+        self.update(|db| db.set(key, value)).await?
+    }
+
+    /// Get a value of a key.
+    ///
+    /// Please see [PickleDb::get()](../struct.PickleDb.html#method.get) for more details.
+    ///
+    pub fn get<V>(&self, key: &str) -> Option<V>
+        where
+            V: DeserializeOwned
+    {
+        self.read(|db| db.get(key))
+    }
+
+    /// Check if a key exists.
+    ///
+    pub fn exists(&self, key: &str) -> bool {
+        self.read(|db| db.exists(key))
+    }
+
+    /// Get a vector of all the keys in the DB.
+    ///
+    pub fn get_all(&self) -> Vec<String> {
+        self.read(|db| db.get_all())
+    }
+
+    /// Get the total number of keys in the DB.
+    ///
+    pub fn total_keys(&self) -> usize {
+        self.read(|db| db.total_keys())
+    }
+
+    /// Remove a key-value pair or a list from the DB.
+    ///
+    /// Please see [PickleDb::rem()](../struct.PickleDb.html#method.rem) for more details.
+    ///
+    pub async fn rem(&self, key: &str) -> Result<bool, Error> {
+        self.update(|db| db.rem(key)).await
+    }
+
+    /// Create a new list.
+    ///
+    /// Please see [PickleDb::lcreate()](../struct.PickleDb.html#method.lcreate) for more details.
+    ///
+    pub async fn lcreate(&self, name: &str) -> Result<(), Error> {
+        self.update(|db| db.lcreate(name)).await
+    }
+
+    /// Check if a list exists.
+    ///
+    pub fn lexists(&self, name: &str) -> bool {
+        self.read(|db| db.lexists(name))
+    }
+
+    /// Add a single item to an existing list.
+    ///
+    /// Please see [PickleDb::ladd()](../struct.PickleDb.html#method.ladd) for more details.
+    ///
+    pub async fn ladd<V>(&self, name: &str, value: &V) -> Result<bool, Error>
+        where
+            V: Serialize
+    {
+        self.update(|db| db.ladd(name, value)).await?
+    }
+
+    /// Add multiple items to an existing list.
+    ///
+    /// Please see [PickleDb::lextend()](../struct.PickleDb.html#method.lextend) for more details.
+    ///
+    pub async fn lextend<V>(&self, name: &str, seq: &[V]) -> Result<bool, Error>
+        where
+            V: Serialize
+    {
+        self.update(|db| db.lextend_in(None, name, seq)).await?
+    }
+
+    /// Get an item of of a certain list in a certain position.
+    ///
+    /// Please see [PickleDb::lget()](../struct.PickleDb.html#method.lget) for more details.
+    ///
+    pub fn lget<V>(&self, name: &str, pos: usize) -> Option<V>
+        where
+            V: DeserializeOwned
+    {
+        self.read(|db| db.lget(name, pos))
+    }
+
+    /// Get the length of a list.
+    ///
+    pub fn llen(&self, name: &str) -> usize {
+        self.read(|db| db.llen(name))
+    }
+
+    /// Remove a list and return the number of items that were in it.
+    ///
+    /// Please see [PickleDb::lrem_list()](../struct.PickleDb.html#method.lrem_list) for more details.
+    ///
+    pub async fn lrem_list(&self, name: &str) -> Result<usize, Error> {
+        self.update(|db| db.lrem_list(name)).await
+    }
+
+    /// Pop an item out of a list.
+    ///
+    /// Please see [PickleDb::lpop()](../struct.PickleDb.html#method.lpop) for more details.
+    ///
+    pub async fn lpop<V>(&self, name: &str, pos: usize) -> Result<Option<V>, Error>
+        where
+            V: DeserializeOwned
+    {
+        self.update(|db| db.lpop(name, pos)).await
+    }
+
+    /// Remove an item out of a list.
+    ///
+    /// Please see [PickleDb::lrem_value()](../struct.PickleDb.html#method.lrem_value) for more details.
+    ///
+    pub async fn lrem_value<V>(&self, name: &str, value: &V) -> Result<bool, Error>
+        where
+            V: Serialize
+    {
+        self.update(|db| db.lrem_value(name, value)).await
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use serde_json::Value;
+use crate::backup;
+use crate::compression::{self, Compression};
+#[cfg(feature = "encryption")]
+use crate::encryption::Cipher;
+use crate::error::Error;
+use crate::format;
+use crate::index::Index;
+use crate::namespace::NamespaceData;
+use crate::schema::Schema;
+
+/// The data of a DB at the time it was dumped. The key-value pairs, lists and namespaces are shared with the DB,
+/// which copies them only if it changes them while the snapshot is still waiting to be written
+pub(crate) struct Snapshot {
+    pub(crate) path: String,
+    /// The number of numbered backups that are kept of the DB file
+    pub(crate) backups: usize,
+    pub(crate) compression: Compression,
+    #[cfg(feature = "encryption")]
+    pub(crate) cipher: Option<Arc<Cipher>>,
+    pub(crate) map: Arc<HashMap<String, Value>>,
+    pub(crate) list_map: Arc<HashMap<String, Vec<Value>>>,
+    pub(crate) namespaces: Arc<HashMap<String, NamespaceData>>,
+    pub(crate) indexes: HashMap<String, Index>,
+    pub(crate) key_schemas: HashMap<String, Schema>,
+    pub(crate) list_schemas: HashMap<String, Schema>,
+    pub(crate) python_compat: bool,
+}
+
+impl Snapshot {
+    fn write(&self) -> Result<(), Error> {
+        let db_file = format::DbFileRef {
+            format: format::VERSION,
+            map: &self.map,
+            list_map: &self.list_map,
+            namespaces: &self.namespaces,
+            indexes: &self.indexes,
+            key_schemas: &self.key_schemas,
+            list_schemas: &self.list_schemas,
+            python_compat: self.python_compat,
+        };
+        #[allow(unused_mut)]
+        let mut contents = compression::encode(db_file.to_json()?, self.compression)?;
+        #[cfg(feature = "encryption")]
+        {
+            if let Some(cipher) = &self.cipher {
+                contents = cipher.encrypt(&contents)?;
+            }
+        }
+        let rotated = backup::rotate(&self.path, self.backups);
+        backup::write_atomically(&self.path, &contents)?;
+        rotated?;
+        Ok(())
+    }
+}
+
+struct State {
+    /// The latest snapshot that wasn't written yet. A newer snapshot replaces it, so a burst of dumps is written once
+    pending: Option<Snapshot>,
+    submitted: u64,
+    written: u64,
+    /// The error of the last write that failed since the last flush
+    error: Option<Error>,
+    closed: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn wait<'a>(&self, state: MutexGuard<'a, State>) -> MutexGuard<'a, State> {
+        self.changed.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A thread that writes the snapshots of a DB to its file
+pub(crate) struct BackgroundWriter {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl BackgroundWriter {
+    pub(crate) fn start() -> Result<BackgroundWriter, Error> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State { pending: None, submitted: 0, written: 0, error: None, closed: false }),
+            changed: Condvar::new(),
+        });
+        let thread = {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name(String::from("pickledb-writer"))
+                .spawn(move || run(&shared))?
+        };
+        Ok(BackgroundWriter { shared, thread: Some(thread) })
+    }
+
+    /// Hand a snapshot to the writer thread, replacing the snapshot that is waiting to be written if there is one
+    pub(crate) fn submit(&mut self, snapshot: Snapshot) {
+        let mut state = self.shared.lock();
+        state.pending = Some(snapshot);
+        state.submitted += 1;
+        self.shared.changed.notify_all();
+    }
+
+    /// Wait until all snapshots that were submitted are written, and return the error of the last write that failed
+    pub(crate) fn flush(&self) -> Result<(), Error> {
+        let mut state = self.shared.lock();
+        while state.written < state.submitted {
+            state = self.shared.wait(state);
+        }
+        match state.error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for BackgroundWriter {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.changed.notify_all();
+        // the thread writes the pending snapshot before it exits
+        if let Some(thread) = self.thread.take() {
+            let _ignore = thread.join();
+        }
+    }
+}
+
+fn run(shared: &Shared) {
+    let mut state = shared.lock();
+    loop {
+        if let Some(snapshot) = state.pending.take() {
+            let submitted = state.submitted;
+            drop(state);
+            let res = snapshot.write();
+            // release the data before the DB is told, so its next change doesn't need to copy it
+            drop(snapshot);
+            state = shared.lock();
+            state.written = submitted;
+            if let Err(err) = res {
+                state.error = Some(err);
+            }
+            shared.changed.notify_all();
+        } else if state.closed {
+            return;
+        } else {
+            state = shared.wait(state);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The path of the n-th numbered backup of a DB file, for example `example.db.1`
+pub(crate) fn backup_path(location: &str, n: usize) -> String {
+    format!("{}.{}", location, n)
+}
+
+/// Shift the numbered backups of a DB file by one, dropping the oldest, and copy the DB file to the first backup.
+/// This is done before the DB file is overwritten, so it keeps the `count` previous versions of the file
+pub(crate) fn rotate(location: &str, count: usize) -> io::Result<()> {
+    if count == 0 || !Path::new(location).exists() {
+        return Ok(());
+    }
+    for n in (1..count).rev() {
+        let from = backup_path(location, n);
+        if Path::new(&from).exists() {
+            fs::rename(&from, backup_path(location, n + 1))?;
+        }
+    }
+    // the DB file is copied and not renamed, so it exists even if writing the new one fails
+    fs::copy(location, backup_path(location, 1))?;
+    Ok(())
+}
+
+/// Write the contents of a file so it's never found partially written: the contents are written to a temporary file
+/// which then replaces the file
+pub(crate) fn write_atomically(location: &str, contents: &[u8]) -> io::Result<()> {
+    let tmp = format!("{}.tmp", location);
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, location)
+}
@@ -0,0 +1,114 @@
+//! `pickledb-resp`, a server that serves a DB file to Redis clients.
+//!
+//! The DB file is created if it doesn't exist. By default every change is dumped to the file immediately, and the server
+//! listens on 127.0.0.1:6380 so it doesn't clash with a Redis server on the same host. Please see the `resp` module of
+//! pickledb for the supported commands.
+
+use std::env;
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::process;
+use std::thread;
+use pickledb::resp::RespServer;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+
+const USAGE: &str = "usage: pickledb-resp <db file> [options]
+
+options:
+  --tcp <addr>           listen on a TCP address, 127.0.0.1:6380 by default
+  --unix <path>          listen on a Unix socket
+  --read-only            reject the commands that change the DB
+  --dump-upon-request    dump changes only upon SAVE rather than immediately";
+
+struct Options {
+    path: String,
+    tcp: Option<String>,
+    unix: Option<String>,
+    read_only: bool,
+    dump_upon_request: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut args = args.iter();
+    let path = match args.next() {
+        Some(path) if !path.starts_with("--") => path.clone(),
+        _ => return Err(String::from("missing DB file")),
+    };
+    let mut options = Options { path, tcp: None, unix: None, read_only: false, dump_upon_request: false };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tcp" => options.tcp = Some(args.next().ok_or("missing address of --tcp")?.clone()),
+            "--unix" => options.unix = Some(args.next().ok_or("missing path of --unix")?.clone()),
+            "--read-only" => options.read_only = true,
+            "--dump-upon-request" => options.dump_upon_request = true,
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+    if options.tcp.is_none() && options.unix.is_none() {
+        options.tcp = Some(String::from("127.0.0.1:6380"));
+    }
+    Ok(options)
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let policy = if options.read_only {
+        PickleDbDumpPolicy::NeverDump
+    } else if options.dump_upon_request {
+        PickleDbDumpPolicy::DumpUponRequest
+    } else {
+        PickleDbDumpPolicy::AutoDump
+    };
+    let db = if Path::new(&options.path).exists() {
+        PickleDb::load(&options.path, policy).map_err(|err| format!("can't load '{}': {}", options.path, err))?
+    } else {
+        PickleDb::new(&options.path, policy)
+    };
+    let server = if options.read_only { RespServer::new_read_only(db) } else { RespServer::new(db) };
+
+    #[cfg(unix)]
+    let unix = match &options.unix {
+        Some(path) => {
+            let listener = UnixListener::bind(path).map_err(|err| format!("can't listen on '{}': {}", path, err))?;
+            eprintln!("serving '{}' on {}", options.path, path);
+            let server = server.clone();
+            Some(thread::spawn(move || server.serve_unix(listener)))
+        },
+        None => None,
+    };
+    #[cfg(not(unix))]
+    let unix: Option<thread::JoinHandle<std::io::Result<()>>> = match &options.unix {
+        Some(_) => return Err(String::from("Unix sockets aren't supported on this platform")),
+        None => None,
+    };
+
+    if let Some(addr) = &options.tcp {
+        let listener = TcpListener::bind(addr).map_err(|err| format!("can't listen on {}: {}", addr, err))?;
+        eprintln!("serving '{}' on {}", options.path, listener.local_addr().map_err(|err| err.to_string())?);
+        server.serve_tcp(listener).map_err(|err| err.to_string())?;
+    }
+    if let Some(unix) = unix {
+        unix.join().map_err(|_| String::from("the Unix socket server panicked"))?.map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| matches!(arg.as_str(), "help" | "-h" | "--help")) {
+        println!("{}", USAGE);
+        return;
+    }
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            process::exit(2);
+        },
+    };
+    if let Err(message) = run(options) {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,111 @@
+use pickledb::PickleDb;
+use serde_json::Value;
+use crate::CliError;
+
+/// The commands that operate on the keys and lists of a DB: their names, arguments and descriptions
+pub(crate) const COMMANDS: &[(&str, &str, &str)] = &[
+    ("get", "<key>", "print the value of a key"),
+    ("set", "<key> <value>", "set the value of a key, which is parsed as JSON or taken as a string if it isn't valid JSON"),
+    ("rem", "<key>", "remove a key or a list"),
+    ("keys", "[pattern]", "print the keys and lists whose names match a glob pattern, all of them by default"),
+    ("lget", "<list> <pos>", "print the item in a position of a list"),
+    ("ladd", "<list> <value>...", "add items to a list, which is created if it doesn't exist"),
+    ("lpop", "<list> <pos>", "remove the item in a position of a list and print it"),
+    ("llen", "<list>", "print the number of items in a list"),
+];
+
+/// Check whether a command only reads the DB
+pub(crate) fn is_read_only(command: &str) -> bool {
+    matches!(command, "get" | "keys" | "lget" | "llen")
+}
+
+/// Format a value as pretty JSON
+pub(crate) fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+/// Parse a value given on the command line: JSON if it's valid JSON, otherwise a string
+pub(crate) fn parse_value(text: &str) -> Value {
+    serde_json::from_str(text).unwrap_or_else(|_| Value::String(String::from(text)))
+}
+
+fn parse_pos(text: &str) -> Result<usize, CliError> {
+    text.parse().map_err(|_| CliError::Usage(format!("invalid position '{}'", text)))
+}
+
+fn key_not_found(db: &PickleDb, key: &str) -> CliError {
+    if db.lexists(key) {
+        CliError::Failed(format!("'{}' is a list, please use lget", key))
+    } else {
+        CliError::Failed(format!("key '{}' not found", key))
+    }
+}
+
+fn list_not_found(name: &str) -> CliError {
+    CliError::Failed(format!("list '{}' not found", name))
+}
+
+/// Run a command on a DB and return what it prints, if anything
+pub(crate) fn execute(db: &mut PickleDb, command: &str, args: &[String]) -> Result<Option<String>, CliError> {
+    match (command, args) {
+        ("get", [key]) => match db.get::<Value>(key) {
+            Some(value) => Ok(Some(pretty(&value))),
+            None => Err(key_not_found(db, key)),
+        },
+        ("set", [key, value]) => {
+            db.set(key, &parse_value(value))?;
+            Ok(None)
+        },
+        ("rem", [key]) => {
+            if !db.try_rem(key)? {
+                return Err(CliError::Failed(format!("key '{}' not found", key)));
+            }
+            Ok(None)
+        },
+        ("keys", []) | ("keys", [_]) => {
+            let mut keys = db.get_all_matching(args.first().map_or("*", String::as_str));
+            keys.sort();
+            Ok(Some(keys.join("\n")).filter(|keys| !keys.is_empty()))
+        },
+        ("lget", [name, pos]) => {
+            let pos = parse_pos(pos)?;
+            if !db.lexists(name) {
+                return Err(list_not_found(name));
+            }
+            match db.lget::<Value>(name, pos) {
+                Some(item) => Ok(Some(pretty(&item))),
+                None => Err(CliError::Failed(format!("position {} is out of the bounds of list '{}'", pos, name))),
+            }
+        },
+        ("ladd", [name, values @ ..]) if !values.is_empty() => {
+            let values: Vec<Value> = values.iter().map(|value| parse_value(value)).collect();
+            // a list that doesn't exist is created only if its items are valid
+            if !db.lexists(name) {
+                db.lcreate_with(name, &values)?;
+            } else if !db.lextend(name, &values)? {
+                return Err(list_not_found(name));
+            }
+            Ok(None)
+        },
+        ("lpop", [name, pos]) => {
+            let pos = parse_pos(pos)?;
+            if !db.lexists(name) {
+                return Err(list_not_found(name));
+            }
+            match db.try_lpop::<Value>(name, pos)? {
+                Some(item) => Ok(Some(pretty(&item))),
+                None => Err(CliError::Failed(format!("position {} is out of the bounds of list '{}'", pos, name))),
+            }
+        },
+        ("llen", [name]) => {
+            if !db.lexists(name) {
+                return Err(list_not_found(name));
+            }
+            Ok(Some(db.llen(name).to_string()))
+        },
+        _ => match COMMANDS.iter().find(|(name, _, _)| *name == command) {
+            Some((name, args, _)) => Err(CliError::Usage(format!("usage: {} {}", name, args))),
+            None => Err(CliError::Usage(format!("unknown command '{}'", command))),
+        },
+    }
+}
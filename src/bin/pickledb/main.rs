@@ -0,0 +1,185 @@
+//! `pickledb`, a command-line tool for inspecting and changing DB files.
+//!
+//! Every command loads the DB file with `PickleDb::load`, and commands that change the DB dump it before they exit. The
+//! file is replaced atomically, and with `--backups <n>` the previous `n` versions of it are kept.
+//! Values are printed as pretty JSON. Errors are printed to stderr and exit with a non-zero status: 1 when the command
+//! failed and 2 when it's used incorrectly.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+use pickledb::export::{ConflictPolicy, ExportFormat};
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use serde_json::{Map, Value};
+
+mod commands;
+#[cfg(feature = "shell")]
+mod shell;
+
+/// The commands that operate on the DB file as a whole: their names, arguments and descriptions
+const FILE_COMMANDS: &[(&str, &str, &str)] = &[
+    ("dump", "", "rewrite the DB file in the current format"),
+    ("stats", "", "print the number of keys, lists and list items, and the size and compression of the file"),
+    ("export", "<out> [--format <f>]", "write the keys and lists to a file as ndjson (the default), or the keys as csv or json"),
+    ("import", "<in> [--format <f>]", "set the keys and lists of a file written by export, replacing the ones that exist"),
+    ("shell", "", "start an interactive shell on the DB file (requires the `shell` feature)"),
+];
+
+pub(crate) enum CliError {
+    /// The command is used incorrectly
+    Usage(String),
+    /// The command failed
+    Failed(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Usage(message) | CliError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<pickledb::error::Error> for CliError {
+    fn from(err: pickledb::error::Error) -> CliError {
+        CliError::Failed(err.to_string())
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(err: io::Error) -> CliError {
+        CliError::Failed(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for CliError {
+    fn from(err: serde_json::Error) -> CliError {
+        CliError::Failed(err.to_string())
+    }
+}
+
+fn usage() -> String {
+    let mut usage = String::from("usage: pickledb [--backups <n>] <command> <db file> [arguments]\n\n");
+    usage.push_str(&format!("  {:<30} {}\n\ncommands:\n", "--backups <n>", "keep the n previous versions of the DB file upon changes"));
+    for (name, args, about) in commands::COMMANDS.iter().chain(FILE_COMMANDS) {
+        usage.push_str(&format!("  {:<30} {}\n", format!("{} {}", name, args), about));
+    }
+    usage
+}
+
+/// Load a DB file, keeping `backups` previous versions of it when it's saved. The DB is dumped only by `save()`, so
+/// commands that fail or don't change anything leave the file as it is
+fn load(path: &str, backups: usize) -> Result<PickleDb, CliError> {
+    let mut db = PickleDb::load(path, PickleDbDumpPolicy::NeverDump)
+        .map_err(|err| CliError::Failed(format!("can't load '{}': {}", path, err)))?;
+    db.set_backup_rotation(backups);
+    Ok(db)
+}
+
+fn save(db: &mut PickleDb, path: &str) -> Result<(), CliError> {
+    let previous = db.set_dump_policy(PickleDbDumpPolicy::DumpUponRequest);
+    let dumped = db.try_dump();
+    db.set_dump_policy(previous);
+    dumped.map_err(|err| CliError::Failed(format!("can't write '{}': {}", path, err)))
+}
+
+fn stats(db: &PickleDb, path: &str) -> Result<Value, CliError> {
+    let keys = db.get_all();
+    let lists: Vec<&String> = keys.iter().filter(|key| db.lexists(key)).collect();
+    let mut stats = Map::new();
+    stats.insert(String::from("keys"), Value::from(keys.len() - lists.len()));
+    stats.insert(String::from("lists"), Value::from(lists.len()));
+    stats.insert(String::from("list_items"), Value::from(lists.iter().map(|name| db.llen(name)).sum::<usize>()));
+    stats.insert(String::from("namespaces"), Value::from(db.list_namespaces().len()));
+    stats.insert(String::from("file_size"), Value::from(fs::metadata(path)?.len()));
+    stats.insert(String::from("compression"), Value::from(format!("{:?}", db.compression()).to_lowercase()));
+    Ok(Value::Object(stats))
+}
+
+/// The format of an exported file, given by `--format`
+fn export_format(args: &[String]) -> Result<ExportFormat, CliError> {
+    match args {
+        [] => Ok(ExportFormat::Ndjson),
+        [flag, format] if flag == "--format" => match format.as_str() {
+            "ndjson" => Ok(ExportFormat::Ndjson),
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => Err(CliError::Usage(format!("unknown format '{}', it may be ndjson, csv or json", format))),
+        },
+        _ => Err(CliError::Usage(String::from("usage: --format <ndjson|csv|json>"))),
+    }
+}
+
+fn run(args: &[String]) -> Result<Option<String>, CliError> {
+    let (backups, args) = match args {
+        [option, count, args @ ..] if option == "--backups" => match count.parse() {
+            Ok(count) => (count, args),
+            Err(_) => return Err(CliError::Usage(format!("'{}' isn't a number of backups", count))),
+        },
+        [option] if option == "--backups" => return Err(CliError::Usage(String::from("missing the number of backups"))),
+        _ => (0, args),
+    };
+    let (command, path, args) = match args {
+        [command, ..] if matches!(command.as_str(), "help" | "-h" | "--help") => return Ok(Some(String::from(usage().trim_end()))),
+        [command, path, args @ ..] => (command.as_str(), path.as_str(), args),
+        _ => return Err(CliError::Usage(String::from("missing command or DB file"))),
+    };
+
+    match (command, args) {
+        ("dump", []) => {
+            save(&mut load(path, backups)?, path)?;
+            Ok(None)
+        },
+        ("stats", []) => Ok(Some(commands::pretty(&stats(&load(path, backups)?, path)?))),
+        ("export", [out, format @ ..]) => {
+            let format = export_format(format)?;
+            load(path, backups)?.export(out, format)?;
+            Ok(None)
+        },
+        ("import", [source, format @ ..]) => {
+            let format = export_format(format)?;
+            let mut db = load(path, backups)?;
+            db.import(source, format, ConflictPolicy::Overwrite)?;
+            save(&mut db, path)?;
+            Ok(None)
+        },
+        #[cfg(feature = "shell")]
+        ("shell", []) => {
+            shell::run(path, backups)?;
+            Ok(None)
+        },
+        #[cfg(not(feature = "shell"))]
+        ("shell", []) => Err(CliError::Failed(String::from("the shell requires the `shell` feature of pickledb"))),
+        _ if FILE_COMMANDS.iter().any(|(name, _, _)| *name == command) => Err(CliError::Usage(format!("wrong arguments to '{}'", command))),
+        _ if !commands::COMMANDS.iter().any(|(name, _, _)| *name == command) => Err(CliError::Usage(format!("unknown command '{}'", command))),
+        _ => {
+            let mut db = load(path, backups)?;
+            let output = commands::execute(&mut db, command, args)?;
+            if !commands::is_read_only(command) {
+                save(&mut db, path)?;
+            }
+            Ok(output)
+        },
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(Some(output)) => {
+            let mut stdout = io::stdout();
+            let _ignore = writeln!(stdout, "{}", output);
+        },
+        Ok(None) => (),
+        Err(CliError::Usage(message)) => {
+            eprintln!("{}\n\n{}", message, usage());
+            process::exit(2);
+        },
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        },
+    }
+}
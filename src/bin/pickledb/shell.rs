@@ -0,0 +1,214 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use crate::commands::{self, parse_value};
+use crate::CliError;
+
+/// The commands of the shell: their names, arguments and descriptions. An argument named `<value>` takes the rest of the
+/// line, so values may contain spaces
+const SHELL_COMMANDS: &[(&str, &str, &str)] = &[
+    ("GET", "<key>", "print the value of a key"),
+    ("SET", "<key> <value>", "set the value of a key, which is parsed as JSON or taken as a string if it isn't valid JSON"),
+    ("REM", "<key>", "remove a key or a list"),
+    ("EXISTS", "<key>", "check whether a key or a list exists"),
+    ("KEYS", "[pattern]", "print the keys and lists whose names match a glob pattern, all of them by default"),
+    ("LCREATE", "<list>", "create an empty list, replacing the list if it exists"),
+    ("LEXISTS", "<list>", "check whether a list exists"),
+    ("LADD", "<list> <value>", "add an item to a list, which is created if it doesn't exist"),
+    ("LGET", "<list> <pos>", "print the item in a position of a list"),
+    ("LPOP", "<list> <pos>", "remove the item in a position of a list and print it"),
+    ("LLEN", "<list>", "print the number of items in a list"),
+    ("LREMLIST", "<list>", "remove a list"),
+    ("LREMVALUE", "<list> <value>", "remove the first item of a list that equals a value"),
+    ("SAVE", "", "dump the changes to the DB file"),
+    ("HELP", "", "print this help"),
+    ("QUIT", "", "exit the shell, changes that weren't saved are discarded"),
+];
+
+/// What the line editor needs to complete commands and key names
+struct ShellHelper {
+    keys: Vec<String>,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos].rfind(char::is_whitespace).map_or(0, |space| space + 1);
+        let word = &line[start..pos];
+        let candidates: Vec<&str> = if start == 0 {
+            SHELL_COMMANDS.iter().map(|(name, _, _)| *name).filter(|name| name.starts_with(&word.to_uppercase())).collect()
+        } else {
+            self.keys.iter().map(String::as_str).filter(|key| key.starts_with(word)).collect()
+        };
+        let pairs = candidates.into_iter()
+            .map(|candidate| Pair { display: String::from(candidate), replacement: String::from(candidate) })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+fn help() -> String {
+    SHELL_COMMANDS.iter()
+        .map(|(name, args, about)| format!("{:<28} {}", format!("{} {}", name, args), about))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Split a line to the command and its arguments. The last argument of a command that takes a `<value>` is the rest of the line
+fn split(line: &str) -> (String, Vec<String>) {
+    let line = line.trim();
+    let (command, mut rest) = line.split_at(line.find(char::is_whitespace).unwrap_or(line.len()));
+    let command = command.to_uppercase();
+    let args_usage = SHELL_COMMANDS.iter().find(|(name, _, _)| *name == command).map_or("", |(_, args, _)| *args);
+    let mut args = Vec::new();
+    if args_usage.ends_with("<value>") {
+        for _ in 1..args_usage.split_whitespace().count() {
+            rest = rest.trim_start();
+            let (arg, remaining) = rest.split_at(rest.find(char::is_whitespace).unwrap_or(rest.len()));
+            args.push(String::from(arg));
+            rest = remaining;
+        }
+        if !rest.trim().is_empty() {
+            args.push(String::from(rest.trim()));
+        }
+    } else {
+        args.extend(rest.split_whitespace().map(String::from));
+    }
+    (command, args)
+}
+
+fn yes_no(answer: bool) -> Option<String> {
+    Some(String::from(if answer { "true" } else { "false" }))
+}
+
+/// The DB a shell works on, and whether it changed since it was last saved
+struct Session {
+    db: PickleDb,
+    unsaved: bool,
+}
+
+impl Session {
+    /// Run a command of the shell and return what it prints, if anything
+    fn execute(&mut self, command: &str, args: &[String]) -> Result<Option<String>, CliError> {
+        let db = &mut self.db;
+        let output = match (command, args) {
+            ("EXISTS", [key]) => yes_no(db.exists(key)),
+            ("LEXISTS", [name]) => yes_no(db.lexists(name)),
+            ("LCREATE", [name]) => {
+                db.try_lcreate(name)?;
+                None
+            },
+            ("LREMLIST", [name]) => {
+                if !db.lexists(name) {
+                    return Err(CliError::Failed(format!("list '{}' not found", name)));
+                }
+                db.try_lrem_list(name)?;
+                None
+            },
+            ("LREMVALUE", [name, value]) => yes_no(db.try_lrem_value(name, &parse_value(value))?),
+            ("SAVE", []) => {
+                db.try_dump().map_err(|err| CliError::Failed(format!("can't save the DB: {}", err)))?;
+                self.unsaved = false;
+                return Ok(Some(String::from("saved")));
+            },
+            ("HELP", []) => Some(help()),
+            (_, _) if commands::COMMANDS.iter().any(|(name, _, _)| name.eq_ignore_ascii_case(command)) => {
+                commands::execute(db, &command.to_lowercase(), args)?
+            },
+            _ => match SHELL_COMMANDS.iter().find(|(name, _, _)| *name == command) {
+                Some((name, args, _)) => return Err(CliError::Usage(format!("usage: {} {}", name, args))),
+                None => return Err(CliError::Usage(format!("unknown command '{}', please see HELP", command))),
+            },
+        };
+        if matches!(command, "SET" | "REM" | "LCREATE" | "LADD" | "LPOP" | "LREMLIST" | "LREMVALUE") {
+            self.unsaved = true;
+        }
+        Ok(output)
+    }
+}
+
+fn history_file() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| Path::new(&home).join(".pickledb_history"))
+}
+
+/// Run an interactive shell on a DB file, which is created if it doesn't exist
+pub(crate) fn run(path: &str, backups: usize) -> Result<(), CliError> {
+    let mut db = if Path::new(path).exists() {
+        PickleDb::load(path, PickleDbDumpPolicy::DumpUponRequest)
+            .map_err(|err| CliError::Failed(format!("can't load '{}': {}", path, err)))?
+    } else {
+        println!("'{}' doesn't exist, it will be created upon SAVE", path);
+        PickleDb::new(path, PickleDbDumpPolicy::DumpUponRequest)
+    };
+    db.set_backup_rotation(backups);
+    let mut session = Session { db, unsaved: false };
+
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new().map_err(|err| CliError::Failed(err.to_string()))?;
+    editor.set_helper(Some(ShellHelper { keys: session.db.get_all() }));
+    let history = history_file();
+    if let Some(history) = &history {
+        let _ignore = editor.load_history(history);
+    }
+
+    let prompt = format!("{}> ", path);
+    let mut quitting = false;
+    loop {
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(CliError::Failed(err.to_string())),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let _ignore = editor.add_history_entry(line.as_str());
+
+        let (command, args) = split(&line);
+        if command == "QUIT" || command == "EXIT" {
+            if session.unsaved && !quitting {
+                println!("there are unsaved changes, SAVE them or QUIT again to discard them");
+                quitting = true;
+                continue;
+            }
+            break;
+        }
+        quitting = false;
+        match session.execute(&command, &args) {
+            Ok(Some(output)) => println!("{}", output),
+            Ok(None) => println!("OK"),
+            Err(err) => println!("(error) {}", err),
+        }
+        if let Some(helper) = editor.helper_mut() {
+            helper.keys = session.db.get_all();
+        }
+    }
+
+    if let Some(history) = &history {
+        let _ignore = editor.save_history(history);
+    }
+    if session.unsaved {
+        println!("unsaved changes were discarded");
+    }
+    // the changes were either saved or discarded, so they mustn't be dumped when the DB is dropped
+    session.db.set_dump_policy(PickleDbDumpPolicy::NeverDump);
+    Ok(())
+}
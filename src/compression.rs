@@ -0,0 +1,123 @@
+//! Compression of the DB file.
+//!
+//! DB files are JSON, which usually compresses well. A DB can be dumped compressed with gzip (requires the `gzip` feature)
+//! or zstd (requires the `zstd` feature), please see [PickleDb::set_compression()](../struct.PickleDb.html#method.set_compression).
+//! Loading a DB detects whether its file is compressed and with which algorithm, and the loaded DB keeps dumping its file
+//! the same way, so the rest of the API doesn't change.
+
+use std::io;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use std::io::{Read, Write};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+/// The start of an encrypted DB file, which is decrypted before it's decompressed
+pub(crate) const ENCRYPTED_MAGIC: &[u8] = b"PKLDBENC";
+
+/// The compression of a DB file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Compression {
+    /// The DB file is plain JSON
+    #[default]
+    None,
+    /// The DB file is compressed with gzip, in the default compression level
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// The DB file is compressed with zstd, in the default compression level
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+/// Compress the JSON text of a DB file
+pub(crate) fn encode(json: String, compression: Compression) -> io::Result<Vec<u8>> {
+    match compression {
+        Compression::None => Ok(json.into_bytes()),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(json.as_bytes())?;
+            encoder.finish()
+        },
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let mut encoder = zstd::Encoder::new(Vec::new(), zstd::DEFAULT_COMPRESSION_LEVEL)?;
+            encoder.write_all(json.as_bytes())?;
+            encoder.finish()
+        },
+    }
+}
+
+#[cfg(not(all(feature = "gzip", feature = "zstd")))]
+fn unsupported(algorithm: &str, feature: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("the DB file is compressed with {}, which requires the `{}` feature", algorithm, feature),
+    )
+}
+
+fn check_not_encrypted(contents: &[u8]) -> io::Result<()> {
+    if contents.starts_with(ENCRYPTED_MAGIC) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the DB file is encrypted, please load it with PickleDb::load_encrypted(), which requires the `encryption` feature",
+        ));
+    }
+    Ok(())
+}
+
+/// Detect the compression of the contents of a DB file and decompress them to the JSON text
+pub(crate) fn decode(contents: Vec<u8>) -> io::Result<(String, Compression)> {
+    check_not_encrypted(&contents)?;
+    if contents.starts_with(&GZIP_MAGIC) {
+        #[cfg(feature = "gzip")]
+        {
+            let mut json = String::new();
+            flate2::read::GzDecoder::new(&contents[..]).read_to_string(&mut json)?;
+            return Ok((json, Compression::Gzip));
+        }
+        #[cfg(not(feature = "gzip"))]
+        return Err(unsupported("gzip", "gzip"));
+    }
+    if contents.starts_with(&ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        {
+            let mut json = String::new();
+            zstd::Decoder::new(&contents[..])?.read_to_string(&mut json)?;
+            return Ok((json, Compression::Zstd));
+        }
+        #[cfg(not(feature = "zstd"))]
+        return Err(unsupported("zstd", "zstd"));
+    }
+    let json = String::from_utf8(contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok((json, Compression::None))
+}
+
+/// Decompress as much as possible of the contents of a damaged DB file, which may be truncated or corrupted
+pub(crate) fn decode_partially(contents: Vec<u8>) -> io::Result<(Vec<u8>, Compression)> {
+    check_not_encrypted(&contents)?;
+    if contents.starts_with(&GZIP_MAGIC) {
+        #[cfg(feature = "gzip")]
+        {
+            // the data that was decompressed before an error is kept
+            let mut decompressed = Vec::new();
+            let _ignore = flate2::read::GzDecoder::new(&contents[..]).read_to_end(&mut decompressed);
+            return Ok((decompressed, Compression::Gzip));
+        }
+        #[cfg(not(feature = "gzip"))]
+        return Err(unsupported("gzip", "gzip"));
+    }
+    if contents.starts_with(&ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        {
+            let mut decompressed = Vec::new();
+            if let Ok(mut decoder) = zstd::Decoder::new(&contents[..]) {
+                let _ignore = decoder.read_to_end(&mut decompressed);
+            }
+            return Ok((decompressed, Compression::Zstd));
+        }
+        #[cfg(not(feature = "zstd"))]
+        return Err(unsupported("zstd", "zstd"));
+    }
+    Ok((contents, Compression::None))
+}
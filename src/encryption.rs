@@ -0,0 +1,183 @@
+//! Encryption of the DB file.
+//!
+//! A DB can be encrypted with ChaCha20-Poly1305, an authenticated encryption, so the DB file can be neither read nor changed
+//! without the key. The key is either 32 random bytes or derived from a passphrase with Argon2id, please see
+//! [EncryptionKey](struct.EncryptionKey.html). An encrypted DB is created by
+//! [PickleDb::new_encrypted()](../struct.PickleDb.html#method.new_encrypted), loaded by
+//! [PickleDb::load_encrypted()](../struct.PickleDb.html#method.load_encrypted), and its key is changed by
+//! [PickleDb::rekey()](../struct.PickleDb.html#method.rekey). Loading with a wrong key, or loading a file that was changed,
+//! returns [Error::Decryption](../error/enum.Error.html#variant.Decryption).
+//!
+//! The whole DB file is encrypted, after it's compressed (please see
+//! [PickleDb::set_compression()](../struct.PickleDb.html#method.set_compression)). It starts with a header that holds the salt
+//! of the passphrase (if there is one) and a random nonce which is generated upon every dump. This module is available
+//! only when the `encryption` feature is enabled.
+
+use std::fmt;
+use std::io;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use crate::compression::ENCRYPTED_MAGIC;
+use crate::error::Error;
+
+const FORMAT_VERSION: u8 = 1;
+const KDF_NONE: u8 = 0;
+const KDF_ARGON2ID: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// The magic, the format version, the KDF and the salt, which are authenticated along with the encrypted contents
+const HEADER_LEN: usize = ENCRYPTED_MAGIC.len() + 2 + SALT_LEN;
+
+// the parameters of Argon2id are part of the file format, so they're fixed rather than taken from the crate's defaults
+const ARGON2_M_COST: u32 = 19 * 1024;
+const ARGON2_T_COST: u32 = 2;
+const ARGON2_P_COST: u32 = 1;
+
+#[derive(Clone)]
+enum Secret {
+    Key([u8; 32]),
+    Passphrase(String),
+}
+
+/// The key a DB file is encrypted with
+#[derive(Clone)]
+pub struct EncryptionKey {
+    secret: Secret,
+}
+
+impl EncryptionKey {
+    /// Create a key from 32 bytes, which should be random, for example generated by a key management service.
+    ///
+    pub fn new(key: [u8; 32]) -> EncryptionKey {
+        EncryptionKey { secret: Secret::Key(key) }
+    }
+
+    /// Create a key that is derived from a passphrase.
+    ///
+    /// The key is derived with Argon2id and a random salt that is stored in the DB file, which is deliberately slow,
+    /// so it's done only when an encrypted DB is created or loaded and when its key is changed.
+    ///
+    pub fn from_passphrase(passphrase: &str) -> EncryptionKey {
+        EncryptionKey { secret: Secret::Passphrase(String::from(passphrase)) }
+    }
+
+    fn kdf(&self) -> u8 {
+        match self.secret {
+            Secret::Key(_) => KDF_NONE,
+            Secret::Passphrase(_) => KDF_ARGON2ID,
+        }
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.secret {
+            Secret::Key(_) => write!(f, "EncryptionKey(<key>)"),
+            Secret::Passphrase(_) => write!(f, "EncryptionKey(<passphrase>)"),
+        }
+    }
+}
+
+fn internal_error<E: fmt::Display>(err: E) -> Error {
+    Error::Io(io::Error::other(err.to_string()))
+}
+
+/// A key that is ready for encrypting, along with the salt it was derived with
+pub(crate) struct Cipher {
+    key: EncryptionKey,
+    salt: [u8; SALT_LEN],
+    aead: ChaCha20Poly1305,
+}
+
+impl Cipher {
+    /// Prepare a key for encrypting a new file, with a new random salt if it's derived from a passphrase
+    pub(crate) fn new(key: EncryptionKey) -> Result<Cipher, Error> {
+        let mut salt = [0; SALT_LEN];
+        if key.kdf() == KDF_ARGON2ID {
+            getrandom::getrandom(&mut salt).map_err(internal_error)?;
+        }
+        Cipher::with_salt(key, salt)
+    }
+
+    fn with_salt(key: EncryptionKey, salt: [u8; SALT_LEN]) -> Result<Cipher, Error> {
+        let mut bytes = [0; 32];
+        match &key.secret {
+            Secret::Key(key) => bytes = *key,
+            Secret::Passphrase(passphrase) => {
+                let params = Params::new(ARGON2_M_COST, ARGON2_T_COST, ARGON2_P_COST, Some(bytes.len())).map_err(internal_error)?;
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(passphrase.as_bytes(), &salt, &mut bytes)
+                    .map_err(internal_error)?;
+            },
+        }
+        let aead = ChaCha20Poly1305::new(Key::from_slice(&bytes));
+        Ok(Cipher { key, salt, aead })
+    }
+
+    fn header(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(ENCRYPTED_MAGIC);
+        header.push(FORMAT_VERSION);
+        header.push(self.key.kdf());
+        header.extend_from_slice(&self.salt);
+        header
+    }
+
+    /// Encrypt the contents of a DB file
+    pub(crate) fn encrypt(&self, contents: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut nonce = [0; NONCE_LEN];
+        getrandom::getrandom(&mut nonce).map_err(internal_error)?;
+        let mut encrypted = self.header();
+        let sealed = self.aead.encrypt(Nonce::from_slice(&nonce), Payload { msg: contents, aad: &encrypted })
+            .map_err(|_| Error::Decryption)?;
+        encrypted.extend_from_slice(&nonce);
+        encrypted.extend_from_slice(&sealed);
+        Ok(encrypted)
+    }
+
+    /// Decrypt the contents of a DB file that was encrypted with the same key, though maybe with another salt
+    pub(crate) fn decrypt(&self, contents: &[u8]) -> Result<Vec<u8>, Error> {
+        let (kdf, salt) = parse_header(contents)?;
+        if kdf != self.key.kdf() {
+            return Err(Error::Decryption);
+        }
+        if salt == self.salt {
+            open(&self.aead, contents)
+        } else {
+            open(&Cipher::with_salt(self.key.clone(), salt)?.aead, contents)
+        }
+    }
+}
+
+fn parse_header(contents: &[u8]) -> Result<(u8, [u8; SALT_LEN]), Error> {
+    if contents.len() < HEADER_LEN + NONCE_LEN || !contents.starts_with(ENCRYPTED_MAGIC) {
+        return Err(Error::Decryption);
+    }
+    let version = contents[ENCRYPTED_MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return Err(internal_error(format!("unsupported version {} of the encrypted DB file format", version)));
+    }
+    let kdf = contents[ENCRYPTED_MAGIC.len() + 1];
+    let mut salt = [0; SALT_LEN];
+    salt.copy_from_slice(&contents[HEADER_LEN - SALT_LEN..HEADER_LEN]);
+    Ok((kdf, salt))
+}
+
+fn open(aead: &ChaCha20Poly1305, contents: &[u8]) -> Result<Vec<u8>, Error> {
+    let (header, rest) = contents.split_at(HEADER_LEN);
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    aead.decrypt(Nonce::from_slice(nonce), Payload { msg: sealed, aad: header }).map_err(|_| Error::Decryption)
+}
+
+/// Decrypt the contents of a DB file with a key, and return them along with the key prepared with the salt of the file,
+/// so the file can be encrypted again without deriving the key again
+pub(crate) fn decrypt(contents: &[u8], key: EncryptionKey) -> Result<(Vec<u8>, Cipher), Error> {
+    let (kdf, salt) = parse_header(contents)?;
+    if kdf != key.kdf() {
+        return Err(Error::Decryption);
+    }
+    let cipher = Cipher::with_salt(key, salt)?;
+    let decrypted = open(&cipher.aead, contents)?;
+    Ok((decrypted, cipher))
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+use std::io;
+
+/// An enum that represents the errors PickleDB APIs may return
+#[derive(Debug)]
+pub enum Error {
+    /// Reading from or writing to the DB file failed
+    Io(io::Error),
+    /// A value couldn't be serialized or deserialized
+    Serialization(serde_json::Error),
+    /// The value stored under a key is not of the type that was requested.
+    /// `key` is the key (or list name) and `reason` describes what didn't match
+    TypeMismatch { key: String, reason: String },
+    /// A JSON path or a query couldn't be parsed. `query` is the text that was given and `reason` describes the problem
+    InvalidQuery { query: String, reason: String },
+    /// Setting the value of `key` would violate the unique index `index`,
+    /// because `existing_key` already has the same value in the indexed field
+    ConstraintViolation { index: String, key: String, existing_key: String },
+    /// A JSON Schema is invalid or uses keywords that aren't supported
+    InvalidSchema(String),
+    /// A value or a list item doesn't conform to the schema of its key or list. `key` is the key or list name,
+    /// `path` is the JSON path inside the value in which the problem was found and `reason` describes the problem
+    SchemaViolation { key: String, path: String, reason: String },
+    /// A before-mutation hook vetoed a change of `key`, `reason` is the error the hook returned
+    Vetoed { key: String, reason: String },
+    /// An encrypted DB file couldn't be decrypted, either because the key is wrong or because the file was changed
+    Decryption,
+    /// The content of the DB file doesn't match its checksum, the string describes the mismatch
+    Corrupted(String),
+    /// An imported key or list already exists in the DB, the string is its name
+    Conflict(String),
+    /// An imported file isn't in the format it was imported as. `line` is the line in which the problem was found
+    /// and `reason` describes the problem
+    InvalidImport { line: usize, reason: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::Serialization(err) => write!(f, "serialization error: {}", err),
+            Error::TypeMismatch { key, reason } => write!(f, "type mismatch in '{}': {}", key, reason),
+            Error::InvalidQuery { query, reason } => write!(f, "invalid query '{}': {}", query, reason),
+            Error::ConstraintViolation { index, key, existing_key } =>
+                write!(f, "value of '{}' violates unique index '{}': '{}' has the same value", key, index, existing_key),
+            Error::InvalidSchema(reason) => write!(f, "invalid schema: {}", reason),
+            Error::SchemaViolation { key, path, reason } => write!(f, "value of '{}' doesn't conform to its schema at {}: {}", key, path, reason),
+            Error::Vetoed { key, reason } => write!(f, "change of '{}' was vetoed by a hook: {}", key, reason),
+            Error::Decryption => write!(f, "the DB file couldn't be decrypted: the key is wrong or the file was changed"),
+            Error::Corrupted(reason) => write!(f, "the DB file is corrupted: {}", reason),
+            Error::Conflict(key) => write!(f, "'{}' already exists in the DB", key),
+            Error::InvalidImport { line, reason } => write!(f, "invalid import at line {}: {}", line, reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Serialization(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Error {
+        Error::Serialization(err)
+    }
+}
@@ -0,0 +1,291 @@
+//! Exporting the keys and lists of a DB for other tools, and importing them back.
+//!
+//! Please see [PickleDb::export()](../struct.PickleDb.html#method.export) and [PickleDb::import()](../struct.PickleDb.html#method.import).
+//! Files are written and read entry by entry, so exporting or importing a large DB doesn't build the whole file in memory.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{BufRead, Write};
+use std::mem;
+use serde::de::{self, Deserializer as _, MapAccess, Visitor};
+use serde::ser::{SerializeMap, Serializer as _};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::error::Error;
+
+/// The format of an exported file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Newline-delimited JSON, in which every line is an object such as `{"key":"key1","type":"value","value":100}`.
+    /// `type` is either `value` or `list`, and the value of a list is the array of its items. This is the only format
+    /// that holds lists as well as values
+    Ndjson,
+    /// CSV with a `key,value` header and a row for every key. Strings are written as they are and other values as JSON,
+    /// and importing parses every value as JSON, or takes it as a string if it isn't valid JSON. It can't hold lists, so
+    /// a DB that has lists can't be exported in it
+    Csv,
+    /// A pretty-printed JSON object whose members are the keys and their values, for example `{"key1": 100}`.
+    /// It can't hold lists, so a DB that has lists can't be exported in it
+    Json,
+}
+
+/// What importing does with keys and lists that already exist in the DB
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// The imported keys and lists replace the existing ones
+    Overwrite,
+    /// The imported keys and lists that already exist are skipped
+    Skip,
+    /// Nothing is imported if any of the imported keys or lists already exists, and
+    /// [Error::Conflict](../error/enum.Error.html#variant.Conflict) is returned
+    Fail,
+}
+
+/// A key and its value, or a list and its items, as they're read from an exported file
+pub(crate) enum Entry {
+    Value(String, Value),
+    List(String, Vec<Value>),
+}
+
+impl Entry {
+    pub(crate) fn key(&self) -> &str {
+        match self {
+            Entry::Value(key, _) | Entry::List(key, _) => key,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct NdjsonLine<'a, V> {
+    key: &'a str,
+    #[serde(rename = "type")]
+    kind: &'a str,
+    value: &'a V,
+}
+
+#[derive(Deserialize)]
+struct OwnedNdjsonLine {
+    key: String,
+    #[serde(rename = "type")]
+    kind: String,
+    value: Value,
+}
+
+/// The text of a value in CSV. Strings that would be parsed as JSON upon import are written as JSON strings, so they're
+/// imported as the same strings
+fn csv_text(value: &Value) -> String {
+    match value {
+        Value::String(text) if serde_json::from_str::<Value>(text).is_err() => text.clone(),
+        _ => value.to_string(),
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        String::from(text)
+    }
+}
+
+/// The reason of a JSON error, without the position which is relative to what was parsed rather than to the file
+fn json_reason(err: &serde_json::Error) -> String {
+    let reason = err.to_string();
+    match reason.rfind(" at line ") {
+        Some(pos) => String::from(&reason[..pos]),
+        None => reason,
+    }
+}
+
+/// Check that a format can hold the lists of a DB before anything is written. Only NDJSON holds lists, and an
+/// [Error::TypeMismatch](../error/enum.Error.html#variant.TypeMismatch) of the first list is returned for the other formats
+pub(crate) fn check_lists(format: ExportFormat, lists: &[(&String, &Vec<Value>)]) -> Result<(), Error> {
+    match lists.first() {
+        Some((name, _)) if format != ExportFormat::Ndjson => Err(Error::TypeMismatch {
+            key: String::from(name.as_str()),
+            reason: format!("lists can't be exported as {}, please export them as NDJSON", if format == ExportFormat::Csv { "CSV" } else { "JSON" }),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Write the values and the lists of a DB in a format, and return how many of them were written
+pub(crate) fn write<W: Write>(
+    mut writer: W,
+    format: ExportFormat,
+    values: &[(&String, &Value)],
+    lists: &[(&String, &Vec<Value>)],
+) -> Result<usize, Error> {
+    match format {
+        ExportFormat::Ndjson => {
+            for (key, value) in values {
+                serde_json::to_writer(&mut writer, &NdjsonLine { key, kind: "value", value: *value })?;
+                writeln!(writer)?;
+            }
+            for (name, items) in lists {
+                serde_json::to_writer(&mut writer, &NdjsonLine { key: name, kind: "list", value: *items })?;
+                writeln!(writer)?;
+            }
+            Ok(values.len() + lists.len())
+        },
+        ExportFormat::Csv => {
+            writeln!(writer, "key,value")?;
+            for (key, value) in values {
+                writeln!(writer, "{},{}", csv_field(key), csv_field(&csv_text(value)))?;
+            }
+            Ok(values.len())
+        },
+        ExportFormat::Json => {
+            let mut serializer = serde_json::Serializer::pretty(&mut writer);
+            let mut map = serializer.serialize_map(Some(values.len()))?;
+            for (key, value) in values {
+                map.serialize_entry(key, value)?;
+            }
+            map.end()?;
+            writeln!(writer)?;
+            Ok(values.len())
+        },
+    }
+}
+
+/// Read the entries of an exported file one by one, and hand each of them to `f` as soon as it's read
+pub(crate) fn read<R, F>(reader: R, format: ExportFormat, mut f: F) -> Result<(), Error>
+    where
+        R: BufRead,
+        F: FnMut(Entry) -> Result<(), Error>
+{
+    match format {
+        ExportFormat::Ndjson => read_ndjson(reader, f),
+        ExportFormat::Csv => read_csv(reader, f),
+        ExportFormat::Json => {
+            let mut deserializer = serde_json::Deserializer::from_reader(reader);
+            let mut error = None;
+            let result = deserializer.deserialize_map(EntryVisitor { f: &mut f, error: &mut error })
+                .and_then(|()| deserializer.end());
+            if let Some(err) = error {
+                return Err(err);
+            }
+            result.map_err(|err| Error::InvalidImport { line: err.line(), reason: json_reason(&err) })
+        },
+    }
+}
+
+fn read_ndjson<R, F>(reader: R, mut f: F) -> Result<(), Error>
+    where
+        R: BufRead,
+        F: FnMut(Entry) -> Result<(), Error>
+{
+    for (pos, text) in reader.lines().enumerate() {
+        let text = text?;
+        if text.trim().is_empty() {
+            continue;
+        }
+        let line = pos + 1;
+        let parsed: OwnedNdjsonLine = serde_json::from_str(&text)
+            .map_err(|err| Error::InvalidImport { line, reason: json_reason(&err) })?;
+        let entry = match (parsed.kind.as_str(), parsed.value) {
+            ("value", value) => Entry::Value(parsed.key, value),
+            ("list", Value::Array(items)) => Entry::List(parsed.key, items),
+            ("list", _) => return Err(Error::InvalidImport { line, reason: String::from("the value of a list must be an array") }),
+            (kind, _) => return Err(Error::InvalidImport { line, reason: format!("unknown type '{}'", kind) }),
+        };
+        f(entry)?;
+    }
+    Ok(())
+}
+
+fn read_csv<R, F>(mut reader: R, mut f: F) -> Result<(), Error>
+    where
+        R: BufRead,
+        F: FnMut(Entry) -> Result<(), Error>
+{
+    let mut line = 0;
+    match read_csv_record(&mut reader, &mut line)? {
+        Some(header) if header == ["key", "value"] => (),
+        _ => return Err(Error::InvalidImport { line: 1, reason: String::from("the first row must be a 'key,value' header") }),
+    }
+    loop {
+        let start = line + 1;
+        let record = match read_csv_record(&mut reader, &mut line)? {
+            Some(record) => record,
+            None => return Ok(()),
+        };
+        match <[String; 2]>::try_from(record) {
+            Ok([key, value]) => {
+                let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+                f(Entry::Value(key, value))?;
+            },
+            Err(record) if record == [""] => continue,
+            Err(record) => return Err(Error::InvalidImport { line: start, reason: format!("expected 2 fields, found {}", record.len()) }),
+        }
+    }
+}
+
+/// Read the fields of a CSV record, which spans several lines if a quoted field contains line breaks
+fn read_csv_record<R: BufRead>(reader: &mut R, line: &mut usize) -> Result<Option<Vec<String>>, Error> {
+    let start = *line + 1;
+    let mut text = String::new();
+    if reader.read_line(&mut text)? == 0 {
+        return Ok(None);
+    }
+    *line += 1;
+
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    loop {
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                },
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') if field.is_empty() => quoted = true,
+                (false, ',') => fields.push(mem::take(&mut field)),
+                (false, '\r') | (false, '\n') => (),
+                (false, c) => field.push(c),
+            }
+        }
+        if !quoted {
+            break;
+        }
+        text.clear();
+        if reader.read_line(&mut text)? == 0 {
+            return Err(Error::InvalidImport { line: start, reason: String::from("a quoted field isn't terminated") });
+        }
+        *line += 1;
+    }
+    fields.push(field);
+    Ok(Some(fields))
+}
+
+/// Hands the members of a JSON object to a function as they're parsed
+struct EntryVisitor<'a, F> {
+    f: &'a mut F,
+    /// The error the function returned, which stops the parsing
+    error: &'a mut Option<Error>,
+}
+
+impl<'de, F> Visitor<'de> for EntryVisitor<'_, F>
+    where
+        F: FnMut(Entry) -> Result<(), Error>
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some((key, value)) = map.next_entry::<String, Value>()? {
+            if let Err(err) = (self.f)(Entry::Value(key, value)) {
+                *self.error = Some(err);
+                return Err(de::Error::custom("the import was stopped"));
+            }
+        }
+        Ok(())
+    }
+}
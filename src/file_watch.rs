@@ -0,0 +1,142 @@
+//! Reloading a DB when its file is changed by someone else.
+//!
+//! [PickleDb::watch_file()](../struct.PickleDb.html#method.watch_file) turns a DB into a
+//! [WatchedPickleDb](struct.WatchedPickleDb.html), which watches the DB file and reloads the key-value pairs and lists
+//! whenever the file changes. The platform's file notifications (inotify on Linux) are used when they're available,
+//! otherwise the file is polled. This module is available only when the `file-watch` feature is enabled.
+
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::Duration;
+use notify::event::{AccessKind, AccessMode, MetadataKind, ModifyKind};
+use notify::{Config, Event, EventKind, PollWatcher, RecursiveMode, Watcher};
+use crate::error::Error;
+use crate::PickleDb;
+
+/// The interval in which the DB file is polled when the platform's file notifications aren't available
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A DB whose file is watched, which reloads the key-value pairs and lists whenever the file changes.
+///
+/// It's created by [PickleDb::watch_file()](../struct.PickleDb.html#method.watch_file) or
+/// [PickleDb::watch_file_polling()](../struct.PickleDb.html#method.watch_file_polling). Since reloading happens
+/// on a background thread, the DB is accessed through [lock()](#method.lock). Every reload is done by
+/// [PickleDb::reload()](../struct.PickleDb.html#method.reload), so the new content is validated before it replaces
+/// the data in memory and subscribers are notified of the keys that changed. If a reload fails, for example because
+/// the file was only partially written, the data in memory is kept and the error can be retrieved by
+/// [take_reload_error()](#method.take_reload_error). The next change of the file triggers another reload.
+///
+/// Dropping it stops watching the file and drops the DB.
+pub struct WatchedPickleDb {
+    // declared first so the watcher thread stops before the DB is dropped
+    _watcher: Box<dyn Watcher + Send>,
+    db: Arc<Mutex<PickleDb>>,
+    reload_error: Arc<Mutex<Option<Error>>>,
+    polling: bool,
+}
+
+fn watch_error(err: notify::Error) -> Error {
+    Error::Io(io::Error::other(err.to_string()))
+}
+
+/// Changes that may have changed the content of the file. Opening, reading and closing the file without writing,
+/// which also happen when the file is reloaded, are ignored
+fn is_content_change(kind: &EventKind) -> bool {
+    match kind {
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+        EventKind::Access(_) => false,
+        EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime)) => false,
+        _ => true,
+    }
+}
+
+impl WatchedPickleDb {
+    pub(crate) fn new(db: PickleDb, poll_interval: Option<Duration>) -> Result<WatchedPickleDb, Error> {
+        let path = PathBuf::from(&db.db_file_path);
+        let file_name: OsString = path.file_name()
+            .ok_or_else(|| Error::Io(io::Error::new(io::ErrorKind::InvalidInput, "the DB file path has no file name")))?
+            .to_os_string();
+        // the directory is watched and not the file itself, so a file that is replaced keeps being watched
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let db = Arc::new(Mutex::new(db));
+        let reload_error = Arc::new(Mutex::new(None));
+        let handler = {
+            let db = Arc::downgrade(&db);
+            let reload_error = Arc::downgrade(&reload_error);
+            move |event: notify::Result<Event>| on_event(event, &file_name, &db, &reload_error)
+        };
+
+        let (watcher, polling): (Box<dyn Watcher + Send>, bool) = match poll_interval {
+            Some(interval) => (poll_watcher(handler, &dir, interval)?, true),
+            None => match native_watcher(handler.clone(), &dir) {
+                Ok(watcher) => (watcher, false),
+                Err(_) => (poll_watcher(handler, &dir, DEFAULT_POLL_INTERVAL)?, true),
+            },
+        };
+
+        Ok(WatchedPickleDb { _watcher: watcher, db, reload_error, polling })
+    }
+
+    /// Lock the DB in order to read or change it. File changes aren't reloaded while the lock is held.
+    ///
+    pub fn lock(&self) -> MutexGuard<'_, PickleDb> {
+        self.db.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Get the error of the last reload that failed, if any, and clear it.
+    ///
+    pub fn take_reload_error(&self) -> Option<Error> {
+        self.reload_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take()
+    }
+
+    /// Check whether the file is polled, either because polling was requested or because the platform's
+    /// file notifications aren't available.
+    ///
+    pub fn is_polling(&self) -> bool {
+        self.polling
+    }
+}
+
+fn native_watcher<F>(handler: F, dir: &Path) -> Result<Box<dyn Watcher + Send>, Error>
+    where
+        F: Fn(notify::Result<Event>) + Send + 'static
+{
+    let mut watcher = notify::recommended_watcher(handler).map_err(watch_error)?;
+    watcher.watch(dir, RecursiveMode::NonRecursive).map_err(watch_error)?;
+    Ok(Box::new(watcher))
+}
+
+fn poll_watcher<F>(handler: F, dir: &Path, interval: Duration) -> Result<Box<dyn Watcher + Send>, Error>
+    where
+        F: Fn(notify::Result<Event>) + Send + 'static
+{
+    let config = Config::default().with_poll_interval(interval).with_compare_contents(true);
+    let mut watcher = PollWatcher::new(handler, config).map_err(watch_error)?;
+    watcher.watch(dir, RecursiveMode::NonRecursive).map_err(watch_error)?;
+    Ok(Box::new(watcher))
+}
+
+fn on_event(event: notify::Result<Event>, file_name: &OsString, db: &Weak<Mutex<PickleDb>>, reload_error: &Weak<Mutex<Option<Error>>>) {
+    let event = match event {
+        Ok(event) => event,
+        Err(_) => return,
+    };
+    if !is_content_change(&event.kind) || !event.paths.iter().any(|path| path.file_name() == Some(file_name.as_os_str())) {
+        return;
+    }
+
+    let (db, reload_error) = match (db.upgrade(), reload_error.upgrade()) {
+        (Some(db), Some(reload_error)) => (db, reload_error),
+        _ => return,
+    };
+    let res = db.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).reload();
+    if let Err(err) = res {
+        *reload_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(err);
+    }
+}
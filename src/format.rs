@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use serde_json::Value;
+use serde::ser::{SerializeMap, Serializer as _};
+use serde_derive::{Deserialize, Serialize};
+use crate::error::Error;
+use crate::index::Index;
+use crate::namespace::NamespaceData;
+use crate::schema::Schema;
+
+/// The version of the layout of the DB file, which is stored in its first field. Files that have it always have a checksum
+pub(crate) const VERSION: u32 = 1;
+
+/// The last field of the DB file, which holds the CRC32C checksum of the JSON text before it
+const CHECKSUM_FIELD: &str = ",\"checksum\":\"crc32c:";
+
+/// The layout of the DB file: a JSON object with the version of the layout, the key-value map, the lists and the
+/// namespaces, in which all values are stored as plain nested JSON, followed by a checksum
+#[derive(Deserialize)]
+pub(crate) struct DbFile {
+    pub(crate) map: HashMap<String, Value>,
+    pub(crate) list_map: HashMap<String, Vec<Value>>,
+    #[serde(default)]
+    pub(crate) namespaces: HashMap<String, NamespaceData>,
+    /// The definitions of the secondary indexes, their entries are rebuilt upon load
+    #[serde(default)]
+    pub(crate) indexes: HashMap<String, Index>,
+    /// The schemas of values, by key prefix
+    #[serde(default)]
+    pub(crate) key_schemas: HashMap<String, Schema>,
+    /// The schemas of list items, by list name
+    #[serde(default)]
+    pub(crate) list_schemas: HashMap<String, Schema>,
+}
+
+/// Same as `DbFile`, but borrows the data so dumping doesn't need to copy it
+#[derive(Serialize)]
+pub(crate) struct DbFileRef<'a> {
+    pub(crate) format: u32,
+    pub(crate) map: &'a HashMap<String, Value>,
+    pub(crate) list_map: &'a HashMap<String, Vec<Value>>,
+    pub(crate) namespaces: &'a HashMap<String, NamespaceData>,
+    pub(crate) indexes: &'a HashMap<String, Index>,
+    pub(crate) key_schemas: &'a HashMap<String, Schema>,
+    pub(crate) list_schemas: &'a HashMap<String, Schema>,
+    /// Whether the file is written in the format of Python's pickleDB rather than in this layout
+    #[serde(skip)]
+    pub(crate) python_compat: bool,
+}
+
+impl DbFileRef<'_> {
+    /// Serialize the data to the JSON text of a DB file, which ends with the checksum of the text before it
+    pub(crate) fn to_json(&self) -> Result<String, serde_json::Error> {
+        if self.python_compat {
+            return self.to_python_json();
+        }
+        let mut json = serde_json::to_string(self)?;
+        // the closing brace of the object, which comes after the checksum
+        json.pop();
+        let checksum = crc32c::crc32c(json.as_bytes());
+        json.push_str(&format!("{}{:08x}\"}}", CHECKSUM_FIELD, checksum));
+        Ok(json)
+    }
+
+    /// Serialize the keys and the lists to the format of Python's pickleDB: a JSON object of the keys and their values,
+    /// in which lists are arrays. The other data of the DB has no place in this format, and there's no checksum
+    fn to_python_json(&self) -> Result<String, serde_json::Error> {
+        let mut json = Vec::new();
+        let mut serializer = serde_json::Serializer::new(&mut json);
+        let mut object = serializer.serialize_map(Some(self.map.len() + self.list_map.len()))?;
+        for (key, value) in self.map.iter() {
+            object.serialize_entry(key, value)?;
+        }
+        for (name, items) in self.list_map.iter() {
+            object.serialize_entry(name, items)?;
+        }
+        object.end()?;
+        Ok(String::from_utf8(json).expect("JSON is UTF-8"))
+    }
+}
+
+/// Check the checksum of the JSON text of a DB file. Files without a version, which were written by older versions
+/// or by hand, may have no checksum and then aren't checked, but a missing checksum is corruption in the other files
+pub(crate) fn verify(json: &str) -> Result<(), Error> {
+    let json = json.trim_end();
+    let pos = match json.rfind(CHECKSUM_FIELD) {
+        Some(pos) => pos,
+        None => {
+            // content that can't be parsed is reported when the file is parsed
+            let has_version = serde_json::from_str::<HashMap<String, Value>>(json)
+                .is_ok_and(|object| object.contains_key("format"));
+            if has_version {
+                return Err(Error::Corrupted(String::from("the checksum is missing")));
+            }
+            return Ok(());
+        },
+    };
+    let stored = json[pos + CHECKSUM_FIELD.len()..].strip_suffix("\"}")
+        .filter(|hex| hex.len() == 8)
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .ok_or_else(|| Error::Corrupted(String::from("the checksum can't be read")))?;
+    let actual = crc32c::crc32c(&json.as_bytes()[..pos]);
+    if stored != actual {
+        return Err(Error::Corrupted(format!("checksum mismatch, expected {:08x} but the content has {:08x}", stored, actual)));
+    }
+    Ok(())
+}
+
+/// The layout of DB files written by older versions: a JSON array of the key-value map and the lists,
+/// in which every value is a string containing the value's JSON text
+#[derive(Deserialize)]
+struct LegacyDbFile(HashMap<String, String>, HashMap<String, Vec<String>>);
+
+fn decode_map(map: HashMap<String, String>) -> Result<HashMap<String, Value>, serde_json::Error> {
+    map.into_iter()
+        .map(|(key, val_as_string)| Ok((key, serde_json::from_str(&val_as_string)?)))
+        .collect()
+}
+
+fn decode_list_map(list_map: HashMap<String, Vec<String>>) -> Result<HashMap<String, Vec<Value>>, serde_json::Error> {
+    list_map.into_iter()
+        .map(|(name, list)| {
+            let list = list.iter()
+                .map(|item_as_string| serde_json::from_str(item_as_string))
+                .collect::<Result<Vec<Value>, _>>()?;
+            Ok((name, list))
+        })
+        .collect()
+}
+
+impl LegacyDbFile {
+    fn decode(self) -> Result<DbFile, serde_json::Error> {
+        Ok(DbFile {
+            map: decode_map(self.0)?,
+            list_map: decode_list_map(self.1)?,
+            namespaces: HashMap::new(),
+            indexes: HashMap::new(),
+            key_schemas: HashMap::new(),
+            list_schemas: HashMap::new(),
+        })
+    }
+}
+
+/// Parse the contents of a DB file. Both the current format and the legacy format are supported
+pub(crate) fn parse(contents: &str) -> Result<DbFile, serde_json::Error> {
+    if contents.trim_start().starts_with('[') {
+        serde_json::from_str::<LegacyDbFile>(contents)?.decode()
+    } else {
+        serde_json::from_str(contents).map_err(|err| {
+            let object = serde_json::from_str::<HashMap<String, Value>>(contents);
+            if err.is_data() && object.is_ok_and(|object| !object.contains_key("map")) {
+                return serde::de::Error::custom("this isn't a PickleDB file, files of Python's PickleDB are loaded by load_python_compat()");
+            }
+            err
+        })
+    }
+}
+
+/// Parse a DB file of Python's pickleDB, which is a JSON object of the keys and their values. Arrays are lists, since
+/// that's how Python's `lcreate()` stores them, and the other values are the values of keys
+pub(crate) fn parse_python(contents: &str) -> Result<DbFile, serde_json::Error> {
+    let object: HashMap<String, Value> = serde_json::from_str(contents)?;
+    let mut map = HashMap::new();
+    let mut list_map = HashMap::new();
+    for (key, value) in object {
+        match value {
+            Value::Array(items) => {
+                list_map.insert(key, items);
+            },
+            value => {
+                map.insert(key, value);
+            },
+        }
+    }
+    Ok(DbFile {
+        map,
+        list_map,
+        namespaces: HashMap::new(),
+        indexes: HashMap::new(),
+        key_schemas: HashMap::new(),
+        list_schemas: HashMap::new(),
+    })
+}
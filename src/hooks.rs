@@ -0,0 +1,153 @@
+//! Hooks that run before and after the DB is changed.
+//!
+//! A before-mutation hook is registered by [PickleDb::add_before_hook()](../struct.PickleDb.html#method.add_before_hook)
+//! and receives every [Mutation](struct.Mutation.html) before it's applied. It may change the value being set or added,
+//! for example to stamp an `updated_at` field, or veto the mutation by returning an error. An after-mutation hook is
+//! registered by [PickleDb::add_after_hook()](../struct.PickleDb.html#method.add_after_hook) and receives a
+//! [ChangeEvent](../watch/struct.ChangeEvent.html) for every change that was applied, for example for auditing or for
+//! invalidating a cache.
+//!
+//! Hooks run in the order they were registered, each before-mutation hook receiving the mutation as the previous one
+//! left it. Hooks aren't stored in the DB file. Changes inside namespaces run the hooks of the DB as well, and
+//! [Mutation::namespace()](struct.Mutation.html#method.namespace) and the `namespace` of the
+//! [ChangeEvent](../watch/struct.ChangeEvent.html) tell which namespace was changed.
+
+use serde_json::Value;
+use crate::error::Error;
+use crate::watch::{ChangeEvent, ChangeKind};
+
+/// A mutation that is about to be applied to the DB.
+///
+/// The kinds of mutations and the values they carry are:
+/// * `Set` - [PickleDb::set()](../struct.PickleDb.html#method.set), the value being set
+/// * `ListPush` - [PickleDb::ladd()](../struct.PickleDb.html#method.ladd) and
+///   [PickleDb::lextend()](../struct.PickleDb.html#method.lextend), the item being added. Every item is a separate mutation
+/// * `ListRemoveValue` - [PickleDb::lrem_value()](../struct.PickleDb.html#method.lrem_value), the item to remove
+/// * `Removed`, `ListCreated`, `ListPop` and `ListRemoved` - [PickleDb::rem()](../struct.PickleDb.html#method.rem),
+///   [PickleDb::lcreate()](../struct.PickleDb.html#method.lcreate), [PickleDb::lpop()](../struct.PickleDb.html#method.lpop)
+///   and [PickleDb::lrem_list()](../struct.PickleDb.html#method.lrem_list), no value
+#[derive(Debug)]
+pub struct Mutation {
+    key: String,
+    kind: ChangeKind,
+    value: Option<Value>,
+    namespace: Option<String>,
+}
+
+impl Mutation {
+    /// Get the key or list name the mutation applies to.
+    ///
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Get the namespace the key or list is in, or `None` if it's a key or list of the DB itself.
+    ///
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// Get the kind of the mutation.
+    ///
+    pub fn kind(&self) -> ChangeKind {
+        self.kind
+    }
+
+    /// Get the value the mutation carries, if any.
+    ///
+    pub fn value(&self) -> Option<&Value> {
+        self.value.as_ref()
+    }
+
+    /// Get a mutable reference to the value the mutation carries, if any, in order to change it in place.
+    ///
+    pub fn value_mut(&mut self) -> Option<&mut Value> {
+        self.value.as_mut()
+    }
+
+    /// Replace the value the mutation carries. This has no effect on mutations that don't carry a value.
+    ///
+    pub fn set_value(&mut self, value: Value) {
+        if self.value.is_some() {
+            self.value = Some(value);
+        }
+    }
+}
+
+/// An identifier of a registered hook, which can be used to remove it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HookId(u64);
+
+type BeforeHook = Box<dyn FnMut(&mut Mutation) -> Result<(), String> + Send>;
+type AfterHook = Box<dyn FnMut(&ChangeEvent) + Send>;
+
+/// The hooks registered on a DB
+#[derive(Default)]
+pub(crate) struct Hooks {
+    next_id: u64,
+    before: Vec<(HookId, BeforeHook)>,
+    after: Vec<(HookId, AfterHook)>,
+}
+
+impl Hooks {
+    fn next_id(&mut self) -> HookId {
+        self.next_id += 1;
+        HookId(self.next_id)
+    }
+
+    pub(crate) fn add_before(&mut self, hook: BeforeHook) -> HookId {
+        let id = self.next_id();
+        self.before.push((id, hook));
+        id
+    }
+
+    pub(crate) fn add_after(&mut self, hook: AfterHook) -> HookId {
+        let id = self.next_id();
+        self.after.push((id, hook));
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: HookId) -> bool {
+        let count = self.before.len() + self.after.len();
+        self.before.retain(|(hook_id, _)| *hook_id != id);
+        self.after.retain(|(hook_id, _)| *hook_id != id);
+        count != self.before.len() + self.after.len()
+    }
+
+    fn run_before(&mut self, ns: Option<&str>, key: &str, kind: ChangeKind, value: Option<Value>) -> Result<Option<Value>, Error> {
+        if self.before.is_empty() {
+            return Ok(value);
+        }
+
+        let mut mutation = Mutation { key: String::from(key), kind, value, namespace: ns.map(String::from) };
+        for (_, hook) in &mut self.before {
+            hook(&mut mutation).map_err(|reason| Error::Vetoed { key: String::from(key), reason })?;
+        }
+        Ok(mutation.value)
+    }
+
+    /// Run the before-mutation hooks on a mutation that doesn't carry a value.
+    /// An [Error::Vetoed](../error/enum.Error.html#variant.Vetoed) is returned if one of them vetoed it
+    pub(crate) fn before(&mut self, ns: Option<&str>, key: &str, kind: ChangeKind) -> Result<(), Error> {
+        self.run_before(ns, key, kind, None).map(|_| ())
+    }
+
+    /// Run the before-mutation hooks on a mutation that carries a value and return the value as they left it.
+    /// An [Error::Vetoed](../error/enum.Error.html#variant.Vetoed) is returned if one of them vetoed it
+    pub(crate) fn before_value(&mut self, ns: Option<&str>, key: &str, kind: ChangeKind, value: Value) -> Result<Value, Error> {
+        Ok(self.run_before(ns, key, kind, Some(value))?.unwrap_or(Value::Null))
+    }
+
+    /// Run the after-mutation hooks on a change that was applied.
+    /// The values are cloned only if there are such hooks
+    pub(crate) fn after(&mut self, ns: Option<&str>, key: &str, kind: ChangeKind, old: Option<&Value>, new: Option<&Value>) {
+        if self.after.is_empty() {
+            return;
+        }
+
+        let event = ChangeEvent { key: String::from(key), kind, old: old.cloned(), new: new.cloned(), namespace: ns.map(String::from) };
+        for (_, hook) in &mut self.after {
+            hook(&event);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::{BTreeSet, HashMap};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::error::Error;
+use crate::query::JsonPath;
+
+/// A secondary index over the values of a key-value map.
+///
+/// The index maps the JSON representation of the part of each value its path points to,
+/// to the keys of the values that contain it. Only keys that start with the index key prefix are indexed,
+/// and values in which the path doesn't exist aren't indexed. A unique index doesn't allow two keys
+/// to have the same indexed value.
+///
+/// Only the definition of the index is stored in the DB file, the entries are rebuilt upon load
+#[derive(Clone)]
+pub(crate) struct Index {
+    pub(crate) path: JsonPath,
+    key_prefix: String,
+    unique: bool,
+    entries: HashMap<String, BTreeSet<String>>,
+}
+
+/// The way an index definition is stored in the DB file
+#[derive(Serialize, Deserialize)]
+struct IndexDef {
+    path: String,
+    #[serde(default)]
+    key_prefix: String,
+    #[serde(default)]
+    unique: bool,
+}
+
+/// Older DB files store only the path of each index
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IndexRepr {
+    Path(String),
+    Def(IndexDef),
+}
+
+impl Serialize for Index {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        IndexDef { path: self.path.to_string(), key_prefix: self.key_prefix.clone(), unique: self.unique }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Index {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Index, D::Error> {
+        let def = match IndexRepr::deserialize(deserializer)? {
+            IndexRepr::Path(path) => IndexDef { path, key_prefix: String::new(), unique: false },
+            IndexRepr::Def(def) => def,
+        };
+        Ok(Index {
+            path: JsonPath::parse(&def.path).map_err(de::Error::custom)?,
+            key_prefix: def.key_prefix,
+            unique: def.unique,
+            entries: HashMap::new(),
+        })
+    }
+}
+
+/// The representation of an indexed value. Values are indexed by their compact JSON text
+fn index_key(value: &Value) -> String {
+    value.to_string()
+}
+
+impl Index {
+    /// Create an index and add all values of `map` to it. If the index is unique and two values
+    /// have the same indexed value an error is returned
+    pub(crate) fn build(name: &str, path: JsonPath, key_prefix: &str, unique: bool, map: &HashMap<String, Value>) -> Result<Index, Error> {
+        let mut index = Index { path, key_prefix: String::from(key_prefix), unique, entries: HashMap::new() };
+        for (key, val) in map {
+            index.check(name, key, val)?;
+            index.insert(key, val);
+        }
+        Ok(index)
+    }
+
+    /// Clear the index and add all values of `map` to it. Uniqueness isn't checked, so a DB file
+    /// that was edited by hand can still be loaded
+    pub(crate) fn rebuild(&mut self, map: &HashMap<String, Value>) {
+        self.entries.clear();
+        for (key, val) in map {
+            self.insert(key, val);
+        }
+    }
+
+    /// A copy of the index definition without its entries, which is all that is needed for dumping it
+    pub(crate) fn definition(&self) -> Index {
+        Index { path: self.path.clone(), key_prefix: self.key_prefix.clone(), unique: self.unique, entries: HashMap::new() }
+    }
+
+    /// Check that the values of `map` don't violate the uniqueness of the index
+    pub(crate) fn check_map(&self, name: &str, map: &HashMap<String, Value>) -> Result<(), Error> {
+        if !self.unique {
+            return Ok(());
+        }
+
+        let mut seen: HashMap<String, &str> = HashMap::new();
+        for (key, val) in map.iter().filter(|(key, _)| self.covers(key)) {
+            if let Some(selected) = self.path.select(val) {
+                if let Some(existing_key) = seen.insert(index_key(selected), key) {
+                    return Err(Error::ConstraintViolation {
+                        index: String::from(name),
+                        key: key.clone(),
+                        existing_key: String::from(existing_key),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn covers(&self, key: &str) -> bool {
+        key.starts_with(&self.key_prefix)
+    }
+
+    /// Check that setting `value` to `key` doesn't violate the uniqueness of the index
+    fn check(&self, name: &str, key: &str, value: &Value) -> Result<(), Error> {
+        if !self.unique || !self.covers(key) {
+            return Ok(());
+        }
+
+        let selected = match self.path.select(value) {
+            Some(selected) => selected,
+            None => return Ok(()),
+        };
+
+        match self.entries.get(&index_key(selected)).and_then(|keys| keys.iter().find(|other| *other != key)) {
+            Some(existing_key) => Err(Error::ConstraintViolation {
+                index: String::from(name),
+                key: String::from(key),
+                existing_key: existing_key.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn insert(&mut self, key: &str, value: &Value) {
+        if !self.covers(key) {
+            return;
+        }
+        if let Some(selected) = self.path.select(value) {
+            self.entries.entry(index_key(selected)).or_default().insert(String::from(key));
+        }
+    }
+
+    fn remove(&mut self, key: &str, value: &Value) {
+        if !self.covers(key) {
+            return;
+        }
+        if let Some(selected) = self.path.select(value) {
+            let indexed = index_key(selected);
+            if let Some(keys) = self.entries.get_mut(&indexed) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.entries.remove(&indexed);
+                }
+            }
+        }
+    }
+
+    /// Get the keys of the values in which the path points to `value`
+    pub(crate) fn find(&self, value: &Value) -> Vec<String> {
+        match self.entries.get(&index_key(value)) {
+            Some(keys) => keys.iter().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Check that setting `value` to `key` doesn't violate any of the unique indexes
+pub(crate) fn check_all(indexes: &HashMap<String, Index>, key: &str, value: &Value) -> Result<(), Error> {
+    for (name, index) in indexes {
+        index.check(name, key, value)?;
+    }
+    Ok(())
+}
+
+/// Update all indexes after the value of `key` changed from `old` to `new`.
+/// `None` means the key didn't exist before the change or doesn't exist after it
+pub(crate) fn update_all(indexes: &mut HashMap<String, Index>, key: &str, old: Option<&Value>, new: Option<&Value>) {
+    for index in indexes.values_mut() {
+        if let Some(old) = old {
+            index.remove(key, old);
+        }
+        if let Some(new) = new {
+            index.insert(key, new);
+        }
+    }
+}
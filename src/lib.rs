@@ -7,7 +7,8 @@
 //! periodically according to a policy defined by the user. There are APIs to create a new key-value store in memory or to load it from a file.
 //! Everything runs in the user's process and thread and in its memory, which means that the key-value data will be stored in the user 
 //! process's memory and each API call will access that key-value store directly and may trigger a dump to the DB file. There are no additional 
-//! threads or processes created throughout the life-cycle of any of the APIs.
+//! threads or processes created throughout the life-cycle of any of the APIs, unless the user asks for dumping in the background or for
+//! watching the DB file.
 //! 
 //! ## So what is it useful for? 
 //! 
@@ -29,6 +30,42 @@
 //!   create and delete lists and to add or remove items from them. Lists are also heterogeneous, meaning each list can store objects of different 
 //!   types. Please see more details below
 //! 
+//! * Namespaces, which keep the keys and lists of different users of the same DB file separately from one another.
+//!   Please see [PickleDb::namespace()](struct.PickleDb.html#method.namespace) for more details
+//! * Query values and list items by JSON path, for example `$.age > 30`.
+//!   Please see [PickleDb::query()](struct.PickleDb.html#method.query) for more details
+//! * Secondary indexes on fields of values, optionally unique, please see [PickleDb::create_index()](struct.PickleDb.html#method.create_index)
+//! * Validation of values and list items against JSON Schemas, please see [PickleDb::set_key_schema()](struct.PickleDb.html#method.set_key_schema)
+//! * Typed handles to lists and buckets (maps of values of the same type), for when a collection holds items of a single type.
+//!   Please see more details below
+//! * Notifications about changes of keys and lists, please see [PickleDb::subscribe()](struct.PickleDb.html#method.subscribe)
+//! * Hooks that run before and after every change, for transforming or vetoing changes and for auditing them.
+//!   Please see [PickleDb::add_before_hook()](struct.PickleDb.html#method.add_before_hook) for more details
+//! * Reloading the data when the DB file is changed by someone else, optionally by watching the file (requires the
+//!   `file-watch` feature). Please see [PickleDb::reload()](struct.PickleDb.html#method.reload) for more details
+//! * Backups of the DB, either on request or rotated upon every dump, which can be restored later.
+//!   Please see [PickleDb::backup_to()](struct.PickleDb.html#method.backup_to) for more details
+//! * Checksums of the DB file, which detect corruption upon load. Please see [PickleDb::verify()](struct.PickleDb.html#method.verify)
+//!   for more details
+//! * Exporting the keys and lists to NDJSON, CSV or JSON for other tools, and importing them back.
+//!   Please see [PickleDb::export()](struct.PickleDb.html#method.export) for more details
+//! * Sharing DB files with Python services, in the format of Python's PickleDB.
+//!   Please see [PickleDb::load_python_compat()](struct.PickleDb.html#method.load_python_compat) for more details
+//! * Recovering the data of a truncated or corrupted DB file, optionally from its backups.
+//!   Please see [PickleDb::load_lenient()](struct.PickleDb.html#method.load_lenient) for more details
+//! * Compression of the DB file with gzip or zstd (requires the `gzip` or `zstd` feature), which is detected upon load.
+//!   Please see [PickleDb::set_compression()](struct.PickleDb.html#method.set_compression) for more details
+//! * Authenticated encryption of the DB file, with a key or a passphrase (requires the `encryption` feature).
+//!   Please see [PickleDb::new_encrypted()](struct.PickleDb.html#method.new_encrypted) for more details
+//! * Dumping in the background, so changes don't wait for the DB file to be written.
+//!   Please see [PickleDb::set_background_dump()](struct.PickleDb.html#method.set_background_dump) for more details
+//! * An asynchronous API for tokio applications, whose dumps don't block the executor (requires the `tokio` feature).
+//!   Please see [AsyncPickleDb](async_db/struct.AsyncPickleDb.html) for more details
+//! * Serving the DB to Redis clients over TCP or Unix sockets (requires the `resp` feature).
+//!   Please see [RespServer](resp/struct.RespServer.html) for more details
+//! * Serving the DB over HTTP as JSON resources, optionally requiring a bearer token (requires the `server` feature).
+//!   Please see [HttpServer](server/struct.HttpServer.html) for more details
+//!
 //! Please take a look at the API documentation to get more details.
 //! 
 //! ## PickleDB provides heterogeneous map and lists!
@@ -56,6 +93,11 @@
 //! their performance cost but high performance is not one of PickleDB's main objectives and I think it's a fair price to pay for achieving 
 //! heterogeneous data structures.
 //! 
+//! In memory every value and list item is kept as a [JSON value](https://docs.serde.rs/serde_json/enum.Value.html), so the DB file is
+//! plain nested JSON which is easy to read and edit by hand. Files written by older versions of PickleDB, in which every value was an
+//! escaped JSON string, are still loaded by [PickleDb::load()](struct.PickleDb.html#method.load) and are written in the new format
+//! upon the next dump.
+//! 
 //! In order to achieve this magic, all objects must be serializable. PickleDB uses the [Serde](https://serde.rs/) library for serialization and 
 //! it currently supports only [JSON serialization](https://docs.serde.rs/serde_json/). In the future I intend to add more serialization options
 //! such as [bincode](https://crates.io/crates/bincode) or [pickle](https://crates.io/crates/serde-pickle).
@@ -70,6 +112,29 @@
 //! 
 //! You can take a look at the examples provided with PickleDB to get a better idea of how this works. 
 //! 
+//! ## Typed lists and buckets
+//!
+//! Heterogeneous collections mean that every call to [get()](struct.PickleDb.html#method.get) or
+//! [lget()](struct.PickleDb.html#method.lget) needs the value type, and that a wrong type silently results in `None`.
+//! When a list holds items of a single type it's more convenient to use a typed handle:
+//! [PickleDb::list::<T>()](struct.PickleDb.html#method.list) returns a [TypedList](struct.TypedList.html) and
+//! [PickleDb::bucket::<T>()](struct.PickleDb.html#method.bucket) returns a [TypedBucket](struct.TypedBucket.html),
+//! which is a map of string keys to values of type `T` stored under a single key. Both verify upon creation that
+//! all existing items are of type `T` and return an [Error::TypeMismatch](error/enum.Error.html#variant.TypeMismatch)
+//! otherwise.
+//!
+//! ```rust,ignore
+//! let mut shapes = db.list::<Rectangle>("shapes").unwrap();
+//! shapes.push(&Rectangle { width: 4, length: 10 });
+//! let first = shapes.get(0).unwrap();
+//!
+//! let mut users = db.bucket::<User>("users").unwrap();
+//! users.set("john", &User { name: String::from("John"), age: 30 }).unwrap();
+//! for (id, user) in users.iter() {
+//!     println!("{}: {}", id, user.name);
+//! }
+//! ```
+//!
 //! ## Dumping data to a file
 //! 
 //! As mentioned before, PickleDB stores all the data in a file for persistency. Dumping data to a file is pretty expensive in terms of time and
@@ -88,16 +153,76 @@
 //! * [PickleDbDumpPolicy::PeriodicDump(Duration)](enum.PickleDbDumpPolicy.html#variant.PeriodicDump) - changes will be dumped to the file periodically, 
 //!   no sooner than the Duration provided by the user. The way this mechanism works is as follows: each time there is a DB change the last DB dump time 
 //!   is checked. If the time that has passed since the last dump is higher than Duration, changes will be dumped, otherwise changes will not be dumped.  
+//! * [PickleDbDumpPolicy::DumpEveryNWrites(n)](enum.PickleDbDumpPolicy.html#variant.DumpEveryNWrites) - changes will be dumped upon every n-th change
+//! * [PickleDbDumpPolicy::DumpAfterBytesChanged(n)](enum.PickleDbDumpPolicy.html#variant.DumpAfterBytesChanged) - changes will be dumped once they
+//!   changed at least n bytes
+//! * [PickleDbDumpPolicy::Any(policies)](enum.PickleDbDumpPolicy.html#variant.Any) - changes will be dumped when any of the policies decides to dump them,
+//!   for example every 100 changes or every 5 seconds
+//! * [PickleDbDumpPolicy::Custom(policy)](enum.PickleDbDumpPolicy.html#variant.Custom) - changes will be dumped when a policy implemented by the user
+//!   decides to dump them. Please see [DumpPolicy](policy/trait.DumpPolicy.html) for more details
 //! 
 //! Apart from this dump policy, persistency is also kept by a implementing the `Drop` trait for the `PickleDB` object which ensures all in-memory data 
 //! is dumped to the file upon destruction of the object.
 //! 
-use std::io::Error;
+//! With [PickleDb::set_background_dump()](struct.PickleDb.html#method.set_background_dump) the dumps themselves are moved to a background thread:
+//! every dump captures a snapshot of the data, which is cheap since the data is copied only when it's changed while the snapshot is still
+//! waiting to be written, and the file is written by the background thread. When dumps come faster than the file is written, only the
+//! latest snapshot is written. [PickleDb::flush()](struct.PickleDb.html#method.flush) waits until all the dumps were written to the file.
+//! 
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Write};
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::fs;
+use std::path::Path;
 use serde::{de::DeserializeOwned, Serialize};
+#[allow(clippy::single_component_path_imports)]
 use serde_json;
+use serde_json::Value;
+
+#[cfg(feature = "tokio")]
+pub mod async_db;
+pub mod error;
+#[cfg(feature = "file-watch")]
+pub mod file_watch;
+mod background;
+mod backup;
+pub mod compression;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod export;
+mod format;
+pub mod hooks;
+mod index;
+mod namespace;
+pub mod policy;
+pub mod query;
+pub mod repair;
+#[cfg(feature = "resp")]
+pub mod resp;
+mod schema;
+#[cfg(feature = "server")]
+pub mod server;
+mod typed;
+pub mod watch;
+
+pub use namespace::Namespace;
+pub use typed::{TypedBucket, TypedList};
+use query::{JsonPath, Query};
+use index::Index;
+use schema::Schema;
+use namespace::{DataMut, NamespaceData, ScopeMut};
+use watch::{ChangeEvent, ChangeKind, Subscription, Watchers};
+use hooks::{HookId, Hooks, Mutation};
+use background::{BackgroundWriter, Snapshot};
+use policy::{DumpPolicy, DumpStats};
+use compression::Compression;
+use export::{ConflictPolicy, ExportFormat};
+use repair::RepairReport;
+#[cfg(feature = "encryption")]
+use encryption::{Cipher, EncryptionKey};
+#[cfg(feature = "file-watch")]
+use file_watch::WatchedPickleDb;
 
 /// An enum that determines the policy of dumping PickleDB changes into the file 
 pub enum PickleDbDumpPolicy {
@@ -112,15 +237,45 @@ pub enum PickleDbDumpPolicy {
     /// If the time that has passed since the last dump is higher than Duration, changes will be dumped, 
     /// otherwise changes will not be dumped
     PeriodicDump(Duration),
+    /// Changes will be dumped to the file upon every N-th change since the last dump
+    DumpEveryNWrites(u64),
+    /// Changes will be dumped to the file once they changed at least N bytes since the last dump. Please see
+    /// [DumpStats::bytes](policy/struct.DumpStats.html#structfield.bytes) for how the bytes are counted
+    DumpAfterBytesChanged(u64),
+    /// Changes will be dumped to the file when any of the policies decides to dump them, for example
+    /// `Any(vec![DumpEveryNWrites(100), PeriodicDump(Duration::from_secs(5))])` dumps every 100 changes or every 5 seconds
+    Any(Vec<PickleDbDumpPolicy>),
+    /// Changes will be dumped to the file when a policy implemented by the user decides to dump them.
+    /// Please see [DumpPolicy](policy/trait.DumpPolicy.html) for more details
+    Custom(Box<dyn DumpPolicy>),
 }
 
 /// A struct that represents a PickleDB object
 pub struct PickleDb {
-    map: HashMap<String, String>, 
-    list_map: HashMap<String, Vec<String>>,
+    // the data that is dumped is shared with the snapshots handed to the background writer, and copied upon write
+    map: Arc<HashMap<String, Value>>,
+    list_map: Arc<HashMap<String, Vec<Value>>>,
+    namespaces: Arc<HashMap<String, NamespaceData>>,
+    indexes: HashMap<String, Index>,
+    key_schemas: HashMap<String, Schema>,
+    list_schemas: HashMap<String, Schema>,
     db_file_path: String,
     dump_policy: PickleDbDumpPolicy,
-    last_dump: Instant
+    last_dump: Instant,
+    writes_since_dump: u64,
+    bytes_since_dump: u64,
+    /// Whether the bytes of changes are counted even if the dump policy doesn't use them, for a DB whose dumps are
+    /// decided by the policy of its owner
+    count_bytes: bool,
+    backups: usize,
+    compression: Compression,
+    /// Whether the DB file is in the format of Python's pickleDB
+    python_compat: bool,
+    #[cfg(feature = "encryption")]
+    cipher: Option<Arc<Cipher>>,
+    hooks: Hooks,
+    watchers: Watchers,
+    writer: Option<BackgroundWriter>,
 }
 
 impl PickleDb {
@@ -140,19 +295,41 @@ impl PickleDb {
     /// 
     /// let mut db = PickleDB::new("example.db", false);
     /// ```
+    #[allow(clippy::doc_overindented_list_items, clippy::redundant_field_names)]
     pub fn new(location: &str, dump_policy: PickleDbDumpPolicy) -> PickleDb {
         PickleDb { 
-            map: HashMap::new(), 
-            list_map: HashMap::new(), 
+            map: Arc::new(HashMap::new()),
+            list_map: Arc::new(HashMap::new()),
+            namespaces: Arc::new(HashMap::new()),
+            indexes: HashMap::new(),
+            key_schemas: HashMap::new(),
+            list_schemas: HashMap::new(),
             db_file_path: String::from(location), 
             dump_policy: dump_policy,
-            last_dump: Instant::now() }
+            last_dump: Instant::now(),
+            writes_since_dump: 0,
+            bytes_since_dump: 0,
+            count_bytes: false,
+            backups: 0,
+            compression: Compression::None,
+            python_compat: false,
+            #[cfg(feature = "encryption")]
+            cipher: None,
+            hooks: Hooks::default(),
+            watchers: Watchers::default(),
+            writer: None }
     }
 
     /// Load a DB from a file.
     /// 
     /// This method tries to load a DB from a file. Upon success an instance of `PickleDB` is returned, 
     /// otherwise an error is returned.
+    ///
+    /// Every dump ends the file with a checksum of its content, which is verified upon load, so a file that was corrupted
+    /// is rejected even if it's still valid JSON. In that case the error is of kind `InvalidData` and wraps
+    /// [Error::Corrupted](error/enum.Error.html#variant.Corrupted). Files without a checksum, for example ones that were
+    /// written by older versions, are loaded without verifying them. Please see [verify()](#method.verify) for checking
+    /// a file without loading it.
     /// 
     /// # Arguments
     /// 
@@ -170,6 +347,14 @@ impl PickleDb {
     ///     dumped to the file periodically, no sooner than the Duration provided by the user. The way this mechanism works is
     ///     as follows: each time there is a DB change the last DB dump time is checked. If the time that has passed
     ///     since the last dump is higher than Duration, changes will be dumped, otherwise changes will not be dumped.    
+    ///   * [PickleDbDumpPolicy::DumpEveryNWrites(n)](enum.PickleDbDumpPolicy.html#variant.DumpEveryNWrites) - changes will be
+    ///     dumped upon every n-th change since the last dump
+    ///   * [PickleDbDumpPolicy::DumpAfterBytesChanged(n)](enum.PickleDbDumpPolicy.html#variant.DumpAfterBytesChanged) - changes
+    ///     will be dumped once they changed at least n bytes since the last dump
+    ///   * [PickleDbDumpPolicy::Any(policies)](enum.PickleDbDumpPolicy.html#variant.Any) - changes will be dumped when any of
+    ///     the policies decides to dump them
+    ///   * [PickleDbDumpPolicy::Custom(policy)](enum.PickleDbDumpPolicy.html#variant.Custom) - changes will be dumped when a
+    ///     policy implemented by the user decides to dump them
     /// 
     /// # Examples
     /// 
@@ -179,15 +364,49 @@ impl PickleDb {
     /// let db = PickleDB::load("example.db", PickleDbDumpPolicy::AutoDump);
     /// ```
     pub fn load(location: &str, dump_policy: PickleDbDumpPolicy) -> Result<PickleDb, Error> {
-        let contents = fs::read_to_string(location)?;
-        let map_from_file: (_,_) = serde_json::from_str(&contents)?;
-        Ok(PickleDb { 
-            map: map_from_file.0, 
-            list_map: map_from_file.1, 
+        let contents = fs::read(location)?;
+        PickleDb::from_contents(location, contents, dump_policy)
+    }
+
+    /// Create a DB from the contents of a DB file that was read from `location`, which may be compressed
+    pub(crate) fn from_contents(location: &str, contents: Vec<u8>, dump_policy: PickleDbDumpPolicy) -> Result<PickleDb, Error> {
+        let (json, compression) = compression::decode(contents)?;
+        format::verify(&json).map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        Ok(PickleDb::from_db_file(location, format::parse(&json)?, compression, dump_policy))
+    }
+
+    /// Create a DB from the parsed content of a DB file
+    fn from_db_file(location: &str, mut db_from_file: format::DbFile, compression: Compression, dump_policy: PickleDbDumpPolicy) -> PickleDb {
+        for index in db_from_file.indexes.values_mut() {
+            index.rebuild(&db_from_file.map);
+        }
+        for data in db_from_file.namespaces.values_mut() {
+            for index in data.indexes.values_mut() {
+                index.rebuild(&data.map);
+            }
+        }
+        PickleDb { 
+            map: Arc::new(db_from_file.map),
+            list_map: Arc::new(db_from_file.list_map),
+            namespaces: Arc::new(db_from_file.namespaces),
+            indexes: db_from_file.indexes,
+            key_schemas: db_from_file.key_schemas,
+            list_schemas: db_from_file.list_schemas,
             db_file_path: String::from(location), 
-            dump_policy: dump_policy,
-            last_dump: Instant::now()
-            })
+            dump_policy,
+            last_dump: Instant::now(),
+            writes_since_dump: 0,
+            bytes_since_dump: 0,
+            count_bytes: false,
+            backups: 0,
+            compression,
+            python_compat: false,
+            #[cfg(feature = "encryption")]
+            cipher: None,
+            hooks: Hooks::default(),
+            watchers: Watchers::default(),
+            writer: None,
+            }
     }
 
     /// Load a DB from a file in read-only mode.
@@ -216,232 +435,1189 @@ impl PickleDb {
         PickleDb::load(location, PickleDbDumpPolicy::NeverDump)
     }
 
-    /// Dump the data to the file.
-    /// 
-    /// Calling this method is necessary only if the DB is loaded or created with `auto_dump = true`.
-    /// Otherwise the data is dumped to the file upon every change. This method returns `true` if
-    /// dump is successful, false otherwise.
-    /// 
-    pub fn dump(&mut self) -> bool {
-        if let PickleDbDumpPolicy::NeverDump = self.dump_policy {
-            return true
-        }
-
-        match serde_json::to_string(&(&self.map, &self.list_map)) {
-            Ok(db_as_json) => {
-                fs::write(&self.db_file_path, &db_as_json).expect("Unable to write file");
-                if let PickleDbDumpPolicy::PeriodicDump(_dur) = self.dump_policy {
-                    self.last_dump = Instant::now();
-                }
-                true
-            }
-            Err(_) => false,
-        }
+    /// Load a DB from a file written by [Python's PickleDB](https://pythonhosted.org/pickleDB/).
+    ///
+    /// Python's PickleDB stores a plain JSON object of the keys and their values. Arrays in it are loaded as lists, since
+    /// that's how Python's `lcreate()` stores lists, and the other values (including the dicts of Python's `dcreate()`) as
+    /// the values of keys, so a value that was set to an array is loaded as a list. The loaded DB keeps dumping its file
+    /// in the same format, so Python and Rust services can share it. Please see
+    /// [set_python_compat()](#method.set_python_compat) for what isn't stored in this format.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - a path of a file written by Python's PickleDB
+    /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file. Please see
+    ///   [PickleDB::load()](#method.load) to understand the different policy options
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// // written in Python by: db = pickledb.load("shared.db", True); db.set("num", 100); db.lcreate("list1")
+    /// let mut db = PickleDb::load_python_compat("shared.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    /// assert_eq!(db.get::<i32>("num").unwrap(), 100);
+    /// db.ladd("list1", &"hello").unwrap();
+    /// ```
+    ///
+    pub fn load_python_compat(location: &str, dump_policy: PickleDbDumpPolicy) -> Result<PickleDb, Error> {
+        let (json, compression) = compression::decode(fs::read(location)?)?;
+        let mut db = PickleDb::from_db_file(location, format::parse_python(&json)?, compression, dump_policy);
+        db.python_compat = true;
+        Ok(db)
     }
 
-    fn dumpdb(&mut self) {
-        match self.dump_policy {
-            PickleDbDumpPolicy::AutoDump => {
-                self.dump();
-            },
-            PickleDbDumpPolicy::PeriodicDump(duration) => {
-                let now = Instant::now();
-                if now.duration_since(self.last_dump) > duration {
-                    self.last_dump = Instant::now();
-                    self.dump();
-                }
-            },
-
-            _ => (),
-        }
+    /// Check a DB file for corruption without loading it.
+    ///
+    /// The file is read, decompressed if it's compressed, and its content is checked against the checksum that was written
+    /// with it and parsed. `Ok` is returned if the file is intact, [Error::Corrupted](error/enum.Error.html#variant.Corrupted)
+    /// if the content doesn't match the checksum, and another error if the file can't be read, decompressed or parsed.
+    /// Files without a checksum, for example ones that were written by older versions, are only parsed. Encrypted files can't
+    /// be verified without their key, but they're authenticated whenever they're loaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - a path of a DB file or of a backup
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use pickledb::error::Error;
+    ///
+    /// match PickleDb::verify("example.db") {
+    ///     Ok(()) => println!("the DB file is intact"),
+    ///     Err(Error::Corrupted(reason)) => println!("the DB file is corrupted: {}", reason),
+    ///     Err(err) => println!("the DB file can't be verified: {}", err),
+    /// }
+    /// ```
+    ///
+    pub fn verify(location: &str) -> Result<(), error::Error> {
+        let (json, _compression) = compression::decode(fs::read(location)?)?;
+        format::verify(&json)?;
+        format::parse(&json)?;
+        Ok(())
     }
 
-    /// Set a key-value pair.
-    /// 
-    /// The key has to be a string but the value can be of any type that is serializable.
-    /// That includes all primitive types, vectors, tuples and every struct that has the 
-    /// `#[derive(Serialize, Deserialize)` attribute.
-    /// 
+    /// Load a DB from a file, recovering as much of the data as possible if the file is damaged.
+    ///
+    /// If the file loads like it does with [PickleDB::load()](#method.load), the DB is returned along with a report whose
+    /// [is_intact()](repair/struct.RepairReport.html#method.is_intact) is `true`. Otherwise every key, list and list item
+    /// that can still be parsed is recovered, for example all the keys before the point where a truncated file ends, and
+    /// both the current and the legacy formats are supported. The report holds the error loading failed with, the number
+    /// of keys and lists that were recovered and a description of everything that was dropped.
+    ///
+    /// If nothing can be recovered from the file, or it can't be read at all, the newest numbered backup that loads is used
+    /// instead (please see [set_backup_rotation()](#method.set_backup_rotation)), and the report tells which one. If there's
+    /// no such backup, an empty DB is returned. Nothing is written until the DB is dumped, please see [repair()](#method.repair)
+    /// for fixing the file itself. An error is returned only if the file is encrypted, or compressed without the feature
+    /// its compression requires.
+    ///
     /// # Arguments
-    /// 
-    /// * `key` - a string key
-    /// * `value` - a value of any serializable type
-    /// 
+    ///
+    /// * `location` - a path where the DB is loaded from
+    /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file. Please see
+    ///   [PickleDB::load()](#method.load) to understand the different policy options
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```rust,ignore
-    /// // set a number
-    /// db.set("key1", &100);
-    /// 
-    /// // set a floating point number
-    /// db.set("key2", &1.234);
-    /// 
-    /// // set a String
-    /// db.set("key3", &String::from("hello world"));
-    /// 
-    /// // set a Vec
-    /// db.set("key4", &vec![1,2,3]);
-    /// 
-    /// // set a struct
-    /// #[derive(Serialize, Deserialize)]
-    /// struct Coor {
-    ///     x: i32,
-    ///     y: i32,
+    /// let (db, report) = PickleDb::load_lenient("example.db", PickleDbDumpPolicy::DumpUponRequest).unwrap();
+    /// if !report.is_intact() {
+    ///     println!("recovered {} keys and {} lists", report.recovered_keys, report.recovered_lists);
+    ///     for dropped in &report.dropped {
+    ///         println!("dropped {}", dropped);
+    ///     }
     /// }
-    /// let mycoor = Coor { x: 1, y : 2 };
-    /// db.set("key5", &mycoor);
     /// ```
-    /// 
-    pub fn set<V>(&mut self, key: &str, value: &V)
-        where
-            V: Serialize
-    {
-        if self.list_map.contains_key(key) {
-            self.list_map.remove(key);
+    ///
+    pub fn load_lenient(location: &str, dump_policy: PickleDbDumpPolicy) -> Result<(PickleDb, RepairReport), error::Error> {
+        let mut report = RepairReport::default();
+        let mut db = match fs::read(location) {
+            Ok(contents) => match PickleDb::from_contents(location, contents.clone(), PickleDbDumpPolicy::NeverDump) {
+                Ok(mut db) => {
+                    db.dump_policy = dump_policy;
+                    return Ok((db, report));
+                },
+                Err(err) => {
+                    report.error = Some(repair::load_error(err));
+                    let (contents, compression) = compression::decode_partially(contents)?;
+                    let (db_from_file, dropped) = repair::salvage(&String::from_utf8_lossy(&contents));
+                    report.dropped = dropped;
+                    PickleDb::from_db_file(location, db_from_file, compression, PickleDbDumpPolicy::NeverDump)
+                },
+            },
+            Err(err) => {
+                report.error = Some(error::Error::Io(err));
+                PickleDb::new(location, PickleDbDumpPolicy::NeverDump)
+            },
+        };
+
+        if db.map.is_empty() && db.list_map.is_empty() && db.namespaces.is_empty() {
+            let backups = (1..).map(|n| backup::backup_path(location, n)).take_while(|backup| Path::new(backup).exists());
+            for backup in backups {
+                let restored = fs::read(&backup)
+                    .and_then(|contents| PickleDb::from_contents(location, contents, PickleDbDumpPolicy::NeverDump));
+                if let Ok(restored) = restored {
+                    db = restored;
+                    report.restored_from = Some(backup);
+                    break;
+                }
+            }
         }
-        self.map.insert(String::from(key), serde_json::to_string(value).unwrap());
-        self.dumpdb();
+
+        report.recovered_keys = db.map.len() + db.namespaces.values().map(|data| data.map.len()).sum::<usize>();
+        report.recovered_lists = db.list_map.len() + db.namespaces.values().map(|data| data.list_map.len()).sum::<usize>();
+        db.dump_policy = dump_policy;
+        Ok((db, report))
     }
 
-    /// Get a value of a key.
-    /// 
-    /// The key is always a string but the value can be of any type. It's the user's
-    /// responsibility to know the value type and give it while calling this method.
-    /// If the key doesn't exist or if the type is wrong, `None` will be returned.
-    /// Otherwise `Some(V)` will be returned.
-    /// Since the values are stored in a serialized way the returned object is
-    /// not a reference to the value stored in a DB but actually a new instance of it
-    /// 
+    /// Repair a damaged DB file.
+    ///
+    /// The data is recovered like [load_lenient()](#method.load_lenient) does, and if the file isn't intact the recovered data
+    /// is written back to it, keeping its compression. The damaged file is kept as `<DB file>.damaged`, in case more of it can
+    /// be recovered by hand. The repaired file is written to a temporary file which then replaces the DB file, so it's never
+    /// found partially written. An intact file isn't changed.
+    ///
     /// # Arguments
-    /// 
-    /// * `key` - a string key
-    /// 
+    ///
+    /// * `location` - a path of the DB file to repair
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```rust,ignore
-    /// // read a num
-    /// let num = db.get::<i32>("key1").unwrap();
-    /// 
-    /// // read a floating point number
-    /// let float_num = db.get::<f32>("key2").unwrap();
-    /// 
-    /// // read a String
-    /// let my_str = db.get::<String>("key3").unwrap();
-    /// 
-    /// // read a Vec
-    /// ley vec = db.get::<Vec<i32>>("key4").unwrap();
-    /// 
-    /// // read a struct
-    /// let coor = db.get::<Coor>("key5").unwrap();
+    /// let report = PickleDb::repair("example.db").unwrap();
+    /// if let Some(backup) = &report.restored_from {
+    ///     println!("restored from {}", backup);
+    /// }
+    /// let db = PickleDb::load("example.db", PickleDbDumpPolicy::AutoDump).unwrap();
     /// ```
-    /// 
-    pub fn get<V>(&self, key: &str) -> Option<V> 
-        where 
-            V: DeserializeOwned
-    {
-        match self.map.get(key) {
-            Some(val_as_string) => match serde_json::from_str(&val_as_string) {
-                Ok(val) => Some(val),
-                Err(_) => None
-            },
-            
-            None => None,
+    ///
+    pub fn repair(location: &str) -> Result<RepairReport, error::Error> {
+        let (db, report) = PickleDb::load_lenient(location, PickleDbDumpPolicy::NeverDump)?;
+        if report.is_intact() {
+            return Ok(report);
         }
+        if Path::new(location).exists() {
+            fs::copy(location, format!("{}.damaged", location))?;
+        }
+        backup::write_atomically(location, &db.contents()?)?;
+        Ok(report)
     }
 
-    /// Check if a key exists.
+    /// Dump the data to the file.
     /// 
-    /// This method returns `true` if the key exists and `false` otherwise.
+    /// Calling this method is necessary only if the DB is loaded or created with `auto_dump = true`.
+    /// Otherwise the data is dumped to the file upon every change. This method returns `true` if
+    /// dump is successful, false otherwise.
     /// 
-    /// # Arguments
+    /// When backups are rotated (please see [set_backup_rotation()](#method.set_backup_rotation)) the previous file is
+    /// backed up before it's overwritten. If that fails the data is still dumped, but `false` is returned. Please see
+    /// [try_dump()](#method.try_dump) for the reason of a failure.
     /// 
-    /// * `key` - the key to check
+    /// When dumping in the background (please see [set_background_dump()](#method.set_background_dump)) this method
+    /// only hands a snapshot of the data to the background thread, and [flush()](#method.flush) waits until it's written.
     /// 
-    pub fn exists(&self, key: &str) -> bool {
-        self.map.get(key).is_some() || self.list_map.get(key).is_some()
+    pub fn dump(&mut self) -> bool {
+        self.try_dump().is_ok()
     }
 
-    /// Get a vector of all the keys in the DB.
-    /// 
-    /// The keys returned in the vector are not references to the actual key string
-    /// objects but rather a clone of them.
-    /// 
-    pub fn get_all(&self) -> Vec<String> {
-        [self.map
-            .iter()
-            .map(|(key, _)| key.clone())
-            .collect::<Vec<String>>(),
+    /// Dump the data to the file, reporting why it failed.
+    ///
+    /// This is the same as [dump()](#method.dump), except that an error is returned if the data or its backups can't be
+    /// written. The file is replaced atomically, so a failed dump leaves the previous file intact. When backups are rotated
+    /// and that fails the data is still dumped, and the error of the rotation is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut db = PickleDb::load("example.db", PickleDbDumpPolicy::DumpUponRequest).unwrap();
+    /// db.set("key1", &100).unwrap();
+    /// if let Err(err) = db.try_dump() {
+    ///     eprintln!("the DB wasn't saved: {}", err);
+    /// }
+    /// ```
+    ///
+    pub fn try_dump(&mut self) -> Result<(), error::Error> {
+        if let PickleDbDumpPolicy::NeverDump = self.dump_policy {
+            return Ok(())
+        }
 
-        self.list_map
-            .iter()
-            .map(|(key, _)| key.clone())
-            .collect::<Vec<String>>()]
-        
-        .concat()
-    }
+        let backups = self.backups_to_rotate();
+        if let Some(writer) = &mut self.writer {
+            writer.submit(Snapshot {
+                path: self.db_file_path.clone(),
+                backups,
+                compression: self.compression,
+                #[cfg(feature = "encryption")]
+                cipher: self.cipher.clone(),
+                map: self.map.clone(),
+                list_map: self.list_map.clone(),
+                namespaces: self.namespaces.clone(),
+                indexes: self.indexes.iter().map(|(name, index)| (name.clone(), index.definition())).collect(),
+                key_schemas: self.key_schemas.clone(),
+                list_schemas: self.list_schemas.clone(),
+                python_compat: self.python_compat,
+            });
+            self.reset_dump_stats();
+            return Ok(())
+        }
 
-    /// Get the total number of keys in the DB.
-    /// 
-    pub fn total_keys(&self) -> usize {
-        self.map.iter().len() + self.list_map.iter().len()
+        let db_as_json = self.contents()?;
+        let rotated = backup::rotate(&self.db_file_path, backups);
+        backup::write_atomically(&self.db_file_path, &db_as_json)?;
+        self.reset_dump_stats();
+        rotated?;
+        Ok(())
     }
 
-    /// Remove a key-value pair or a list from the DB.
-    /// 
-    /// This methods returns `true` if the key was found in the DB or false if it wasn't found
-    /// 
+    /// Change the policy of dumping DB changes into the file.
+    ///
+    /// The new policy applies from the next change on, and changes that the previous policy didn't dump yet are counted
+    /// by the new policy as well (except for the bytes they changed, if the previous policy didn't use them). Please see [PickleDB::load()](#method.load) to understand the different policy options.
+    /// The previous policy is returned, so it can be restored later.
+    ///
     /// # Arguments
-    /// 
-    /// * `key` - the key or list name to remove
-    /// 
-    pub fn rem(&mut self, key: &str) -> bool {
-        let res = self.map.remove(key).is_some() || self.list_map.remove(key).is_some();
-        self.dumpdb();
-        res
+    ///
+    /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut db = PickleDb::load("example.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    ///
+    /// // don't dump upon every item of a bulk import
+    /// let previous = db.set_dump_policy(PickleDbDumpPolicy::DumpUponRequest);
+    /// for (key, value) in items {
+    ///     db.set(&key, &value).unwrap();
+    /// }
+    /// db.set_dump_policy(previous);
+    /// db.dump();
+    /// ```
+    ///
+    pub fn set_dump_policy(&mut self, dump_policy: PickleDbDumpPolicy) -> PickleDbDumpPolicy {
+        std::mem::replace(&mut self.dump_policy, dump_policy)
     }
 
-    /// Create a new list.
-    /// 
-    /// This method just creates a new list, it doesn't add any elements to it.
-    /// For adding elements to the list please call [ladd()](#method.ladd) or [lextend()](#method.lextend).
-    /// If another list or value is already set under this key, they will be overridden,
-    /// meaning the new list will override the old list or value.
-    /// 
+    /// Write the data to another file.
+    ///
+    /// The DB keeps dumping to its own file, for making the other file the DB file please use [relocate()](#method.relocate).
+    /// The data is written regardless of the dump policy, and an error is returned if it can't be written. The file is
+    /// replaced atomically, so it's never found partially written.
+    ///
     /// # Arguments
-    /// 
-    /// * `name` - the key of the list that will be created
-    /// 
-    pub fn lcreate(&mut self, name: &str) {
-        let new_list: Vec<String> = Vec::new();
-        if self.map.contains_key(name) {
-            self.map.remove(name);
-        }
-        self.list_map.insert(String::from(name), new_list);
-        self.dumpdb();
+    ///
+    /// * `location` - a path where the data will be written
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let db = PickleDb::load("example.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    /// db.save_as("example-copy.db").unwrap();
+    /// ```
+    ///
+    pub fn save_as(&self, location: &str) -> Result<(), error::Error> {
+        backup::write_atomically(location, &self.contents()?)?;
+        Ok(())
     }
 
-    /// Check if a list exists.
-    /// 
-    /// This method returns `true` if the list name exists and `false` otherwise.
-    /// The difference between this method and [exists()](#method.exists) is that this methods checks only
-    /// for lists with that name (key) and [exists()](#method.exists) checks for both values and lists.
-    /// 
+    /// Export the keys and lists to a file that other tools can read, such as NDJSON or CSV.
+    ///
+    /// The file is written key by key in the order of the keys, so exporting a large DB doesn't build the whole file in
+    /// memory. Only [ExportFormat::Ndjson](export/enum.ExportFormat.html#variant.Ndjson) holds lists, the other formats
+    /// hold only the values of the keys, so if the DB has lists an [Error::TypeMismatch](error/enum.Error.html#variant.TypeMismatch)
+    /// is returned for them and no file is written. Namespaces aren't exported. The number of keys and lists that were
+    /// written is returned.
+    ///
     /// # Arguments
-    /// 
-    /// * `name` - the list key to check
-    /// 
-    pub fn lexists(&self, name: &str) -> bool {
-        self.list_map.get(name).is_some()
+    ///
+    /// * `location` - a path where the file will be written
+    /// * `format` - the format of the file
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use pickledb::export::ExportFormat;
+    ///
+    /// let db = PickleDb::load("example.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    /// db.export("example.csv", ExportFormat::Csv).unwrap();
+    /// ```
+    ///
+    pub fn export(&self, location: &str, format: ExportFormat) -> Result<usize, error::Error> {
+        let mut values: Vec<(&String, &Value)> = self.map.iter().collect();
+        values.sort_by_key(|(key, _)| *key);
+        let mut lists: Vec<(&String, &Vec<Value>)> = self.list_map.iter().collect();
+        lists.sort_by_key(|(name, _)| *name);
+        export::check_lists(format, &lists)?;
+
+        let mut writer = BufWriter::new(fs::File::create(location)?);
+        let written = export::write(&mut writer, format, &values, &lists)?;
+        writer.flush()?;
+        Ok(written)
     }
 
-    /// Add a single item to an existing list.
+    /// Import the keys and lists of a file written by [export()](#method.export), or by other tools in the same format.
+    ///
+    /// The file is read key by key, and the keys and lists are set with [set()](#method.set) and
+    /// [lcreate_with()](#method.lcreate_with), so they're checked by the schemas, the indexes and the hooks of the DB like
+    /// any other change. The changes are dumped once when the import is over, according to the dump policy, and the error
+    /// of the dump is returned if it fails. If an imported key or list can't be set, or the file turns out to be invalid,
+    /// the import stops with its error and the keys and lists of the DB are restored as they were before the import, so
+    /// nothing of the file is imported. The after-mutation hooks and the subscriptions have already received the changes
+    /// of the keys and lists that were set before the error, though. The number of keys and lists that were imported is
+    /// returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - a path of the file to import
+    /// * `format` - the format of the file
+    /// * `conflict_policy` - what to do with keys and lists that already exist, please see
+    ///   [ConflictPolicy](export/enum.ConflictPolicy.html)
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use pickledb::export::{ConflictPolicy, ExportFormat};
+    ///
+    /// let mut db = PickleDb::new("example.db", PickleDbDumpPolicy::AutoDump);
+    /// let imported = db.import("example.ndjson", ExportFormat::Ndjson, ConflictPolicy::Fail).unwrap();
+    /// ```
+    ///
+    pub fn import(&mut self, location: &str, format: ExportFormat, conflict_policy: ConflictPolicy) -> Result<usize, error::Error> {
+        if conflict_policy == ConflictPolicy::Fail {
+            export::read(BufReader::new(fs::File::open(location)?), format, |entry| {
+                if self.exists(entry.key()) {
+                    return Err(error::Error::Conflict(String::from(entry.key())));
+                }
+                Ok(())
+            })?;
+        }
+
+        let reader = BufReader::new(fs::File::open(location)?);
+        let dump_policy = self.set_dump_policy(PickleDbDumpPolicy::DumpUponRequest);
+        // the data to restore if the import fails. The maps are shared until the import changes them, like with snapshots
+        let before = (Arc::clone(&self.map), Arc::clone(&self.list_map), self.indexes.clone(), self.writes_since_dump, self.bytes_since_dump);
+        let mut imported = 0;
+        let result = export::read(reader, format, |entry| {
+            if conflict_policy == ConflictPolicy::Skip && self.exists(entry.key()) {
+                return Ok(());
+            }
+            match entry {
+                export::Entry::Value(key, value) => self.set(&key, &value)?,
+                export::Entry::List(name, items) => self.lcreate_with(&name, &items)?,
+            }
+            imported += 1;
+            Ok(())
+        });
+        self.set_dump_policy(dump_policy);
+        if let Err(err) = result {
+            (self.map, self.list_map, self.indexes, self.writes_since_dump, self.bytes_since_dump) = before;
+            return Err(err);
+        }
+        if imported > 0 && self.dump_policy.should_dump(&self.dump_stats()) {
+            self.try_dump()?;
+        }
+        Ok(imported)
+    }
+
+    /// Write a backup of the data to another file.
+    ///
+    /// The backup is a copy of the data in memory at the time of the call, including changes that weren't dumped yet.
+    /// It's written to a temporary file which then replaces the backup file, so the backup file is never found partially
+    /// written. The DB keeps dumping to its own file. A backup can be restored by [restore_from()](#method.restore_from).
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - a path where the backup will be written
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let db = PickleDb::load("example.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    /// db.backup_to("backups/example-before-deploy.db").unwrap();
+    /// ```
+    ///
+    pub fn backup_to(&self, location: &str) -> Result<(), error::Error> {
+        backup::write_atomically(location, &self.contents()?)?;
+        Ok(())
+    }
+
+    /// Keep numbered backups of the DB file, which are rotated upon every dump.
+    ///
+    /// Before every dump of new changes the DB file is copied to `<DB file>.1`, the previous `<DB file>.1` becomes `<DB file>.2` and so on,
+    /// and the oldest backup is overwritten once there are `count` of them. For example, with a count of 3 the DB file
+    /// `example.db` keeps its 3 previous versions in `example.db.1` (the latest), `example.db.2` and `example.db.3`.
+    /// Any of them can be restored by [restore_from()](#method.restore_from). A count of 0, which is the default,
+    /// disables the backups.
+    ///
+    /// Please note that every dump copies the whole file, so with [PickleDbDumpPolicy::AutoDump](enum.PickleDbDumpPolicy.html#variant.AutoDump)
+    /// the backups keep only the last few changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - the number of backups to keep
+    ///
+    pub fn set_backup_rotation(&mut self, count: usize) {
+        self.backups = count;
+    }
+
+    /// Restore a DB from a backup.
+    ///
+    /// The backup is loaded, and if it's a valid DB file it's written to `location`, replacing the DB file there,
+    /// and the restored DB is returned. An error is returned if the backup can't be loaded, in which case `location`
+    /// isn't changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `backup` - a path of a backup, written by [backup_to()](#method.backup_to) or rotated by
+    ///   [set_backup_rotation()](#method.set_backup_rotation)
+    /// * `location` - a path where the DB will be stored
+    /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file. Please see
+    ///   [PickleDB::load()](#method.load) to understand the different policy options
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// // undo the last dump
+    /// let db = PickleDb::restore_from("example.db.1", "example.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    /// ```
+    ///
+    pub fn restore_from(backup: &str, location: &str, dump_policy: PickleDbDumpPolicy) -> Result<PickleDb, error::Error> {
+        let contents = fs::read(backup)?;
+        let db = PickleDb::from_contents(location, contents.clone(), dump_policy)?;
+        backup::write_atomically(location, &contents)?;
+        Ok(db)
+    }
+
+    /// Write the data to another file and make it the DB file.
+    ///
+    /// From now on changes are dumped to the new file according to the dump policy, and the previous file is left as it is.
+    /// The data is written regardless of the dump policy. If it can't be written an error is returned and the DB file isn't
+    /// changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - a path where the DB will be stored from now on
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut db = PickleDb::new("untitled.db", PickleDbDumpPolicy::AutoDump);
+    /// db.set("key1", &100).unwrap();
+    ///
+    /// // "Save As"
+    /// db.relocate("document.db").unwrap();
+    /// ```
+    ///
+    pub fn relocate(&mut self, location: &str) -> Result<(), error::Error> {
+        self.save_as(location)?;
+        self.db_file_path = String::from(location);
+        self.reset_dump_stats();
+        Ok(())
+    }
+
+    /// Dump the data in the background or in the calling thread.
+    ///
+    /// When dumping in the background, every dump (whether it's triggered by the dump policy or by calling [dump()](#method.dump))
+    /// captures a snapshot of the data and hands it to a background thread which writes it to the file, so changes don't wait for
+    /// the data to be serialized and written. Capturing a snapshot is cheap: the data is shared with the snapshot and copied only
+    /// if it's changed before the snapshot is written. When dumps come faster than the file is written, only the latest snapshot
+    /// is written.
+    ///
+    /// Since writing happens later, errors are reported by [flush()](#method.flush), which also waits until all the dumps so far
+    /// were written. Disabling dumping in the background waits for them as well. Dropping the DB writes the last dump before
+    /// the background thread exits.
+    ///
+    /// An error is returned if the background thread can't be started, or if disabling it finds that a dump failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - whether to dump in the background
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut db = PickleDb::new("example.db", PickleDbDumpPolicy::AutoDump);
+    /// db.set_background_dump(true).unwrap();
+    ///
+    /// for i in 0..1000 {
+    ///     // returns before the file is written
+    ///     db.set(&format!("key{}", i), &i).unwrap();
+    /// }
+    ///
+    /// // wait until the file contains all the keys
+    /// db.flush().unwrap();
+    /// ```
+    ///
+    pub fn set_background_dump(&mut self, enabled: bool) -> Result<(), error::Error> {
+        match (enabled, self.writer.take()) {
+            (true, None) => self.writer = Some(BackgroundWriter::start()?),
+            (true, Some(writer)) => self.writer = Some(writer),
+            (false, Some(writer)) => writer.flush()?,
+            (false, None) => (),
+        }
+        Ok(())
+    }
+
+    /// Wait until all the dumps so far were written to the file.
+    ///
+    /// This is needed only when dumping in the background (please see [set_background_dump()](#method.set_background_dump)),
+    /// otherwise every dump is written to the file before it returns and this method does nothing. The error of the last write
+    /// that failed since the previous call to this method is returned, if any.
+    ///
+    /// Please note that this method doesn't dump changes which the dump policy didn't dump yet, for that call [dump()](#method.dump) first.
+    ///
+    pub fn flush(&self) -> Result<(), error::Error> {
+        match &self.writer {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Compress the dumped DB file.
+    ///
+    /// The compression applies from the next dump on. A DB that is loaded from a compressed file keeps the compression of
+    /// the file, and loading detects the compression automatically, so compressing the file doesn't change anything else.
+    /// Please see [Compression](compression/enum.Compression.html) for the available algorithms, each of which requires
+    /// a cargo feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `compression` - the compression of the DB file
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use pickledb::compression::Compression;
+    ///
+    /// let mut db = PickleDb::load("example.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    /// db.set_compression(Compression::Zstd);
+    /// db.dump();
+    ///
+    /// // the compression is detected upon load
+    /// let db = PickleDb::load("example.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    /// assert_eq!(db.compression(), Compression::Zstd);
+    /// ```
+    ///
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    /// Get the compression of the dumped DB file, please see [set_compression()](#method.set_compression).
+    ///
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Dump the DB file in the format of [Python's PickleDB](https://pythonhosted.org/pickleDB/), so Python services can
+    /// load it.
+    ///
+    /// The format applies from the next dump on, and a DB loaded by [load_python_compat()](#method.load_python_compat)
+    /// already uses it. The file is a plain JSON object of the keys and the lists, in which lists are arrays. Namespaces,
+    /// indexes, schemas and the checksum have no place in this format, so they aren't dumped: indexes and schemas have to
+    /// be created again after the DB is loaded. Python's PickleDB can't read a file which is compressed or encrypted.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - whether the DB file is dumped in the format of Python's PickleDB
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut db = PickleDb::new("shared.db", PickleDbDumpPolicy::AutoDump);
+    /// db.set_python_compat(true);
+    /// db.set("num", &100).unwrap();
+    ///
+    /// // the file can be read by Python's PickleDB, and by load_python_compat()
+    /// let db = PickleDb::load_python_compat("shared.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    /// ```
+    ///
+    pub fn set_python_compat(&mut self, enabled: bool) {
+        self.python_compat = enabled;
+    }
+
+    /// Check whether the DB file is dumped in the format of Python's PickleDB, please see
+    /// [set_python_compat()](#method.set_python_compat).
+    ///
+    pub fn python_compat(&self) -> bool {
+        self.python_compat
+    }
+
+    /// Constructs a new `PickleDB` instance whose file is encrypted.
+    ///
+    /// The DB file is encrypted with ChaCha20-Poly1305 upon every dump, after it's compressed (if it is), and it can be loaded
+    /// only by [load_encrypted()](#method.load_encrypted) with the same key. Please see [encryption](encryption/index.html)
+    /// for more details. This method is available only when the `encryption` feature is enabled.
+    ///
+    /// An error is returned if a random salt can't be generated or the key can't be derived from the passphrase.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - a path where the DB will be stored
+    /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file. Please see
+    ///   [PickleDB::load()](#method.load) to understand the different policy options
+    /// * `key` - the key the DB file is encrypted with, or the passphrase it's derived from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use pickledb::encryption::EncryptionKey;
+    ///
+    /// let key = EncryptionKey::from_passphrase("correct horse battery staple");
+    /// let mut db = PickleDb::new_encrypted("example.db", PickleDbDumpPolicy::AutoDump, key).unwrap();
+    /// db.set("key1", &100).unwrap();
+    /// ```
+    ///
+    #[cfg(feature = "encryption")]
+    pub fn new_encrypted(location: &str, dump_policy: PickleDbDumpPolicy, key: EncryptionKey) -> Result<PickleDb, error::Error> {
+        let cipher = Cipher::new(key)?;
+        let mut db = PickleDb::new(location, dump_policy);
+        db.cipher = Some(Arc::new(cipher));
+        Ok(db)
+    }
+
+    /// Load a DB from an encrypted file.
+    ///
+    /// This method is similar to the [PickleDB::load()](#method.load) method, but the file is decrypted with `key` first and
+    /// the loaded DB keeps encrypting its file with the same key. If the key is wrong, or the file was changed since it was
+    /// written (including a file which isn't encrypted at all), [Error::Decryption](error/enum.Error.html#variant.Decryption)
+    /// is returned. This method is available only when the `encryption` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - a path where the DB is loaded from
+    /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file. Please see
+    ///   [PickleDB::load()](#method.load) to understand the different policy options
+    /// * `key` - the key the DB file was encrypted with, or the passphrase it was derived from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use pickledb::encryption::EncryptionKey;
+    /// use pickledb::error::Error;
+    ///
+    /// let key = EncryptionKey::from_passphrase("correct horse battery staple");
+    /// match PickleDb::load_encrypted("example.db", PickleDbDumpPolicy::AutoDump, key) {
+    ///     Ok(db) => println!("{} keys", db.total_keys()),
+    ///     Err(Error::Decryption) => println!("wrong passphrase"),
+    ///     Err(err) => println!("{}", err),
+    /// }
+    /// ```
+    ///
+    #[cfg(feature = "encryption")]
+    pub fn load_encrypted(location: &str, dump_policy: PickleDbDumpPolicy, key: EncryptionKey) -> Result<PickleDb, error::Error> {
+        let (contents, cipher) = encryption::decrypt(&fs::read(location)?, key)?;
+        let mut db = PickleDb::from_contents(location, contents, dump_policy)?;
+        db.cipher = Some(Arc::new(cipher));
+        Ok(db)
+    }
+
+    /// Change the key the DB file is encrypted with.
+    ///
+    /// The DB file is rewritten right away with the new key (unless the dump policy is
+    /// [PickleDbDumpPolicy::NeverDump](enum.PickleDbDumpPolicy.html#variant.NeverDump)), including changes that weren't dumped yet,
+    /// and from then on it can be loaded only with the new key. A DB that isn't encrypted becomes encrypted. The file is written to a
+    /// temporary file which then replaces it, so it's never found partially written. Please note that backups which were written
+    /// before, by [backup_to()](#method.backup_to) or by [set_backup_rotation()](#method.set_backup_rotation), keep the previous key.
+    /// This method is available only when the `encryption` feature is enabled.
+    ///
+    /// If the file can't be written an error is returned, and the DB keeps the new key for its next dumps.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the new key, or the passphrase it's derived from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use pickledb::encryption::EncryptionKey;
+    ///
+    /// let mut db = PickleDb::load_encrypted("example.db", PickleDbDumpPolicy::AutoDump, old_key).unwrap();
+    /// db.rekey(new_key).unwrap();
+    /// ```
+    ///
+    #[cfg(feature = "encryption")]
+    pub fn rekey(&mut self, key: EncryptionKey) -> Result<(), error::Error> {
+        let cipher = Cipher::new(key)?;
+        // the dumps that were submitted before are written with the previous key, so they must not overwrite the new file
+        self.flush()?;
+        self.cipher = Some(Arc::new(cipher));
+        if let PickleDbDumpPolicy::NeverDump = self.dump_policy {
+            return Ok(())
+        }
+        backup::write_atomically(&self.db_file_path, &self.contents()?)?;
+        self.reset_dump_stats();
+        Ok(())
+    }
+
+    /// Serialize, compress and encrypt the data to the contents of a DB file
+    pub(crate) fn contents(&self) -> Result<Vec<u8>, error::Error> {
+        let contents = compression::encode(self.serialize()?, self.compression)?;
+        #[cfg(feature = "encryption")]
+        {
+            if let Some(cipher) = &self.cipher {
+                return cipher.encrypt(&contents);
+            }
+        }
+        Ok(contents)
+    }
+
+    /// Serialize the data to the contents of a DB file
+    pub(crate) fn serialize(&self) -> Result<String, serde_json::Error> {
+        let db_file = format::DbFileRef {
+            format: format::VERSION,
+            map: &self.map,
+            list_map: &self.list_map,
+            namespaces: &self.namespaces,
+            indexes: &self.indexes,
+            key_schemas: &self.key_schemas,
+            list_schemas: &self.list_schemas,
+            python_compat: self.python_compat,
+        };
+        db_file.to_json()
+    }
+
+    /// The changes since the last dump
+    pub(crate) fn dump_stats(&self) -> DumpStats {
+        DumpStats {
+            writes: self.writes_since_dump,
+            bytes: self.bytes_since_dump,
+            since_last_dump: self.last_dump.elapsed(),
+        }
+    }
+
+    /// The number of backups to keep when dumping. Nothing is rotated if nothing changed since the last dump,
+    /// since the new backup would be the same as the DB file
+    pub(crate) fn backups_to_rotate(&self) -> usize {
+        if self.writes_since_dump > 0 {
+            self.backups
+        } else {
+            0
+        }
+    }
+
+    /// Count the bytes of changes even if the dump policy doesn't use them, for a DB whose dumps are decided by a policy
+    /// of its owner that uses them
+    #[cfg(feature = "tokio")]
+    pub(crate) fn set_count_bytes(&mut self, count_bytes: bool) {
+        self.count_bytes = count_bytes;
+    }
+
+    /// The number of bytes a change of `key` to or from `value` is counted as in the dump stats
+    fn change_size(&self, key: &str, value: Option<&Value>) -> u64 {
+        policy::change_size(self.count_bytes || self.dump_policy.uses_bytes(), key, value)
+    }
+
+    pub(crate) fn reset_dump_stats(&mut self) {
+        self.last_dump = Instant::now();
+        self.writes_since_dump = 0;
+        self.bytes_since_dump = 0;
+    }
+
+    /// Reset the stats after a dump of the changes counted by `dumped`, which were taken when the dump started.
+    /// Changes that were made while the file was written are still counted
+    #[cfg(feature = "tokio")]
+    pub(crate) fn reset_dump_stats_of(&mut self, dumped: &DumpStats) {
+        self.last_dump = Instant::now();
+        self.writes_since_dump = self.writes_since_dump.saturating_sub(dumped.writes);
+        self.bytes_since_dump = self.bytes_since_dump.saturating_sub(dumped.bytes);
+    }
+
+    fn dumpdb(&mut self) {
+        self.writes_since_dump += 1;
+        let stats = self.dump_stats();
+        if self.dump_policy.should_dump(&stats) {
+            self.dump();
+        }
+    }
+
+    /// Reload the key-value pairs and the lists from the DB file.
+    ///
+    /// This is useful when the file is edited or replaced by another process, for example when a long-running process
+    /// holds a DB loaded by [load_read_only()](#method.load_read_only). The new content is validated before it replaces the
+    /// data in memory: if the file can't be read or parsed, or if one of its values violates a unique index or a schema
+    /// of this DB, an error is returned and the data in memory is left untouched. Namespaces, indexes and schemas aren't
+    /// reloaded, and changes in memory which weren't dumped yet are discarded.
+    ///
+    /// Upon success the keys and lists whose content changed are returned, and every one of them is reported to the
+    /// subscriptions and to the after-mutation hooks as a [ChangeKind::Reloaded](watch/enum.ChangeKind.html#variant.Reloaded)
+    /// change. For reloading automatically whenever the file changes please see [watch_file()](#method.watch_file).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut db = PickleDb::load_read_only("example.db").unwrap();
+    ///
+    /// // ... another process changes the file ...
+    ///
+    /// for key in db.reload().unwrap() {
+    ///     println!("{} changed", key);
+    /// }
+    /// ```
+    ///
+    pub fn reload(&mut self) -> Result<Vec<String>, error::Error> {
+        #[allow(unused_mut)]
+        let mut contents = fs::read(&self.db_file_path)?;
+        #[cfg(feature = "encryption")]
+        {
+            if let Some(cipher) = &self.cipher {
+                contents = cipher.decrypt(&contents)?;
+            }
+        }
+        let (json, _compression) = compression::decode(contents)?;
+        let db_from_file = if self.python_compat {
+            format::parse_python(&json)?
+        } else {
+            format::verify(&json)?;
+            format::parse(&json)?
+        };
+        for (key, val) in &db_from_file.map {
+            schema::check_all(&self.key_schemas, key, val)?;
+        }
+        for (name, schema) in &self.list_schemas {
+            if let Some(list) = db_from_file.list_map.get(name) {
+                for (pos, item) in list.iter().enumerate() {
+                    schema.check_item(name, pos, item)?;
+                }
+            }
+        }
+        for (name, index) in &self.indexes {
+            index.check_map(name, &db_from_file.map)?;
+        }
+
+        let old_map = std::mem::replace(&mut self.map, Arc::new(db_from_file.map));
+        let old_list_map = std::mem::replace(&mut self.list_map, Arc::new(db_from_file.list_map));
+        for index in self.indexes.values_mut() {
+            index.rebuild(&self.map);
+        }
+
+        let content = |map: &HashMap<String, Value>, list_map: &HashMap<String, Vec<Value>>, key: &str| {
+            map.get(key).cloned().or_else(|| list_map.get(key).cloned().map(Value::Array))
+        };
+        let mut changed: Vec<String> = old_map.keys().chain(old_list_map.keys()).chain(self.map.keys()).chain(self.list_map.keys())
+            .filter(|key| (old_map.get(*key), old_list_map.get(*key)) != (self.map.get(*key), self.list_map.get(*key)))
+            .cloned()
+            .collect();
+        changed.sort();
+        changed.dedup();
+
+        for key in &changed {
+            let old = content(&old_map, &old_list_map, key);
+            let new = content(&self.map, &self.list_map, key);
+            self.hooks.after(None, key, ChangeKind::Reloaded, old.as_ref(), new.as_ref());
+            self.watchers.notify(None, key, ChangeKind::Reloaded, old.as_ref(), new.as_ref());
+        }
+        Ok(changed)
+    }
+
+    /// Watch the DB file and reload the key-value pairs and lists whenever it changes.
+    ///
+    /// The DB is moved into the returned [WatchedPickleDb](file_watch/struct.WatchedPickleDb.html), through which it is
+    /// accessed from now on. Whenever the file changes it is reloaded by [reload()](#method.reload) on a background
+    /// thread, so the new content is validated first and subscribers (please see [subscribe()](#method.subscribe)) receive
+    /// a [ChangeKind::Reloaded](watch/enum.ChangeKind.html#variant.Reloaded) event for every key or list that changed.
+    /// The platform's file notifications (inotify on Linux) are used when they're available, otherwise the file is polled
+    /// every [file_watch::DEFAULT_POLL_INTERVAL](file_watch/constant.DEFAULT_POLL_INTERVAL.html).
+    /// Since changes in memory which weren't dumped yet are discarded upon reload, this is mostly useful for DBs loaded
+    /// by [load_read_only()](#method.load_read_only) or with [PickleDbDumpPolicy::AutoDump](enum.PickleDbDumpPolicy.html#variant.AutoDump).
+    ///
+    /// This method is available only when the `file-watch` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let db = PickleDb::load_read_only("example.db").unwrap().watch_file().unwrap();
+    /// let changes = db.lock().subscribe("*");
+    ///
+    /// // ... another process changes the file ...
+    ///
+    /// let event = changes.recv().unwrap();
+    /// println!("{} is now {:?}", event.key, db.lock().get::<String>(&event.key));
+    /// ```
+    ///
+    #[cfg(feature = "file-watch")]
+    pub fn watch_file(self) -> Result<WatchedPickleDb, error::Error> {
+        WatchedPickleDb::new(self, None)
+    }
+
+    /// Watch the DB file by polling it in a certain interval, and reload the key-value pairs and lists whenever it changes.
+    ///
+    /// This method is the same as [watch_file()](#method.watch_file), but always polls the file instead of using the
+    /// platform's file notifications, which is useful for file systems that don't support them (for example network
+    /// file systems). This method is available only when the `file-watch` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - the interval in which the file is polled
+    ///
+    #[cfg(feature = "file-watch")]
+    pub fn watch_file_polling(self, interval: Duration) -> Result<WatchedPickleDb, error::Error> {
+        WatchedPickleDb::new(self, Some(interval))
+    }
+
+    /// Set a key-value pair.
+    /// 
+    /// The key has to be a string but the value can be of any type that is serializable.
+    /// That includes all primitive types, vectors, tuples and every struct that has the 
+    /// `#[derive(Serialize, Deserialize)` attribute.
+    /// If the value violates a unique index (please see [create_unique_index()](#method.create_unique_index))
+    /// it isn't set and an [Error::ConstraintViolation](error/enum.Error.html#variant.ConstraintViolation) is returned.
+    /// If it doesn't conform to the schema of its key (please see [set_key_schema()](#method.set_key_schema))
+    /// it isn't set and an [Error::SchemaViolation](error/enum.Error.html#variant.SchemaViolation) is returned.
+    /// If a before-mutation hook (please see [add_before_hook()](#method.add_before_hook)) vetoes it, it isn't set
+    /// and an [Error::Vetoed](error/enum.Error.html#variant.Vetoed) is returned.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `key` - a string key
+    /// * `value` - a value of any serializable type
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,ignore
+    /// // set a number
+    /// db.set("key1", &100).unwrap();
+    /// 
+    /// // set a floating point number
+    /// db.set("key2", &1.234).unwrap();
+    /// 
+    /// // set a String
+    /// db.set("key3", &String::from("hello world")).unwrap();
+    /// 
+    /// // set a Vec
+    /// db.set("key4", &vec![1,2,3]).unwrap();
+    /// 
+    /// // set a struct
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Coor {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    /// let mycoor = Coor { x: 1, y : 2 };
+    /// db.set("key5", &mycoor).unwrap();
+    /// ```
+    /// 
+    pub fn set<V>(&mut self, key: &str, value: &V) -> Result<(), error::Error>
+        where
+            V: Serialize
+    {
+        self.set_in(None, key, serde_json::to_value(value)?)
+    }
+
+    /// Get a value of a key.
+    /// 
+    /// The key is always a string but the value can be of any type. It's the user's
+    /// responsibility to know the value type and give it while calling this method.
+    /// If the key doesn't exist or if the type is wrong, `None` will be returned.
+    /// Otherwise `Some(V)` will be returned.
+    /// Since the values are stored in a serialized way the returned object is
+    /// not a reference to the value stored in a DB but actually a new instance of it
+    /// 
+    /// # Arguments
+    /// 
+    /// * `key` - a string key
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,ignore
+    /// // read a num
+    /// let num = db.get::<i32>("key1").unwrap();
+    /// 
+    /// // read a floating point number
+    /// let float_num = db.get::<f32>("key2").unwrap();
+    /// 
+    /// // read a String
+    /// let my_str = db.get::<String>("key3").unwrap();
+    /// 
+    /// // read a Vec
+    /// ley vec = db.get::<Vec<i32>>("key4").unwrap();
+    /// 
+    /// // read a struct
+    /// let coor = db.get::<Coor>("key5").unwrap();
+    /// ```
+    /// 
+    #[allow(clippy::manual_ok_err)]
+    pub fn get<V>(&self, key: &str) -> Option<V> 
+        where 
+            V: DeserializeOwned
+    {
+        match self.map.get(key) {
+            Some(val) => match V::deserialize(val) {
+                Ok(val) => Some(val),
+                Err(_) => None
+            },
+
+            None => None,
+        }
+    }
+
+    /// Check if a key exists.
+    /// 
+    /// This method returns `true` if the key exists and `false` otherwise.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `key` - the key to check
+    /// 
+    #[allow(clippy::unnecessary_get_then_check)]
+    pub fn exists(&self, key: &str) -> bool {
+        self.map.get(key).is_some() || self.list_map.get(key).is_some()
+    }
+
+    /// Get a vector of all the keys in the DB.
+    /// 
+    /// The keys returned in the vector are not references to the actual key string
+    /// objects but rather a clone of them.
+    /// 
+    #[allow(clippy::iter_kv_map)]
+    pub fn get_all(&self) -> Vec<String> {
+        [self.map
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<String>>(),
+
+        self.list_map
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<String>>()]
+        
+        .concat()
+    }
+
+    /// Get a vector of the keys in the DB whose names match a glob pattern.
+    ///
+    /// Like [get_all()](#method.get_all) both key-value pairs and lists are returned. In the pattern `*` matches any sequence
+    /// of characters and `?` matches a single character, for example `user:*` or `config.??`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - the glob pattern to match the keys against
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// for key in db.get_all_matching("user:*") {
+    ///     println!("{}", key);
+    /// }
+    /// ```
+    ///
+    pub fn get_all_matching(&self, pattern: &str) -> Vec<String> {
+        self.map.keys()
+            .chain(self.list_map.keys())
+            .filter(|key| watch::matches(pattern, key))
+            .cloned()
+            .collect()
+    }
+
+    /// Get the total number of keys in the DB.
+    /// 
+    pub fn total_keys(&self) -> usize {
+        self.map.iter().len() + self.list_map.iter().len()
+    }
+
+    /// Remove a key-value pair or a list from the DB.
+    /// 
+    /// This methods returns `true` if the key was found in the DB or false if it wasn't found
+    /// 
+    /// # Arguments
+    /// 
+    /// * `key` - the key or list name to remove
+    /// 
+    pub fn rem(&mut self, key: &str) -> bool {
+        self.try_rem(key).unwrap_or(false)
+    }
+
+    /// Remove a key-value pair or a list from the DB, reporting a veto of a before-mutation hook.
+    ///
+    /// This method is the same as [rem()](#method.rem), but if a before-mutation hook vetoes the removal an
+    /// [Error::Vetoed](error/enum.Error.html#variant.Vetoed) is returned instead of `false`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key or list name to remove
+    ///
+    pub fn try_rem(&mut self, key: &str) -> Result<bool, error::Error> {
+        self.rem_in(None, key)
+    }
+
+    /// Create a new list.
+    /// 
+    /// This method just creates a new list, it doesn't add any elements to it.
+    /// For adding elements to the list please call [ladd()](#method.ladd) or [lextend()](#method.lextend).
+    /// If another list or value is already set under this key, they will be overridden,
+    /// meaning the new list will override the old list or value.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `name` - the key of the list that will be created
+    /// 
+    pub fn lcreate(&mut self, name: &str) {
+        let _ = self.try_lcreate(name);
+    }
+
+    /// Create a new list, reporting a veto of a before-mutation hook.
+    ///
+    /// This method is the same as [lcreate()](#method.lcreate), but if a before-mutation hook vetoes the creation
+    /// an [Error::Vetoed](error/enum.Error.html#variant.Vetoed) is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the key of the list that will be created
+    ///
+    pub fn try_lcreate(&mut self, name: &str) -> Result<(), error::Error> {
+        self.lcreate_in(None, name)
+    }
+
+    /// Create a new list that holds the given items.
+    ///
+    /// This method is the same as calling [try_lcreate()](#method.try_lcreate) and then [lextend()](#method.lextend),
+    /// except that the items are checked before the list is created. If a before-mutation hook vetoes the creation or
+    /// one of the items, or one of the items doesn't conform to the schema of the list, an error is returned and the DB
+    /// isn't changed, so a list or value that is already set under this key is kept. Otherwise it's overridden by the
+    /// new list.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the key of the list that will be created
+    /// * `seq` - a slice containing the items of the list
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// // create a list of 3 numbers
+    /// db.lcreate_with("list1", &[100, 200, 300]).unwrap();
+    /// ```
+    ///
+    pub fn lcreate_with<V>(&mut self, name: &str, seq: &[V]) -> Result<(), error::Error>
+        where
+            V: Serialize
+    {
+        self.lcreate_with_in(None, name, seq)
+    }
+
+    /// Check if a list exists.
+    /// 
+    /// This method returns `true` if the list name exists and `false` otherwise.
+    /// The difference between this method and [exists()](#method.exists) is that this methods checks only
+    /// for lists with that name (key) and [exists()](#method.exists) checks for both values and lists.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `name` - the list key to check
+    /// 
+    #[allow(clippy::unnecessary_get_then_check)]
+    pub fn lexists(&self, name: &str) -> bool {
+        self.list_map.get(name).is_some()
+    }
+
+    /// Add a single item to an existing list.
     /// 
     /// As mentioned before, the lists are heterogeneous, meaning a single list can contain 
     /// items of different types. That means that the item can be of any type that is serializable.
     /// That includes all primitive types, vectors, tuples and every struct that has the 
     /// `#[derive(Serialize, Deserialize)` attribute.
-    /// The method return `true` if the item was added successfully or `false` if the list name 
-    /// isn't found in the DB.
+    /// The method return `Ok(true)` if the item was added successfully or `Ok(false)` if the list name 
+    /// isn't found in the DB. If the item doesn't conform to the schema of the list (please see
+    /// [set_list_schema()](#method.set_list_schema)) it isn't added and an
+    /// [Error::SchemaViolation](error/enum.Error.html#variant.SchemaViolation) is returned, and if a before-mutation
+    /// hook vetoes it an [Error::Vetoed](error/enum.Error.html#variant.Vetoed) is returned.
     /// 
     /// # Arguments
     /// 
@@ -455,16 +1631,16 @@ impl PickleDb {
     /// db.lcreate("list1");
     /// 
     /// // add a number item to the list
-    /// db.ladd("list1", &100);
+    /// db.ladd("list1", &100).unwrap();
     /// 
     /// // add a String item to the list
-    /// db.ladd("list1", &String::from("my string"));
+    /// db.ladd("list1", &String::from("my string")).unwrap();
     /// 
     /// // add a vector item to the list
-    /// db.ladd("list1", &vec!["aa", "bb", "cc"]);
+    /// db.ladd("list1", &vec!["aa", "bb", "cc"]).unwrap();
     /// ```
     /// 
-    pub fn ladd<V>(&mut self, name: &str, value: &V) -> bool
+    pub fn ladd<V>(&mut self, name: &str, value: &V) -> Result<bool, error::Error>
         where
             V: Serialize
     {
@@ -480,8 +1656,11 @@ impl PickleDb {
     /// This method adds multiple items to the list, but since they're in a vector that means all
     /// of them are of the same type. Of course it doesn't mean that the list cannot contain items
     /// of other types as well, as you can see in the example below.
-    /// The method return `true` if all items were added successfully or `false` if the list name 
-    /// isn't found in the DB.
+    /// The method return `Ok(true)` if all items were added successfully or `Ok(false)` if the list name 
+    /// isn't found in the DB. If one of the items doesn't conform to the schema of the list none of the
+    /// items are added and an [Error::SchemaViolation](error/enum.Error.html#variant.SchemaViolation) is returned.
+    /// Likewise, if a before-mutation hook vetoes one of the items none of them are added and an
+    /// [Error::Vetoed](error/enum.Error.html#variant.Vetoed) is returned.
     /// 
     /// # Arguments
     /// 
@@ -495,33 +1674,23 @@ impl PickleDb {
     /// db.lcreate("list1");
     /// 
     /// // add a bunch of numbers to the list
-    /// db.lextends("list1", &vec![100, 200, 300]);
+    /// db.lextend("list1", &vec![100, 200, 300]).unwrap();
     /// 
     /// // add a String item to the list
-    /// db.ladd("list1", &String::from("my string"));
+    /// db.ladd("list1", &String::from("my string")).unwrap();
     /// 
     /// // add a vector item to the list
-    /// db.ladd("list1", &vec!["aa", "bb", "cc"]);
+    /// db.ladd("list1", &vec!["aa", "bb", "cc"]).unwrap();
     /// 
     /// // now the list contains 5 items and looks like this: [100, 200, 300, "my string", ["aa, "bb", "cc"]]
     /// ```
     /// 
-    pub fn lextend<V>(&mut self, name: &str, seq: &Vec<V>) -> bool
+    #[allow(clippy::ptr_arg)]
+    pub fn lextend<V>(&mut self, name: &str, seq: &Vec<V>) -> Result<bool, error::Error>
         where
             V: Serialize
     {
-        match self.list_map.get_mut(name) {
-            Some(list) => {
-                let serialized: Vec<String> = seq.iter()
-                .map(|x| serde_json::to_string(x).unwrap())
-                .collect();
-                list.extend(serialized);
-                self.dumpdb();
-                true
-            },
-
-            None => false,
-        }
+        self.lextend_in(None, name, seq)
     }
 
     /// Get an item of of a certain list in a certain position.
@@ -545,10 +1714,10 @@ impl PickleDb {
     /// db.lcreate("list1");
     /// 
     /// // add a number to list1
-    /// db.ladd("list1", &100));
+    /// db.ladd("list1", &100).unwrap();
     /// 
     /// // add a string to list1
-    /// db.ladd("list1", &String::from("my string"));
+    /// db.ladd("list1", &String::from("my string")).unwrap();
     /// 
     /// // read the first item in the list - int
     /// let x = db.lget::<i32>("list1", 0).unwrap();
@@ -556,16 +1725,17 @@ impl PickleDb {
     /// // read the second item in the list - string
     /// let s = db.lget::<String>("list1", 1).unwrap();
     /// ```
+    #[allow(clippy::manual_ok_err)]
     pub fn lget<V>(&self, name: &str, pos: usize) -> Option<V>
         where
             V: DeserializeOwned
     {
         match self.list_map.get(name) {
             Some(list) => match list.get(pos) {
-                Some(val_as_string) => match serde_json::from_str(&val_as_string) {
+                Some(val) => match V::deserialize(val) {
                     Ok(val) => Some(val),
                     Err(_) => None,
-                }
+                },
                 None => None,
             }
             None => None,
@@ -600,71 +1770,413 @@ impl PickleDb {
     /// * `name` - the list key to remove
     /// 
     pub fn lrem_list(&mut self, name: &str) -> usize {
-        let res = self.llen(name);
-        self.list_map.remove(name);
+        self.try_lrem_list(name).unwrap_or(0)
+    }
+
+    /// Remove a list, reporting a veto of a before-mutation hook.
+    ///
+    /// This method is the same as [lrem_list()](#method.lrem_list), but if a before-mutation hook vetoes the removal
+    /// an [Error::Vetoed](error/enum.Error.html#variant.Vetoed) is returned instead of 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key to remove
+    ///
+    pub fn try_lrem_list(&mut self, name: &str) -> Result<usize, error::Error> {
+        self.lrem_list_in(None, name)
+    }
+
+    /// Pop an item out of a list.
+    /// 
+    /// This method takes a list name and a position inside the list, removes the
+    /// item in this position and returns it to the user. It's the user's responsibility 
+    /// to know what is the correct type of the item and give it while calling this method.
+    /// Since the item in the lists are stored in a serialized way the returned object 
+    /// is not a reference to the item stored in a DB but actually a new instance of it.
+    /// If the list is not found in the DB or the given position is out of bounds
+    /// no item will be removed and `None` will be returned. Otherwise the item will be
+    /// removed and `Some(V)` will be returned.
+    /// This method is very similar to [lrem_value()](#method.lrem_value), the only difference is that this 
+    /// methods returns the value and [lrem_value()](#method.lrem_value) returns only an indication whether
+    /// the item was removed or not.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `name` - the list key
+    /// * `pos` - the position of the item to remove
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,ignore
+    /// // create a list
+    /// db.lcreate("list1");
+    /// 
+    /// // add 4 items to the list
+    /// db.lextend("list1", &vec![1,2,3,4]).unwrap();
+    /// 
+    /// // remove item in position 2
+    /// let item2 = db.lpop::<i32>("list1", 2);
+    /// 
+    /// // item2 contains 3 and the list now looks like this: [1, 2, 4]
+    /// 
+    /// // remove item in position 1
+    /// let item1 = db.lpop::<i32>("list1", 1);
+    /// 
+    /// // item1 contains 2 and the list now looks like this: [1, 4]
+    /// ```
+    /// 
+    pub fn lpop<V>(&mut self, name: &str, pos: usize) -> Option<V> 
+        where
+            V: DeserializeOwned
+    {
+        self.try_lpop(name, pos).unwrap_or(None)
+    }
+
+    /// Pop an item out of a list, reporting a veto of a before-mutation hook.
+    ///
+    /// This method is the same as [lpop()](#method.lpop), but if a before-mutation hook vetoes the removal an
+    /// [Error::Vetoed](error/enum.Error.html#variant.Vetoed) is returned instead of `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    /// * `pos` - the position of the item to remove
+    ///
+    pub fn try_lpop<V>(&mut self, name: &str, pos: usize) -> Result<Option<V>, error::Error>
+        where
+            V: DeserializeOwned
+    {
+        match self.lpop_in(None, name, pos)? {
+            Some(res) => Ok(V::deserialize(res).ok()),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the keys of all values that match a query.
+    ///
+    /// The query is a JSON path, optionally followed by a comparison with a JSON literal, for example
+    /// `$.age > 30`. Please see the [query module](query/index.html) for the full syntax.
+    /// Only key-value pairs are searched, for searching inside lists please use [lquery()](#method.lquery).
+    /// An [Error::InvalidQuery](error/enum.Error.html#variant.InvalidQuery) is returned if the query can't be parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - the query
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// db.set("john", &User { name: String::from("John"), age: 35 }).unwrap();
+    /// db.set("jane", &User { name: String::from("Jane"), age: 25 }).unwrap();
+    ///
+    /// // keys contains only "john"
+    /// let keys = db.query("$.age > 30").unwrap();
+    /// ```
+    ///
+    pub fn query(&self, expr: &str) -> Result<Vec<String>, error::Error> {
+        Ok(self.query_with(&Query::parse(expr)?))
+    }
+
+    /// Get the keys of all values that match a query which was already parsed or built.
+    ///
+    /// This method is the same as [query()](#method.query) but takes a [Query](query/struct.Query.html) object.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use pickledb::query::Query;
+    ///
+    /// let keys = db.query_with(&Query::path("$.address.city").unwrap().eq(&"London"));
+    /// ```
+    ///
+    pub fn query_with(&self, query: &Query) -> Vec<String> {
+        self.map
+            .iter()
+            .filter(|(_, val)| query.matches(val))
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    /// Get the positions of all items in a list that match a query.
+    ///
+    /// This method is similar to [query()](#method.query) but searches the items of a list. The positions are
+    /// returned in ascending order. If the list doesn't exist an empty vector is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    /// * `expr` - the query
+    ///
+    pub fn lquery(&self, name: &str, expr: &str) -> Result<Vec<usize>, error::Error> {
+        Ok(self.lquery_with(name, &Query::parse(expr)?))
+    }
+
+    /// Get the positions of all items in a list that match a query which was already parsed or built.
+    ///
+    /// This method is the same as [lquery()](#method.lquery) but takes a [Query](query/struct.Query.html) object.
+    ///
+    pub fn lquery_with(&self, name: &str, query: &Query) -> Vec<usize> {
+        match self.list_map.get(name) {
+            Some(list) => list
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| query.matches(item))
+                .map(|(pos, _)| pos)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Create a secondary index on a field of the values.
+    ///
+    /// The index maps the part of each value that `path` points to, to the keys of the values that contain it,
+    /// so [find_by_index()](#method.find_by_index) can find keys by a field of their value without going over all values.
+    /// The index is kept up to date by [set()](#method.set), [rem()](#method.rem) and every other method that changes
+    /// key-value pairs. Only key-value pairs are indexed, list items and keys in namespaces aren't
+    /// (namespaces have indexes of their own, please see [Namespace::create_index()](struct.Namespace.html#method.create_index)).
+    /// The index definition is stored in the DB file and the index is rebuilt when the DB is loaded.
+    /// If an index with the same name exists it is replaced. An [Error::InvalidQuery](error/enum.Error.html#variant.InvalidQuery)
+    /// is returned if the path can't be parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the index name
+    /// * `path` - a JSON path inside the values, please see the [query module](query/index.html) for the syntax
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// db.create_index("by_email", "$.email").unwrap();
+    ///
+    /// db.set("user1", &User { name: String::from("John"), email: String::from("john@example.com") }).unwrap();
+    ///
+    /// // keys contains "user1"
+    /// let keys = db.find_by_index("by_email", &"john@example.com");
+    /// ```
+    ///
+    pub fn create_index(&mut self, name: &str, path: &str) -> Result<(), error::Error> {
+        let index = Index::build(name, JsonPath::parse(path)?, "", false, &self.map)?;
+        self.indexes.insert(String::from(name), index);
+        self.dumpdb();
+        Ok(())
+    }
+
+    /// Create a unique secondary index on a field of the values.
+    ///
+    /// A unique index is the same as an index created by [create_index()](#method.create_index), but it doesn't
+    /// allow two keys to have the same value in the indexed field: [set()](#method.set) returns an
+    /// [Error::ConstraintViolation](error/enum.Error.html#variant.ConstraintViolation) instead of setting such a value.
+    /// The index covers only keys that start with `key_prefix`, an empty prefix covers all keys.
+    /// Values in which the field doesn't exist aren't constrained.
+    /// If existing values already violate the constraint the index isn't created and an
+    /// [Error::ConstraintViolation](error/enum.Error.html#variant.ConstraintViolation) is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the index name
+    /// * `path` - a JSON path inside the values, please see the [query module](query/index.html) for the syntax
+    /// * `key_prefix` - only keys that start with this prefix are covered by the index
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// db.create_unique_index("unique_email", "$.email", "user:").unwrap();
+    ///
+    /// db.set("user:1", &User { name: String::from("John"), email: String::from("john@example.com") }).unwrap();
+    ///
+    /// // returns an error
+    /// assert!(db.set("user:2", &User { name: String::from("Jane"), email: String::from("john@example.com") }).is_err());
+    /// ```
+    ///
+    pub fn create_unique_index(&mut self, name: &str, path: &str, key_prefix: &str) -> Result<(), error::Error> {
+        let index = Index::build(name, JsonPath::parse(path)?, key_prefix, true, &self.map)?;
+        self.indexes.insert(String::from(name), index);
+        self.dumpdb();
+        Ok(())
+    }
+
+    /// Remove a secondary index.
+    ///
+    /// This methods returns `true` if the index was found or false if it wasn't found
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the index name
+    ///
+    pub fn drop_index(&mut self, name: &str) -> bool {
+        let res = self.indexes.remove(name).is_some();
+        if res {
+            self.dumpdb();
+        }
+        res
+    }
+
+    /// Find the keys of the values in which the indexed field equals a certain value.
+    ///
+    /// The keys are returned in ascending order. If the index doesn't exist or no value matches, an empty vector
+    /// is returned. Fields are compared by their JSON representation, which means `1` and `1.0` are different.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the index name
+    /// * `value` - the value of the indexed field to look for
+    ///
+    pub fn find_by_index<V>(&self, name: &str, value: &V) -> Vec<String>
+        where
+            V: Serialize
+    {
+        match (self.indexes.get(name), serde_json::to_value(value)) {
+            (Some(index), Ok(value)) => index.find(&value),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Attach a JSON Schema to the values of keys that start with a certain prefix.
+    ///
+    /// Once a schema is attached, [set()](#method.set) rejects values of keys that start with `key_prefix` if they don't
+    /// conform to the schema, and returns an [Error::SchemaViolation](error/enum.Error.html#variant.SchemaViolation)
+    /// with the path inside the value in which the problem was found. An empty prefix applies the schema to all keys.
+    /// If a schema is already attached to the prefix it is replaced, and if several prefixes match a key, the value has to
+    /// conform to all of their schemas. The schema applies to the keys of namespaces (please see [namespace()](#method.namespace))
+    /// as well. Schemas are stored in the DB file, so they're applied by every user of the file.
+    ///
+    /// The supported keywords are a subset of JSON Schema draft 7: `type`, `enum`, `const`, `properties`, `required`,
+    /// `additionalProperties`, `minProperties`, `maxProperties`, `items`, `minItems`, `maxItems`, `uniqueItems`,
+    /// `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `multipleOf`, `minLength`, `maxLength`, `allOf`,
+    /// `anyOf`, `oneOf` and `not`. Annotations such as `title` and `description` are ignored. A schema that uses other
+    /// keywords (for example `$ref` or `pattern`) is rejected with an [Error::InvalidSchema](error/enum.Error.html#variant.InvalidSchema).
+    /// If existing values under the prefix don't conform to the schema, it isn't attached and an
+    /// [Error::SchemaViolation](error/enum.Error.html#variant.SchemaViolation) is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_prefix` - the schema applies to keys that start with this prefix
+    /// * `schema` - the JSON Schema
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// db.set_key_schema("user:", &serde_json::json!({
+    ///     "type": "object",
+    ///     "properties": { "age": { "type": "integer", "minimum": 0 } },
+    ///     "required": ["age"]
+    /// })).unwrap();
+    ///
+    /// // returns an error with the path "$.age"
+    /// assert!(db.set("user:1", &User { name: String::from("John"), age: -1 }).is_err());
+    /// ```
+    ///
+    pub fn set_key_schema(&mut self, key_prefix: &str, schema: &Value) -> Result<(), error::Error> {
+        let schema = Schema::new(schema.clone()).map_err(error::Error::InvalidSchema)?;
+        let namespaced = self.namespaces.values().flat_map(|data| data.map.iter());
+        for (key, val) in self.map.iter().chain(namespaced).filter(|(key, _)| key.starts_with(key_prefix)) {
+            schema.check_value(key, val)?;
+        }
+        self.key_schemas.insert(String::from(key_prefix), schema);
+        self.dumpdb();
+        Ok(())
+    }
+
+    /// Remove the JSON Schema attached to a key prefix.
+    ///
+    /// This methods returns `true` if a schema was attached to the prefix or false otherwise
+    ///
+    /// # Arguments
+    ///
+    /// * `key_prefix` - the prefix the schema was attached to
+    ///
+    pub fn remove_key_schema(&mut self, key_prefix: &str) -> bool {
+        let res = self.key_schemas.remove(key_prefix).is_some();
+        if res {
+            self.dumpdb();
+        }
+        res
+    }
+
+    /// Attach a JSON Schema to the items of a list.
+    ///
+    /// Once a schema is attached, [ladd()](#method.ladd) and [lextend()](#method.lextend) reject items that don't conform
+    /// to it and return an [Error::SchemaViolation](error/enum.Error.html#variant.SchemaViolation). The list doesn't have to
+    /// exist, so a schema can be attached before the list is created, and it applies to the lists of namespaces with this
+    /// name as well. Please see [set_key_schema()](#method.set_key_schema)
+    /// for the supported keywords. If existing items of the list don't conform to the schema, it isn't attached and an
+    /// [Error::SchemaViolation](error/enum.Error.html#variant.SchemaViolation) is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    /// * `schema` - the JSON Schema of the list items
+    ///
+    pub fn set_list_schema(&mut self, name: &str, schema: &Value) -> Result<(), error::Error> {
+        let schema = Schema::new(schema.clone()).map_err(error::Error::InvalidSchema)?;
+        let namespaced = self.namespaces.values().filter_map(|data| data.list_map.get(name));
+        for list in self.list_map.get(name).into_iter().chain(namespaced) {
+            for (pos, item) in list.iter().enumerate() {
+                schema.check_item(name, pos, item)?;
+            }
+        }
+        self.list_schemas.insert(String::from(name), schema);
         self.dumpdb();
+        Ok(())
+    }
+
+    /// Remove the JSON Schema attached to a list.
+    ///
+    /// This methods returns `true` if a schema was attached to the list or false otherwise
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    ///
+    pub fn remove_list_schema(&mut self, name: &str) -> bool {
+        let res = self.list_schemas.remove(name).is_some();
+        if res {
+            self.dumpdb();
+        }
         res
     }
 
-    /// Pop an item out of a list.
-    /// 
-    /// This method takes a list name and a position inside the list, removes the
-    /// item in this position and returns it to the user. It's the user's responsibility 
-    /// to know what is the correct type of the item and give it while calling this method.
-    /// Since the item in the lists are stored in a serialized way the returned object 
-    /// is not a reference to the item stored in a DB but actually a new instance of it.
-    /// If the list is not found in the DB or the given position is out of bounds
-    /// no item will be removed and `None` will be returned. Otherwise the item will be
-    /// removed and `Some(V)` will be returned.
-    /// This method is very similar to [lrem_value()](#method.lrem_value), the only difference is that this 
-    /// methods returns the value and [lrem_value()](#method.lrem_value) returns only an indication whether
-    /// the item was removed or not.
-    /// 
+    /// Get a part of a value without reading the whole value.
+    ///
+    /// The part is selected by a JSON path, for example `$.address.city`. Please see the
+    /// [query module](query/index.html) for the full syntax. If the key doesn't exist, the path is invalid or
+    /// doesn't exist in the value, or the type is wrong, `None` will be returned. Otherwise `Some(V)` will be returned.
+    ///
     /// # Arguments
-    /// 
-    /// * `name` - the list key
-    /// * `pos` - the position of the item to remove
-    /// 
+    ///
+    /// * `key` - a string key
+    /// * `path` - a JSON path inside the value
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```rust,ignore
-    /// // create a list
-    /// db.lcreate("list1");
-    /// 
-    /// // add 4 items to the list
-    /// db.lextend("list1", &vec![1,2,3,4]);
-    /// 
-    /// // remove item in position 2
-    /// let item2 = db.lpop::<i32>("list1", 2);
-    /// 
-    /// // item2 contains 3 and the list now looks like this: [1, 2, 4]
-    /// 
-    /// // remove item in position 1
-    /// let item1 = db.lpop::<i32>("list1", 1);
-    /// 
-    /// // item1 contains 2 and the list now looks like this: [1, 4]
+    /// let city = db.get_path::<String>("john", "$.address.city").unwrap();
     /// ```
-    /// 
-    pub fn lpop<V>(&mut self, name: &str, pos: usize) -> Option<V> 
+    ///
+    pub fn get_path<V>(&self, key: &str, path: &str) -> Option<V>
         where
             V: DeserializeOwned
     {
-        match self.list_map.get_mut(name) {
-            Some(list) => {
-                if pos < list.len() {
-                    let res = list.remove(pos);
-                    self.dumpdb();
-                    match serde_json::from_str(&res) {
-                        Ok(val) => Some(val),
-                        Err(_) => None,
-                    }
-                } else {
-                    None
-                }
-            },
-                
-            None => None,
-        }
+        let path = JsonPath::parse(path).ok()?;
+        V::deserialize(path.select(self.map.get(key)?)?).ok()
+    }
+
+    /// Get a part of a list item without reading the whole item.
+    ///
+    /// This method is similar to [get_path()](#method.get_path) but reads an item in a certain position of a list.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    /// * `pos` - the position of the item inside the list
+    /// * `path` - a JSON path inside the item
+    ///
+    pub fn lget_path<V>(&self, name: &str, pos: usize, path: &str) -> Option<V>
+        where
+            V: DeserializeOwned
+    {
+        let path = JsonPath::parse(path).ok()?;
+        V::deserialize(path.select(self.list_map.get(name)?.get(pos)?)?).ok()
     }
 
     /// Remove an item out of a list.
@@ -689,7 +2201,7 @@ impl PickleDb {
     /// db.lcreate("list1");
     /// 
     /// // add 4 items to the list
-    /// db.lextend("list1", &vec![1,2,3,4]);
+    /// db.lextend("list1", &vec![1,2,3,4]).unwrap();
     /// 
     /// // remove item in position 2
     /// db.lrem_value("list1", 2);
@@ -706,22 +2218,525 @@ impl PickleDb {
         where
             V: Serialize
     {
-        match self.list_map.get_mut(name) {
-            Some(list) => {
-                let serialized_value = serde_json::to_string(&value).unwrap();
-                match list.iter().position(|x| *x == serialized_value) {
-                    Some(pos) => {
-                        list.remove(pos);
-                        self.dumpdb();
-                        true
-                    },
-
-                    None => false,
+        self.try_lrem_value(name, value).unwrap_or(false)
+    }
+
+    /// Remove an item out of a list, reporting a veto of a before-mutation hook.
+    ///
+    /// This method is the same as [lrem_value()](#method.lrem_value), but if a before-mutation hook vetoes the removal
+    /// an [Error::Vetoed](error/enum.Error.html#variant.Vetoed) is returned instead of `false`, and if the item can't
+    /// be serialized the serialization error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    /// * `value` - the item to remove
+    ///
+    pub fn try_lrem_value<V>(&mut self, name: &str, value: &V) -> Result<bool, error::Error>
+        where
+            V: Serialize
+    {
+        self.lrem_value_in(None, name, serde_json::to_value(value)?)
+    }
+
+    /// The key-value pairs of the DB itself (`ns` is `None`) or of a namespace, or `None` if the namespace doesn't exist
+    fn keys_in(&self, ns: Option<&str>) -> Option<&HashMap<String, Value>> {
+        match ns {
+            None => Some(&self.map),
+            Some(ns) => self.namespaces.get(ns).map(|data| &data.map),
+        }
+    }
+
+    /// A list of the DB itself (`ns` is `None`) or of a namespace, if it exists
+    fn list_in(&self, ns: Option<&str>, name: &str) -> Option<&Vec<Value>> {
+        match ns {
+            None => self.list_map.get(name),
+            Some(ns) => self.namespaces.get(ns)?.list_map.get(name),
+        }
+    }
+
+    /// The indexes of the DB itself (`ns` is `None`) or of a namespace, or `None` if the namespace doesn't exist
+    fn indexes_in(&self, ns: Option<&str>) -> Option<&HashMap<String, Index>> {
+        match ns {
+            None => Some(&self.indexes),
+            Some(ns) => self.namespaces.get(ns).map(|data| &data.indexes),
+        }
+    }
+
+    /// Split the DB into the data a change of the DB itself (`ns` is `None`) or of a namespace applies to, and the hooks
+    /// and the subscriptions the change is reported to. The namespace is created if it doesn't exist yet, so this is
+    /// called only once the change is known to be valid
+    fn scope_mut(&mut self, ns: Option<&str>) -> (ScopeMut<'_>, &mut Hooks, &mut Watchers) {
+        let scope = match ns {
+            None => ScopeMut {
+                map: DataMut::Shared(&mut self.map),
+                list_map: DataMut::Shared(&mut self.list_map),
+                indexes: &mut self.indexes,
+            },
+            Some(ns) => {
+                let data = Arc::make_mut(&mut self.namespaces).entry(String::from(ns)).or_default();
+                ScopeMut {
+                    map: DataMut::Owned(&mut data.map),
+                    list_map: DataMut::Owned(&mut data.list_map),
+                    indexes: &mut data.indexes,
                 }
             },
+        };
+        (scope, &mut self.hooks, &mut self.watchers)
+    }
+
+    /// Set a value of a key of the DB itself (`ns` is `None`) or of a namespace, once the before-mutation hooks, the
+    /// schemas and the unique indexes allow it
+    pub(crate) fn set_in(&mut self, ns: Option<&str>, key: &str, value: Value) -> Result<(), error::Error> {
+        let value = self.hooks.before_value(ns, key, ChangeKind::Set, value)?;
+        schema::check_all(&self.key_schemas, key, &value)?;
+        if let Some(indexes) = self.indexes_in(ns) {
+            index::check_all(indexes, key, &value)?;
+        }
+        let size = self.change_size(key, Some(&value));
+        let (mut scope, hooks, watchers) = self.scope_mut(ns);
+        let old_list = if scope.list_map.get().contains_key(key) {
+            scope.list_map.get_mut().remove(key)
+        } else {
+            None
+        };
+        index::update_all(scope.indexes, key, scope.map.get().get(key), Some(&value));
+        let old = scope.map.get_mut().insert(String::from(key), value).or_else(|| old_list.map(Value::Array));
+        let new = scope.map.get().get(key);
+        hooks.after(ns, key, ChangeKind::Set, old.as_ref(), new);
+        watchers.notify(ns, key, ChangeKind::Set, old.as_ref(), new);
+        self.bytes_since_dump += size;
+        self.dumpdb();
+        Ok(())
+    }
+
+    /// Remove a key-value pair or a list of the DB itself (`ns` is `None`) or of a namespace
+    pub(crate) fn rem_in(&mut self, ns: Option<&str>, key: &str) -> Result<bool, error::Error> {
+        let is_value = self.keys_in(ns).is_some_and(|map| map.contains_key(key));
+        if !is_value && self.list_in(ns, key).is_none() {
+            return Ok(false);
+        }
+        self.hooks.before(ns, key, ChangeKind::Removed)?;
+        let (mut scope, hooks, watchers) = self.scope_mut(ns);
+        let old = if is_value {
+            let old = scope.map.get_mut().remove(key);
+            index::update_all(scope.indexes, key, old.as_ref(), None);
+            old
+        } else {
+            scope.list_map.get_mut().remove(key).map(Value::Array)
+        };
+        hooks.after(ns, key, ChangeKind::Removed, old.as_ref(), None);
+        watchers.notify(ns, key, ChangeKind::Removed, old.as_ref(), None);
+        self.bytes_since_dump += self.change_size(key, old.as_ref());
+        self.dumpdb();
+        Ok(true)
+    }
+
+    /// Create a list in the DB itself (`ns` is `None`) or in a namespace
+    pub(crate) fn lcreate_in(&mut self, ns: Option<&str>, name: &str) -> Result<(), error::Error> {
+        self.lcreate_with_in::<Value>(ns, name, &[])
+    }
+
+    /// Create a list that holds the given items in the DB itself (`ns` is `None`) or in a namespace, once the
+    /// before-mutation hooks and the schema of the list allow the creation and all of the items
+    pub(crate) fn lcreate_with_in<V>(&mut self, ns: Option<&str>, name: &str, seq: &[V]) -> Result<(), error::Error>
+        where
+            V: Serialize
+    {
+        self.hooks.before(ns, name, ChangeKind::ListCreated)?;
+        let (items, size) = self.check_items(ns, name, 0, seq)?;
+        let size = size + self.change_size(name, None);
+        let (mut scope, hooks, watchers) = self.scope_mut(ns);
+        let old_value = if scope.map.get().contains_key(name) {
+            scope.map.get_mut().remove(name)
+        } else {
+            None
+        };
+        if let Some(old) = &old_value {
+            index::update_all(scope.indexes, name, Some(old), None);
+        }
+        let old = scope.list_map.get_mut().insert(String::from(name), Vec::new()).map(Value::Array).or(old_value);
+        hooks.after(ns, name, ChangeKind::ListCreated, old.as_ref(), Some(&Value::Array(Vec::new())));
+        watchers.notify(ns, name, ChangeKind::ListCreated, old.as_ref(), Some(&Value::Array(Vec::new())));
+        let list = scope.list_map.get_mut().get_mut(name).unwrap();
+        list.extend(items);
+        for item in list.iter() {
+            hooks.after(ns, name, ChangeKind::ListPush, None, Some(item));
+            watchers.notify(ns, name, ChangeKind::ListPush, None, Some(item));
+        }
+        self.bytes_since_dump += size;
+        self.dumpdb();
+        Ok(())
+    }
+
+    /// Serialize items that are added to a list at position `start` and return them as the before-mutation hooks left
+    /// them along with the size of the change, or the error of the first item that is vetoed or violates the schema of the list
+    fn check_items<V>(&mut self, ns: Option<&str>, name: &str, start: usize, seq: &[V]) -> Result<(Vec<Value>, u64), error::Error>
+        where
+            V: Serialize
+    {
+        let hooks = &mut self.hooks;
+        let items = seq.iter()
+            .map(|item| hooks.before_value(ns, name, ChangeKind::ListPush, serde_json::to_value(item)?))
+            .collect::<Result<Vec<Value>, _>>()?;
+        if let Some(schema) = self.list_schemas.get(name) {
+            for (i, item) in items.iter().enumerate() {
+                schema.check_item(name, start + i, item)?;
+            }
+        }
+        let size = items.iter().map(|item| self.change_size(name, Some(item))).sum();
+        Ok((items, size))
+    }
+
+    /// Add items to a list of the DB itself (`ns` is `None`) or of a namespace, once the before-mutation hooks and the
+    /// schema of the list allow all of them
+    pub(crate) fn lextend_in<V>(&mut self, ns: Option<&str>, name: &str, seq: &[V]) -> Result<bool, error::Error>
+        where
+            V: Serialize
+    {
+        let start = match self.list_in(ns, name) {
+            Some(list) => list.len(),
+            None => return Ok(false),
+        };
+        let (serialized, size) = self.check_items(ns, name, start, seq)?;
+        // the list is copied only when it's shared with a snapshot, and only once the items are known to be valid
+        let (mut scope, hooks, watchers) = self.scope_mut(ns);
+        let list = scope.list_map.get_mut().get_mut(name).unwrap();
+        list.extend(serialized);
+        for item in &list[start..] {
+            hooks.after(ns, name, ChangeKind::ListPush, None, Some(item));
+            watchers.notify(ns, name, ChangeKind::ListPush, None, Some(item));
+        }
+        self.bytes_since_dump += size;
+        self.dumpdb();
+        Ok(true)
+    }
+
+    /// Remove a list of the DB itself (`ns` is `None`) or of a namespace and return the number of items it had
+    pub(crate) fn lrem_list_in(&mut self, ns: Option<&str>, name: &str) -> Result<usize, error::Error> {
+        let res = match self.list_in(ns, name) {
+            Some(list) => list.len(),
+            None => return Ok(0),
+        };
+        self.hooks.before(ns, name, ChangeKind::ListRemoved)?;
+        let (mut scope, hooks, watchers) = self.scope_mut(ns);
+        let old = scope.list_map.get_mut().remove(name).map(Value::Array);
+        hooks.after(ns, name, ChangeKind::ListRemoved, old.as_ref(), None);
+        watchers.notify(ns, name, ChangeKind::ListRemoved, old.as_ref(), None);
+        self.bytes_since_dump += self.change_size(name, old.as_ref());
+        self.dumpdb();
+        Ok(res)
+    }
+
+    /// Pop an item out of a list of the DB itself (`ns` is `None`) or of a namespace
+    pub(crate) fn lpop_in(&mut self, ns: Option<&str>, name: &str, pos: usize) -> Result<Option<Value>, error::Error> {
+        if self.list_in(ns, name).is_none_or(|list| pos >= list.len()) {
+            return Ok(None);
+        }
+        self.hooks.before(ns, name, ChangeKind::ListPop)?;
+        let (mut scope, hooks, watchers) = self.scope_mut(ns);
+        let res = scope.list_map.get_mut().get_mut(name).unwrap().remove(pos);
+        hooks.after(ns, name, ChangeKind::ListPop, Some(&res), None);
+        watchers.notify(ns, name, ChangeKind::ListPop, Some(&res), None);
+        self.bytes_since_dump += self.change_size(name, Some(&res));
+        self.dumpdb();
+        Ok(Some(res))
+    }
+
+    /// Pop the items at the given positions out of a list of the DB itself (`ns` is `None`) or of a namespace in a single
+    /// change, once the before-mutation hooks allow all of them, and return the number of items that were popped.
+    /// Positions that are out of the bounds of the list are ignored
+    #[cfg(feature = "resp")]
+    pub(crate) fn lpop_all_in(&mut self, ns: Option<&str>, name: &str, positions: &[usize]) -> Result<usize, error::Error> {
+        let len = self.list_in(ns, name).map_or(0, Vec::len);
+        let mut positions: Vec<usize> = positions.iter().copied().filter(|pos| *pos < len).collect();
+        // the items are popped from the tail, so the positions of the others don't change
+        positions.sort_unstable_by(|a, b| b.cmp(a));
+        positions.dedup();
+        if positions.is_empty() {
+            return Ok(0);
+        }
+        for _ in &positions {
+            self.hooks.before(ns, name, ChangeKind::ListPop)?;
+        }
+        let (mut scope, hooks, watchers) = self.scope_mut(ns);
+        let list = scope.list_map.get_mut().get_mut(name).unwrap();
+        let popped: Vec<Value> = positions.iter().map(|pos| list.remove(*pos)).collect();
+        for item in &popped {
+            hooks.after(ns, name, ChangeKind::ListPop, Some(item), None);
+            watchers.notify(ns, name, ChangeKind::ListPop, Some(item), None);
+        }
+        self.bytes_since_dump += popped.iter().map(|item| self.change_size(name, Some(item))).sum::<u64>();
+        self.dumpdb();
+        Ok(popped.len())
+    }
+
+    /// Remove the first occurrence of an item from a list of the DB itself (`ns` is `None`) or of a namespace
+    pub(crate) fn lrem_value_in(&mut self, ns: Option<&str>, name: &str, value: Value) -> Result<bool, error::Error> {
+        if self.lposition(ns, name, &value).is_none() {
+            return Ok(false);
+        }
+        let value = self.hooks.before_value(ns, name, ChangeKind::ListRemoveValue, value)?;
+        // the hooks may have changed the item to remove
+        let pos = match self.lposition(ns, name, &value) {
+            Some(pos) => pos,
+            None => return Ok(false),
+        };
+        let (mut scope, hooks, watchers) = self.scope_mut(ns);
+        let old = scope.list_map.get_mut().get_mut(name).unwrap().remove(pos);
+        hooks.after(ns, name, ChangeKind::ListRemoveValue, Some(&old), None);
+        watchers.notify(ns, name, ChangeKind::ListRemoveValue, Some(&old), None);
+        self.bytes_since_dump += self.change_size(name, Some(&old));
+        self.dumpdb();
+        Ok(true)
+    }
+
+    /// The position of the first occurrence of an item in a list, if the list exists and contains it
+    fn lposition(&self, ns: Option<&str>, name: &str, item: &Value) -> Option<usize> {
+        self.list_in(ns, name)?.iter().position(|x| x == item)
+    }
+
+    /// Subscribe to changes of keys and lists.
+    ///
+    /// The returned [Subscription](watch/struct.Subscription.html) receives a [ChangeEvent](watch/struct.ChangeEvent.html)
+    /// for every change made by [set()](#method.set), [rem()](#method.rem), [lcreate()](#method.lcreate), [ladd()](#method.ladd),
+    /// [lextend()](#method.lextend), [lpop()](#method.lpop), [lrem_value()](#method.lrem_value) and [lrem_list()](#method.lrem_list)
+    /// to a key or list whose name matches `pattern`. The pattern is a glob in which `*` matches any sequence of characters
+    /// and `?` matches a single character. Calls that don't change anything, such as removing a key that doesn't exist,
+    /// aren't reported. The subscription buffers up to [watch::DEFAULT_CAPACITY](watch/constant.DEFAULT_CAPACITY.html) events,
+    /// please see the [watch module](watch/index.html) for what happens when the buffer fills up. Dropping the subscription
+    /// unsubscribes it. Changes inside namespaces aren't reported, they're received by subscriptions made by
+    /// [Namespace::subscribe()](struct.Namespace.html#method.subscribe).
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - a glob of the keys and list names to receive events for
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let config_changes = db.subscribe("config.*");
+    ///
+    /// db.set("config.theme", &String::from("dark")).unwrap();
+    ///
+    /// let event = config_changes.try_recv().unwrap();
+    /// assert_eq!(event.key, "config.theme");
+    /// assert_eq!(event.kind, ChangeKind::Set);
+    /// ```
+    ///
+    pub fn subscribe(&mut self, pattern: &str) -> Subscription {
+        self.watchers.subscribe(None, pattern, watch::DEFAULT_CAPACITY)
+    }
+
+    /// Subscribe to changes of keys and lists, buffering up to `capacity` events.
+    ///
+    /// This method is the same as [subscribe()](#method.subscribe) but allows choosing the size of the buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - a glob of the keys and list names to receive events for
+    /// * `capacity` - the maximal number of events the subscription buffers (at least 1)
+    ///
+    pub fn subscribe_with_capacity(&mut self, pattern: &str, capacity: usize) -> Subscription {
+        self.watchers.subscribe(None, pattern, capacity)
+    }
+
+    /// Register a hook that runs before every mutation of the DB.
+    ///
+    /// The hook receives a [Mutation](hooks/struct.Mutation.html) before [set()](#method.set), [rem()](#method.rem),
+    /// [lcreate()](#method.lcreate), [ladd()](#method.ladd), [lextend()](#method.lextend), [lpop()](#method.lpop),
+    /// [lrem_list()](#method.lrem_list) or [lrem_value()](#method.lrem_value) apply it. The hook may change the value
+    /// being set or added, in which case the changed value is validated and stored. Returning an error vetoes the mutation:
+    /// [set()](#method.set), [ladd()](#method.ladd) and [lextend()](#method.lextend) then return an
+    /// [Error::Vetoed](error/enum.Error.html#variant.Vetoed) with the error message, and the other methods do nothing
+    /// and return the same value they return when there's nothing to change (for example `false` for [rem()](#method.rem)).
+    /// Their `try_` variants, such as [try_rem()](#method.try_rem) and [try_lpop()](#method.try_lpop), return the
+    /// [Error::Vetoed](error/enum.Error.html#variant.Vetoed) instead.
+    /// Calls that have nothing to change, such as removing a key or popping from a list that doesn't exist, don't run the hooks.
+    /// Changes inside namespaces (please see [namespace()](#method.namespace)) run the hooks as well.
+    /// Please see the [hooks module](hooks/index.html) for more details.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - a function that receives the mutation and returns an error message to veto it
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// // stamp every object that is set with the time of the change
+    /// db.add_before_hook(|mutation| {
+    ///     if let Some(Value::Object(fields)) = mutation.value_mut() {
+    ///         fields.insert(String::from("updated_at"), json!(now()));
+    ///     }
+    ///     Ok(())
+    /// });
+    ///
+    /// // protect keys from being removed
+    /// db.add_before_hook(|mutation| match mutation.kind() {
+    ///     ChangeKind::Removed if mutation.key().starts_with("config.") => Err(String::from("config keys can't be removed")),
+    ///     _ => Ok(()),
+    /// });
+    /// ```
+    ///
+    pub fn add_before_hook<F>(&mut self, hook: F) -> HookId
+        where
+            F: FnMut(&mut Mutation) -> Result<(), String> + Send + 'static
+    {
+        self.hooks.add_before(Box::new(hook))
+    }
+
+    /// Register a hook that runs after every change of the DB.
+    ///
+    /// The hook receives a [ChangeEvent](watch/struct.ChangeEvent.html) for every change that was applied by the methods
+    /// listed in [add_before_hook()](#method.add_before_hook), the same events a [subscription](#method.subscribe) receives.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - a function that receives the change
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// db.add_after_hook(|event| println!("{:?} {}", event.kind, event.key));
+    /// ```
+    ///
+    pub fn add_after_hook<F>(&mut self, hook: F) -> HookId
+        where
+            F: FnMut(&ChangeEvent) + Send + 'static
+    {
+        self.hooks.add_after(Box::new(hook))
+    }
 
-            None => false,
+    /// Remove a hook that was registered by [add_before_hook()](#method.add_before_hook) or
+    /// [add_after_hook()](#method.add_after_hook).
+    ///
+    /// This methods returns `true` if the hook was found or false otherwise
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the identifier that was returned when the hook was registered
+    ///
+    pub fn remove_hook(&mut self, id: HookId) -> bool {
+        self.hooks.remove(id)
+    }
+
+    /// Get a view of a namespace.
+    ///
+    /// Namespaces allow several users of the same DB file to keep their keys and lists separately instead of
+    /// prefixing keys by convention. The returned [Namespace](struct.Namespace.html) provides the key-value
+    /// and list APIs (`set`, `get`, `rem`, `get_all`, `total_keys`, `lcreate`, `ladd` and so on), all scoped
+    /// to the namespace. If the namespace doesn't exist it is created upon the first change made through the view,
+    /// so reading from a namespace that doesn't exist doesn't change the DB.
+    /// Keys in namespaces aren't returned by [get_all()](#method.get_all) or counted by
+    /// [total_keys()](#method.total_keys) of the DB, and each namespace is stored as a separate section of the DB file.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the namespace name
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut billing = db.namespace("billing");
+    /// billing.set("key1", &100).unwrap();
+    ///
+    /// // the key exists only in the namespace
+    /// assert!(!db.exists("key1"));
+    /// assert_eq!(db.namespace("billing").get::<i32>("key1").unwrap(), 100);
+    /// ```
+    ///
+    pub fn namespace(&mut self, name: &str) -> Namespace<'_> {
+        Namespace::new(self, name)
+    }
+
+    /// Remove a namespace with all of its keys and lists.
+    ///
+    /// This methods returns `true` if the namespace was found in the DB or false if it wasn't found
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the namespace name
+    ///
+    pub fn drop_namespace(&mut self, name: &str) -> bool {
+        let res = self.namespaces.contains_key(name);
+        if res {
+            Arc::make_mut(&mut self.namespaces).remove(name);
+            self.dumpdb();
         }
+        res
+    }
+
+    /// Get a vector of the names of all namespaces in the DB.
+    ///
+    pub fn list_namespaces(&self) -> Vec<String> {
+        self.namespaces.keys().cloned().collect()
+    }
+
+    /// Get a typed handle to a list.
+    ///
+    /// The returned [TypedList](struct.TypedList.html) provides `get`, `push`, `extend`, `remove` and `iter`
+    /// without the need to give the item type on every call. If the list doesn't exist it is created.
+    /// If the list exists, all of its items are checked to be of type `T`. An
+    /// [Error::TypeMismatch](error/enum.Error.html#variant.TypeMismatch) is returned if one of them isn't,
+    /// or if the name holds a value and not a list.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut shapes = db.list::<Rectangle>("shapes").unwrap();
+    ///
+    /// shapes.push(&Rectangle { width: 4, length: 10 }).unwrap();
+    /// shapes.extend(&vec![Rectangle { width: 1, length: 1 }, Rectangle { width: 2, length: 3 }]).unwrap();
+    ///
+    /// // read the first item
+    /// let rect = shapes.get(0).unwrap();
+    ///
+    /// // iterate over all items
+    /// for rect in shapes.iter() {
+    ///     println!("{}", rect.width * rect.length);
+    /// }
+    /// ```
+    ///
+    pub fn list<T>(&mut self, name: &str) -> Result<TypedList<'_, T>, error::Error>
+        where
+            T: Serialize + DeserializeOwned
+    {
+        TypedList::new(self, name)
+    }
+
+    /// Get a typed handle to a bucket.
+    ///
+    /// A bucket is a map of string keys to values of type `T`, stored as a single value under `name`.
+    /// The returned [TypedBucket](struct.TypedBucket.html) provides `get`, `set`, `remove` and `iter`
+    /// without the need to give the value type on every call. If the bucket doesn't exist it is created.
+    /// If it exists, all of its values are checked to be of type `T`. An
+    /// [Error::TypeMismatch](error/enum.Error.html#variant.TypeMismatch) is returned if one of them isn't,
+    /// or if the name holds a list or a value which isn't a bucket.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the bucket key
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut users = db.bucket::<User>("users").unwrap();
+    ///
+    /// users.set("john", &User { name: String::from("John"), age: 30 }).unwrap();
+    ///
+    /// let john = users.get("john").unwrap();
+    /// assert!(users.remove("john").is_some());
+    /// ```
+    ///
+    pub fn bucket<T>(&mut self, name: &str) -> Result<TypedBucket<'_, T>, error::Error>
+        where
+            T: Serialize + DeserializeOwned
+    {
+        TypedBucket::new(self, name)
     }
 }
 
@@ -731,5 +2746,6 @@ impl Drop for PickleDb {
         } else {
             self.dump();
         }
+        self.watchers.close();
     }
 }
\ No newline at end of file
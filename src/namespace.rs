@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::PickleDb;
+use crate::error::Error;
+use crate::index::Index;
+use crate::query::JsonPath;
+use crate::watch::{self, Subscription};
+
+/// The data of a single namespace, stored as a separate section of the DB file
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub(crate) struct NamespaceData {
+    pub(crate) map: HashMap<String, Value>,
+    pub(crate) list_map: HashMap<String, Vec<Value>>,
+    #[serde(default)]
+    pub(crate) indexes: HashMap<String, Index>,
+}
+
+/// Data of the DB itself, which is shared with the snapshots of the background writer and copied upon write,
+/// or data of a namespace
+pub(crate) enum DataMut<'a, T: Clone> {
+    Shared(&'a mut Arc<T>),
+    Owned(&'a mut T),
+}
+
+impl<T: Clone> DataMut<'_, T> {
+    pub(crate) fn get(&self) -> &T {
+        match self {
+            DataMut::Shared(data) => data,
+            DataMut::Owned(data) => data,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        match self {
+            DataMut::Shared(data) => Arc::make_mut(data),
+            DataMut::Owned(data) => data,
+        }
+    }
+}
+
+/// The key-value pairs, lists and indexes a change applies to: those of the DB itself or those of one of its namespaces
+pub(crate) struct ScopeMut<'a> {
+    pub(crate) map: DataMut<'a, HashMap<String, Value>>,
+    pub(crate) list_map: DataMut<'a, HashMap<String, Vec<Value>>>,
+    pub(crate) indexes: &'a mut HashMap<String, Index>,
+}
+
+/// A view of a namespace inside a [PickleDb](struct.PickleDb.html).
+///
+/// A `Namespace` is returned by [PickleDb::namespace()](struct.PickleDb.html#method.namespace).
+/// It provides the same key-value and list APIs as `PickleDb` itself, but all keys are scoped to the namespace:
+/// the same key can exist in several namespaces and in the DB itself without any conflict between them.
+/// Changes made through the view are dumped according to the dump policy of the DB, and values and list items are
+/// checked against the schemas of the DB (please see [PickleDb::set_key_schema()](struct.PickleDb.html#method.set_key_schema)
+/// and [PickleDb::set_list_schema()](struct.PickleDb.html#method.set_list_schema)) just like those of the DB itself.
+/// Changes run the hooks of the DB (please see [PickleDb::add_before_hook()](struct.PickleDb.html#method.add_before_hook))
+/// and are reported to the subscriptions of the namespace, please see [subscribe()](#method.subscribe).
+/// The namespace is created in the DB upon the first change made through the view.
+pub struct Namespace<'a> {
+    db: &'a mut PickleDb,
+    name: String,
+}
+
+impl<'a> Namespace<'a> {
+    pub(crate) fn new(db: &'a mut PickleDb, name: &str) -> Namespace<'a> {
+        Namespace { db, name: String::from(name) }
+    }
+
+    /// The data of the namespace, or `None` if nothing was written to it yet
+    fn data(&self) -> Option<&NamespaceData> {
+        self.db.namespaces.get(&self.name)
+    }
+
+    /// The data of the namespace, which is created if it doesn't exist yet
+    fn data_mut(&mut self) -> &mut NamespaceData {
+        Arc::make_mut(&mut self.db.namespaces).entry(self.name.clone()).or_default()
+    }
+
+    /// Get the name of the namespace.
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Set a key-value pair in the namespace.
+    ///
+    /// Please see [PickleDb::set()](struct.PickleDb.html#method.set) for more details. The value is checked
+    /// against the unique indexes of the namespace and not against the ones of the DB.
+    ///
+    pub fn set<V>(&mut self, key: &str, value: &V) -> Result<(), Error>
+        where
+            V: Serialize
+    {
+        self.db.set_in(Some(&self.name), key, serde_json::to_value(value)?)
+    }
+
+    /// Get a value of a key in the namespace.
+    ///
+    /// Please see [PickleDb::get()](struct.PickleDb.html#method.get) for more details.
+    ///
+    pub fn get<V>(&self, key: &str) -> Option<V>
+        where
+            V: DeserializeOwned
+    {
+        match self.data().and_then(|data| data.map.get(key)) {
+            Some(val) => V::deserialize(val).ok(),
+            None => None,
+        }
+    }
+
+    /// Check if a key or a list exists in the namespace.
+    ///
+    pub fn exists(&self, key: &str) -> bool {
+        self.data().is_some_and(|data| data.map.contains_key(key) || data.list_map.contains_key(key))
+    }
+
+    /// Get a vector of all the keys in the namespace.
+    ///
+    pub fn get_all(&self) -> Vec<String> {
+        match self.data() {
+            Some(data) => data.map.keys().chain(data.list_map.keys()).cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Get the total number of keys in the namespace.
+    ///
+    pub fn total_keys(&self) -> usize {
+        self.data().map_or(0, |data| data.map.len() + data.list_map.len())
+    }
+
+    /// Remove a key-value pair or a list from the namespace.
+    ///
+    /// This methods returns `true` if the key was found in the namespace or false if it wasn't found
+    ///
+    pub fn rem(&mut self, key: &str) -> bool {
+        self.try_rem(key).unwrap_or(false)
+    }
+
+    /// Remove a key-value pair or a list from the namespace, reporting a veto of a before-mutation hook.
+    ///
+    /// Please see [PickleDb::try_rem()](struct.PickleDb.html#method.try_rem) for more details.
+    ///
+    pub fn try_rem(&mut self, key: &str) -> Result<bool, Error> {
+        self.db.rem_in(Some(&self.name), key)
+    }
+
+    /// Create a secondary index on a field of the values in the namespace.
+    ///
+    /// Please see [PickleDb::create_index()](struct.PickleDb.html#method.create_index) for more details.
+    ///
+    pub fn create_index(&mut self, name: &str, path: &str) -> Result<(), Error> {
+        let index = self.build_index(name, path, "", false)?;
+        self.data_mut().indexes.insert(String::from(name), index);
+        self.db.dumpdb();
+        Ok(())
+    }
+
+    /// Create a unique secondary index on a field of the values in the namespace.
+    ///
+    /// Please see [PickleDb::create_unique_index()](struct.PickleDb.html#method.create_unique_index) for more details.
+    /// Pass an empty `key_prefix` to make the field unique across all values of the namespace.
+    ///
+    pub fn create_unique_index(&mut self, name: &str, path: &str, key_prefix: &str) -> Result<(), Error> {
+        let index = self.build_index(name, path, key_prefix, true)?;
+        self.data_mut().indexes.insert(String::from(name), index);
+        self.db.dumpdb();
+        Ok(())
+    }
+
+    /// Build an index of the values of the namespace, without creating the namespace if the index can't be built
+    fn build_index(&self, name: &str, path: &str, key_prefix: &str, unique: bool) -> Result<Index, Error> {
+        let path = JsonPath::parse(path)?;
+        match self.data() {
+            Some(data) => Index::build(name, path, key_prefix, unique, &data.map),
+            None => Index::build(name, path, key_prefix, unique, &HashMap::new()),
+        }
+    }
+
+    /// Remove a secondary index of the namespace.
+    ///
+    /// This methods returns `true` if the index was found or false if it wasn't found
+    ///
+    pub fn drop_index(&mut self, name: &str) -> bool {
+        if !self.data().is_some_and(|data| data.indexes.contains_key(name)) {
+            return false;
+        }
+        self.data_mut().indexes.remove(name);
+        self.db.dumpdb();
+        true
+    }
+
+    /// Find the keys of the values in the namespace in which the indexed field equals a certain value.
+    ///
+    /// Please see [PickleDb::find_by_index()](struct.PickleDb.html#method.find_by_index) for more details.
+    ///
+    pub fn find_by_index<V>(&self, name: &str, value: &V) -> Vec<String>
+        where
+            V: Serialize
+    {
+        match (self.data().and_then(|data| data.indexes.get(name)), serde_json::to_value(value)) {
+            (Some(index), Ok(value)) => index.find(&value),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Create a new list in the namespace.
+    ///
+    /// Please see [PickleDb::lcreate()](struct.PickleDb.html#method.lcreate) for more details.
+    ///
+    pub fn lcreate(&mut self, name: &str) {
+        let _ = self.try_lcreate(name);
+    }
+
+    /// Create a new list in the namespace, reporting a veto of a before-mutation hook.
+    ///
+    /// Please see [PickleDb::try_lcreate()](struct.PickleDb.html#method.try_lcreate) for more details. If the namespace
+    /// doesn't exist and the creation is vetoed, the namespace isn't created.
+    ///
+    pub fn try_lcreate(&mut self, name: &str) -> Result<(), Error> {
+        self.db.lcreate_in(Some(&self.name), name)
+    }
+
+    /// Check if a list exists in the namespace.
+    ///
+    pub fn lexists(&self, name: &str) -> bool {
+        self.data().is_some_and(|data| data.list_map.contains_key(name))
+    }
+
+    /// Add a single item to an existing list in the namespace.
+    ///
+    /// Please see [PickleDb::ladd()](struct.PickleDb.html#method.ladd) for more details.
+    ///
+    pub fn ladd<V>(&mut self, name: &str, value: &V) -> Result<bool, Error>
+        where
+            V: Serialize
+    {
+        self.lextend(name, &[value])
+    }
+
+    /// Add multiple items to an existing list in the namespace.
+    ///
+    /// Please see [PickleDb::lextend()](struct.PickleDb.html#method.lextend) for more details.
+    ///
+    pub fn lextend<V>(&mut self, name: &str, seq: &[V]) -> Result<bool, Error>
+        where
+            V: Serialize
+    {
+        self.db.lextend_in(Some(&self.name), name, seq)
+    }
+
+    /// Get an item of a list in the namespace in a certain position.
+    ///
+    /// Please see [PickleDb::lget()](struct.PickleDb.html#method.lget) for more details.
+    ///
+    pub fn lget<V>(&self, name: &str, pos: usize) -> Option<V>
+        where
+            V: DeserializeOwned
+    {
+        match self.data().and_then(|data| data.list_map.get(name)).and_then(|list| list.get(pos)) {
+            Some(val) => V::deserialize(val).ok(),
+            None => None,
+        }
+    }
+
+    /// Get the length of a list in the namespace.
+    ///
+    pub fn llen(&self, name: &str) -> usize {
+        self.data().and_then(|data| data.list_map.get(name)).map_or(0, |list| list.len())
+    }
+
+    /// Remove a list from the namespace and return the number of items it had.
+    ///
+    pub fn lrem_list(&mut self, name: &str) -> usize {
+        self.try_lrem_list(name).unwrap_or(0)
+    }
+
+    /// Remove a list from the namespace, reporting a veto of a before-mutation hook.
+    ///
+    /// Please see [PickleDb::try_lrem_list()](struct.PickleDb.html#method.try_lrem_list) for more details.
+    ///
+    pub fn try_lrem_list(&mut self, name: &str) -> Result<usize, Error> {
+        self.db.lrem_list_in(Some(&self.name), name)
+    }
+
+    /// Pop an item out of a list in the namespace.
+    ///
+    /// Please see [PickleDb::lpop()](struct.PickleDb.html#method.lpop) for more details.
+    ///
+    pub fn lpop<V>(&mut self, name: &str, pos: usize) -> Option<V>
+        where
+            V: DeserializeOwned
+    {
+        self.try_lpop(name, pos).unwrap_or(None)
+    }
+
+    /// Pop an item out of a list in the namespace, reporting a veto of a before-mutation hook.
+    ///
+    /// Please see [PickleDb::try_lpop()](struct.PickleDb.html#method.try_lpop) for more details.
+    ///
+    pub fn try_lpop<V>(&mut self, name: &str, pos: usize) -> Result<Option<V>, Error>
+        where
+            V: DeserializeOwned
+    {
+        match self.db.lpop_in(Some(&self.name), name, pos)? {
+            Some(res) => Ok(V::deserialize(res).ok()),
+            None => Ok(None),
+        }
+    }
+
+    /// Remove the first occurrence of an item from a list in the namespace.
+    ///
+    /// Please see [PickleDb::lrem_value()](struct.PickleDb.html#method.lrem_value) for more details.
+    ///
+    pub fn lrem_value<V>(&mut self, name: &str, value: &V) -> bool
+        where
+            V: Serialize
+    {
+        self.try_lrem_value(name, value).unwrap_or(false)
+    }
+
+    /// Remove the first occurrence of an item from a list in the namespace, reporting a veto of a before-mutation hook.
+    ///
+    /// Please see [PickleDb::try_lrem_value()](struct.PickleDb.html#method.try_lrem_value) for more details.
+    ///
+    pub fn try_lrem_value<V>(&mut self, name: &str, value: &V) -> Result<bool, Error>
+        where
+            V: Serialize
+    {
+        self.db.lrem_value_in(Some(&self.name), name, serde_json::to_value(value)?)
+    }
+
+    /// Subscribe to changes of keys and lists in the namespace.
+    ///
+    /// Please see [PickleDb::subscribe()](struct.PickleDb.html#method.subscribe) for more details. The subscription
+    /// receives only changes inside this namespace, and the [namespace](watch/struct.ChangeEvent.html#structfield.namespace)
+    /// of its events is the name of the namespace.
+    ///
+    pub fn subscribe(&mut self, pattern: &str) -> Subscription {
+        self.subscribe_with_capacity(pattern, watch::DEFAULT_CAPACITY)
+    }
+
+    /// Subscribe to changes of keys and lists in the namespace, buffering up to `capacity` events.
+    ///
+    /// Please see [PickleDb::subscribe_with_capacity()](struct.PickleDb.html#method.subscribe_with_capacity) for more details.
+    ///
+    pub fn subscribe_with_capacity(&mut self, pattern: &str, capacity: usize) -> Subscription {
+        self.db.watchers.subscribe(Some(&self.name), pattern, capacity)
+    }
+}
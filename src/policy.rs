@@ -0,0 +1,122 @@
+//! Deciding when changes are dumped to the DB file.
+//!
+//! After every change of the DB, its dump policy is asked whether to dump the DB now. The decision is based on
+//! [DumpStats](struct.DumpStats.html): the number of changes, the number of bytes they changed and the time that passed
+//! since the last dump. [PickleDbDumpPolicy](../enum.PickleDbDumpPolicy.html) provides the common policies and allows
+//! combining them with [PickleDbDumpPolicy::Any](../enum.PickleDbDumpPolicy.html#variant.Any), and other policies can be
+//! written by implementing [DumpPolicy](trait.DumpPolicy.html) and using them with
+//! [PickleDbDumpPolicy::Custom](../enum.PickleDbDumpPolicy.html#variant.Custom).
+
+use std::io;
+use std::time::Duration;
+use serde_json::Value;
+use crate::PickleDbDumpPolicy;
+
+/// The changes of a DB since it was last dumped (or since it was created or loaded, if it wasn't dumped yet)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DumpStats {
+    /// The number of changes
+    pub writes: u64,
+    /// An estimate of the number of bytes the changes changed in the DB file: the size of the keys and the JSON of the values
+    /// and list items that were set, added or removed. Serializing the values has a cost, so the bytes are counted only
+    /// while the dump policy uses them (please see [DumpPolicy::uses_bytes()](trait.DumpPolicy.html#method.uses_bytes)),
+    /// otherwise this is 0
+    pub bytes: u64,
+    /// The time that passed since the last dump
+    pub since_last_dump: Duration,
+}
+
+/// A policy that decides when changes are dumped to the DB file.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use pickledb::policy::{DumpPolicy, DumpStats};
+///
+/// // dump every 10 changes, but only at night
+/// struct NightlyDump;
+///
+/// impl DumpPolicy for NightlyDump {
+///     fn should_dump(&mut self, stats: &DumpStats) -> bool {
+///         stats.writes >= 10 && is_night()
+///     }
+/// }
+///
+/// let mut db = PickleDb::new("example.db", PickleDbDumpPolicy::Custom(Box::new(NightlyDump)));
+/// ```
+///
+pub trait DumpPolicy: Send {
+    /// Decide whether to dump the DB, after it was changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - the changes since the last dump, including the change that was just made
+    ///
+    fn should_dump(&mut self, stats: &DumpStats) -> bool;
+
+    /// Whether the policy uses [DumpStats::bytes](struct.DumpStats.html#structfield.bytes).
+    ///
+    /// Counting the bytes requires serializing every changed value, so they're counted only if this method
+    /// returns `true`. The default implementation returns `true`, policies that don't use the bytes can
+    /// return `false` to save this cost.
+    ///
+    fn uses_bytes(&self) -> bool {
+        true
+    }
+}
+
+impl DumpPolicy for PickleDbDumpPolicy {
+    fn should_dump(&mut self, stats: &DumpStats) -> bool {
+        match self {
+            PickleDbDumpPolicy::NeverDump | PickleDbDumpPolicy::DumpUponRequest => false,
+            PickleDbDumpPolicy::AutoDump => true,
+            PickleDbDumpPolicy::PeriodicDump(duration) => stats.since_last_dump > *duration,
+            PickleDbDumpPolicy::DumpEveryNWrites(writes) => stats.writes >= *writes,
+            PickleDbDumpPolicy::DumpAfterBytesChanged(bytes) => stats.bytes >= *bytes,
+            PickleDbDumpPolicy::Any(policies) => {
+                // every policy sees every change, even after one of them decided to dump
+                let mut dump = false;
+                for policy in policies.iter_mut() {
+                    dump |= policy.should_dump(stats);
+                }
+                dump
+            },
+            PickleDbDumpPolicy::Custom(policy) => policy.should_dump(stats),
+        }
+    }
+
+    fn uses_bytes(&self) -> bool {
+        match self {
+            PickleDbDumpPolicy::DumpAfterBytesChanged(_) => true,
+            PickleDbDumpPolicy::Any(policies) => policies.iter().any(|policy| policy.uses_bytes()),
+            PickleDbDumpPolicy::Custom(policy) => policy.uses_bytes(),
+            _ => false,
+        }
+    }
+}
+
+struct ByteCounter(u64);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The number of bytes a change of `key` to or from `value` is counted as in `DumpStats::bytes`, or 0 if the bytes
+/// aren't `counted`, in which case the value isn't serialized
+pub(crate) fn change_size(counted: bool, key: &str, value: Option<&Value>) -> u64 {
+    if !counted {
+        return 0;
+    }
+    let mut counter = ByteCounter(key.len() as u64);
+    if let Some(value) = value {
+        let _ignore = serde_json::to_writer(&mut counter, value);
+    }
+    counter.0
+}
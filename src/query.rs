@@ -0,0 +1,306 @@
+//! JSON paths and queries over values stored in a PickleDB.
+//!
+//! A JSON path selects a part of a value. It always starts with `$`, which is the value itself, followed by
+//! any number of the following segments:
+//! * `.field` or `['field']` - a field of an object, in which a backslash escapes the quote, for example `['it\'s']`
+//! * `[n]` - the n-th item of an array
+//!
+//! For example `$.address.city` selects the `city` field of the `address` field of a value, and `$.tags[0]` selects
+//! the first item of its `tags` array.
+//!
+//! A query is a JSON path, optionally followed by a comparison operator (`==`, `!=`, `>`, `>=`, `<`, `<=`) and a JSON
+//! literal, for example `$.age > 30` or `$.name == "John"`. A query without an operator matches every value in which
+//! the path exists. Numbers are compared by their numeric value and strings lexicographically. Ordering comparisons
+//! between values of other types never match.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use serde::Serialize;
+use serde_json::Value;
+use crate::error::Error;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+}
+
+/// A parsed JSON path which selects a part of a value. Please see the [module documentation](index.html) for the syntax
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+fn invalid_query(query: &str, reason: &str) -> Error {
+    Error::InvalidQuery { query: String::from(query), reason: String::from(reason) }
+}
+
+impl JsonPath {
+    /// Parse a JSON path, for example `$.address.city`
+    ///
+    pub fn parse(path: &str) -> Result<JsonPath, Error> {
+        let (json_path, rest) = JsonPath::parse_prefix(path)?;
+        if !rest.trim().is_empty() {
+            return Err(invalid_query(path, &format!("unexpected '{}' after the path", rest.trim())));
+        }
+        Ok(json_path)
+    }
+
+    /// Parse a JSON path at the beginning of `expr` and return it with the rest of `expr`
+    fn parse_prefix(expr: &str) -> Result<(JsonPath, &str), Error> {
+        let trimmed = expr.trim_start();
+        if !trimmed.starts_with('$') {
+            return Err(invalid_query(expr, "a path must start with '$'"));
+        }
+
+        let mut segments = Vec::new();
+        let mut rest = &trimmed[1..];
+        loop {
+            if let Some(after_dot) = rest.strip_prefix('.') {
+                let end = after_dot
+                    .find(|c: char| c == '.' || c == '[' || c.is_whitespace() || "<>=!".contains(c))
+                    .unwrap_or(after_dot.len());
+                if end == 0 {
+                    return Err(invalid_query(expr, "missing field name after '.'"));
+                }
+                segments.push(Segment::Field(String::from(&after_dot[..end])));
+                rest = &after_dot[end..];
+            } else if let Some(after_bracket) = rest.strip_prefix('[') {
+                let inner = after_bracket.trim_start();
+                let (segment, after_segment) = match inner.chars().next() {
+                    Some(quote) if quote == '\'' || quote == '"' => {
+                        let (field, after_field) = JsonPath::parse_quoted(expr, &inner[1..], quote)?;
+                        (Segment::Field(field), after_field)
+                    },
+                    _ => {
+                        let end = match inner.find(']') {
+                            Some(end) => end,
+                            None => return Err(invalid_query(expr, "missing ']'")),
+                        };
+                        match inner[..end].trim().parse::<usize>() {
+                            Ok(index) => (Segment::Index(index), &inner[end..]),
+                            Err(_) => return Err(invalid_query(expr, &format!("invalid index '{}'", inner[..end].trim()))),
+                        }
+                    },
+                };
+                rest = match after_segment.trim_start().strip_prefix(']') {
+                    Some(rest) => rest,
+                    None => return Err(invalid_query(expr, "missing ']'")),
+                };
+                segments.push(segment);
+            } else {
+                return Ok((JsonPath { segments }, rest));
+            }
+        }
+    }
+
+    /// Parse the rest of a quoted field name, in which a backslash escapes the character after it, and return the
+    /// field name with what comes after the closing quote
+    fn parse_quoted<'e>(expr: &str, quoted: &'e str, quote: char) -> Result<(String, &'e str), Error> {
+        let mut field = String::new();
+        let mut chars = quoted.char_indices();
+        while let Some((pos, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, escaped)) => field.push(escaped),
+                    None => break,
+                },
+                c if c == quote => return Ok((field, &quoted[pos + 1..])),
+                c => field.push(c),
+            }
+        }
+        Err(invalid_query(expr, &format!("missing closing {}", quote)))
+    }
+
+    /// Get the part of `value` this path points to, or `None` if it doesn't exist in the value
+    ///
+    pub fn select<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.segments.iter().try_fold(value, |current, segment| match segment {
+            Segment::Field(field) => current.get(field.as_str()),
+            Segment::Index(index) => current.get(*index),
+        })
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = Error;
+
+    fn from_str(path: &str) -> Result<JsonPath, Error> {
+        JsonPath::parse(path)
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "$")?;
+        for segment in &self.segments {
+            match segment {
+                Segment::Field(field) => write!(f, "['{}']", field.replace('\\', "\\\\").replace('\'', "\\'"))?,
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+// longer operators first so that ">=" isn't parsed as ">"
+const OPERATORS: [(&str, Operator); 6] = [
+    ("==", Operator::Eq),
+    ("!=", Operator::Ne),
+    (">=", Operator::Ge),
+    ("<=", Operator::Le),
+    (">", Operator::Gt),
+    ("<", Operator::Lt),
+];
+
+/// Compare two JSON values. Numbers are compared by their numeric value and strings lexicographically,
+/// values of other types can only be equal or not equal
+pub(crate) fn compare_values(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => {
+            if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+                Some(a.cmp(&b))
+            } else if let (Some(a), Some(b)) = (a.as_u64(), b.as_u64()) {
+                Some(a.cmp(&b))
+            } else {
+                a.as_f64()?.partial_cmp(&b.as_f64()?)
+            }
+        },
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ if a == b => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+/// A predicate on values: a JSON path and an optional comparison. Please see the [module documentation](index.html)
+/// for the syntax.
+///
+/// A query can be parsed from a string:
+///
+/// ```rust,ignore
+/// let query = Query::parse("$.age > 30").unwrap();
+/// ```
+///
+/// or built:
+///
+/// ```rust,ignore
+/// let query = Query::path("$.age").unwrap().gt(&30);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    path: JsonPath,
+    condition: Option<(Operator, Value)>,
+}
+
+impl Query {
+    /// Parse a query, for example `$.age > 30`
+    ///
+    pub fn parse(expr: &str) -> Result<Query, Error> {
+        let (path, rest) = JsonPath::parse_prefix(expr)?;
+        let rest = rest.trim();
+        if rest.is_empty() {
+            return Ok(Query { path, condition: None });
+        }
+
+        for (symbol, op) in OPERATORS.iter() {
+            if let Some(literal) = rest.strip_prefix(symbol) {
+                return match serde_json::from_str(literal.trim()) {
+                    Ok(literal) => Ok(Query { path, condition: Some((*op, literal)) }),
+                    Err(err) => Err(invalid_query(expr, &format!("invalid JSON literal: {}", err))),
+                };
+            }
+        }
+
+        Err(invalid_query(expr, &format!("unknown operator in '{}'", rest)))
+    }
+
+    /// Create a query that matches all values in which `path` exists.
+    /// Use the comparison methods to add a condition on the value the path points to
+    ///
+    pub fn path(path: &str) -> Result<Query, Error> {
+        Ok(Query { path: JsonPath::parse(path)?, condition: None })
+    }
+
+    fn with_condition<V: Serialize>(self, op: Operator, value: &V) -> Query {
+        Query { path: self.path, condition: Some((op, serde_json::to_value(value).unwrap())) }
+    }
+
+    /// Match values in which the path points to a value equal to `value`
+    ///
+    pub fn eq<V: Serialize>(self, value: &V) -> Query {
+        self.with_condition(Operator::Eq, value)
+    }
+
+    /// Match values in which the path points to a value not equal to `value`
+    ///
+    pub fn ne<V: Serialize>(self, value: &V) -> Query {
+        self.with_condition(Operator::Ne, value)
+    }
+
+    /// Match values in which the path points to a value greater than `value`
+    ///
+    pub fn gt<V: Serialize>(self, value: &V) -> Query {
+        self.with_condition(Operator::Gt, value)
+    }
+
+    /// Match values in which the path points to a value greater than or equal to `value`
+    ///
+    pub fn ge<V: Serialize>(self, value: &V) -> Query {
+        self.with_condition(Operator::Ge, value)
+    }
+
+    /// Match values in which the path points to a value less than `value`
+    ///
+    pub fn lt<V: Serialize>(self, value: &V) -> Query {
+        self.with_condition(Operator::Lt, value)
+    }
+
+    /// Match values in which the path points to a value less than or equal to `value`
+    ///
+    pub fn le<V: Serialize>(self, value: &V) -> Query {
+        self.with_condition(Operator::Le, value)
+    }
+
+    /// Check if a value matches the query
+    ///
+    pub fn matches(&self, value: &Value) -> bool {
+        let selected = match self.path.select(value) {
+            Some(selected) => selected,
+            None => return false,
+        };
+
+        let (op, literal) = match &self.condition {
+            Some(condition) => condition,
+            None => return true,
+        };
+
+        let ordering = compare_values(selected, literal);
+        match op {
+            Operator::Eq => ordering == Some(Ordering::Equal),
+            Operator::Ne => ordering != Some(Ordering::Equal),
+            Operator::Gt => ordering == Some(Ordering::Greater),
+            Operator::Ge => ordering == Some(Ordering::Greater) || ordering == Some(Ordering::Equal),
+            Operator::Lt => ordering == Some(Ordering::Less),
+            Operator::Le => ordering == Some(Ordering::Less) || ordering == Some(Ordering::Equal),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    fn from_str(expr: &str) -> Result<Query, Error> {
+        Query::parse(expr)
+    }
+}
@@ -0,0 +1,496 @@
+//! Recovering the data of a damaged DB file.
+//!
+//! When a DB file is truncated or partially corrupted [PickleDb::load()](../struct.PickleDb.html#method.load) fails,
+//! but most of its content can usually still be read. [PickleDb::load_lenient()](../struct.PickleDb.html#method.load_lenient)
+//! salvages every key, list and list item that can be parsed, and [PickleDb::repair()](../struct.PickleDb.html#method.repair)
+//! also writes the recovered data back to the file. Both describe what was recovered and what was dropped in a
+//! [RepairReport](struct.RepairReport.html).
+
+use std::collections::HashMap;
+use std::io;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use crate::error::Error;
+use crate::format::DbFile;
+use crate::namespace::NamespaceData;
+
+/// The outcome of [PickleDb::load_lenient()](../struct.PickleDb.html#method.load_lenient) and
+/// [PickleDb::repair()](../struct.PickleDb.html#method.repair)
+#[derive(Debug, Default)]
+pub struct RepairReport {
+    /// The error the DB file failed to load with, or `None` if the file is intact and nothing had to be recovered
+    pub error: Option<Error>,
+    /// The number of keys that were recovered, including the keys of namespaces
+    pub recovered_keys: usize,
+    /// The number of lists that were recovered, including the lists of namespaces
+    pub recovered_lists: usize,
+    /// A description of every part of the DB file that couldn't be recovered, for example a key whose value can't be
+    /// parsed or the end of a file that is truncated
+    pub dropped: Vec<String>,
+    /// The numbered backup the data was restored from, when nothing could be recovered from the DB file itself
+    pub restored_from: Option<String>,
+}
+
+impl RepairReport {
+    /// Check whether the DB file was intact, in which case nothing was recovered or dropped.
+    ///
+    pub fn is_intact(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Turn the error loading a DB file failed with back into the error it wraps, if it wraps one
+pub(crate) fn load_error(err: io::Error) -> Error {
+    let wrapped = err.get_ref().is_some_and(|inner| inner.is::<Error>() || inner.is::<serde_json::Error>());
+    if !wrapped {
+        return Error::Io(err);
+    }
+    let inner = err.into_inner().expect("the error wraps another one");
+    match inner.downcast::<Error>() {
+        Ok(err) => *err,
+        Err(inner) => match inner.downcast::<serde_json::Error>() {
+            Ok(err) => Error::Serialization(*err),
+            Err(inner) => Error::Io(io::Error::new(io::ErrorKind::InvalidData, inner)),
+        },
+    }
+}
+
+/// The fields of the DB file in the order they're written, which scanning resumes from after content that can't be read
+const FIELDS: [&str; 6] = ["map", "list_map", "namespaces", "indexes", "key_schemas", "list_schemas"];
+
+/// How values are stored: the current format stores them as JSON, the legacy format as strings that contain their JSON text
+#[derive(Clone, Copy, PartialEq)]
+enum Encoding {
+    Json,
+    Legacy,
+}
+
+fn decode(raw: &str, encoding: Encoding) -> Option<Value> {
+    match encoding {
+        Encoding::Json => serde_json::from_str(raw).ok(),
+        Encoding::Legacy => serde_json::from_str::<String>(raw).ok().and_then(|json| serde_json::from_str(&json).ok()),
+    }
+}
+
+/// Why scanning an object or an array stopped before its end
+enum Stop {
+    Truncated,
+    Unreadable,
+}
+
+/// A member of an object or an item of an array, with the raw JSON text of its value
+struct Member<'a> {
+    /// The key of an object member, empty for array items
+    key: String,
+    raw: &'a str,
+    /// Whether the value ends before the file does. A value that is cut off may still parse, for example a number
+    complete: bool,
+}
+
+#[derive(Default)]
+struct Members<'a> {
+    members: Vec<Member<'a>>,
+    unreadable_keys: usize,
+    stop: Option<Stop>,
+}
+
+/// Follows the structure of JSON text as far as possible, without parsing the values
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+fn string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut pos = start + 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b'"' => return Some(pos + 1),
+            _ => pos += 1,
+        }
+    }
+    None
+}
+
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Scanner<'a> {
+        Scanner { text, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skip the opening bracket of an object or an array, if the text starts with one
+    fn open(&mut self, bracket: u8) -> bool {
+        self.skip_whitespace();
+        if self.peek() != Some(bracket) {
+            return false;
+        }
+        self.pos += 1;
+        true
+    }
+
+    /// The end of the value that starts at the current position, or `None` if the text ends before it does
+    fn value_end(&self) -> Option<usize> {
+        let bytes = self.text.as_bytes();
+        if self.peek() == Some(b'"') {
+            return string_end(bytes, self.pos);
+        }
+        let mut depth = 0;
+        let mut pos = self.pos;
+        while pos < bytes.len() {
+            match bytes[pos] {
+                b'"' => pos = string_end(bytes, pos)? - 1,
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' | b',' if depth == 0 => return Some(pos),
+                b'}' | b']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(pos + 1);
+                    }
+                },
+                _ => (),
+            }
+            pos += 1;
+        }
+        None
+    }
+
+    /// Scan the members of an object (`keyed`) or the items of an array, from the current position until its closing bracket
+    fn members(&mut self, keyed: bool) -> Members<'a> {
+        let close = if keyed { b'}' } else { b']' };
+        let mut members = Members::default();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => {
+                    members.stop = Some(Stop::Truncated);
+                    return members;
+                },
+                Some(byte) if byte == close => {
+                    self.pos += 1;
+                    return members;
+                },
+                _ => (),
+            }
+
+            let mut key = None;
+            if keyed {
+                if self.peek() != Some(b'"') {
+                    members.stop = Some(Stop::Unreadable);
+                    return members;
+                }
+                let end = match self.value_end() {
+                    Some(end) => end,
+                    None => {
+                        members.stop = Some(Stop::Truncated);
+                        return members;
+                    },
+                };
+                key = serde_json::from_str::<String>(&self.text[self.pos..end]).ok();
+                self.pos = end;
+                self.skip_whitespace();
+                if self.peek() != Some(b':') {
+                    members.stop = Some(if self.peek().is_none() { Stop::Truncated } else { Stop::Unreadable });
+                    return members;
+                }
+                self.pos += 1;
+                self.skip_whitespace();
+            }
+
+            // a value that is cut off is still scanned, since the part of a cut off object or array may be recovered
+            let (end, complete) = match self.value_end() {
+                Some(end) => (end, true),
+                None => (self.text.len(), false),
+            };
+            let raw = &self.text[self.pos..end];
+            self.pos = end;
+            match key {
+                Some(key) => members.members.push(Member { key, raw, complete }),
+                None if keyed => members.unreadable_keys += 1,
+                None => members.members.push(Member { key: String::new(), raw, complete }),
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(byte) if byte == close => (),
+                None => {
+                    members.stop = Some(Stop::Truncated);
+                    return members;
+                },
+                Some(_) => {
+                    members.stop = Some(Stop::Unreadable);
+                    return members;
+                },
+            }
+        }
+    }
+
+    /// Move to the next field of the DB file after content that can't be read. Returns false if there's none
+    fn resume(&mut self) -> bool {
+        let mut from = self.pos;
+        loop {
+            let rest = &self.text[from..];
+            let next = FIELDS.iter()
+                .filter_map(|field| rest.find(&format!(",\"{}\":", field)).map(|offset| (offset, *field)))
+                .min();
+            match next {
+                Some((offset, field)) => {
+                    let start = from + offset + 1;
+                    if self.is_field(start, field) {
+                        self.pos = start;
+                        return true;
+                    }
+                    from = start;
+                },
+                None => return false,
+            }
+        }
+    }
+
+    /// Whether the member that starts at `start` is a field of the DB file rather than a key inside a value that has the
+    /// same name. The fields that follow it are checked up to the end of the file: the value of every field has to be an
+    /// object, and a closing brace before the end of the file or a key that isn't a later field show that the member is
+    /// nested in a value. Content that can't be read ends the check, since the file is damaged anyway
+    fn is_field(&self, start: usize, field: &str) -> bool {
+        let mut scanner = Scanner { text: self.text, pos: start };
+        let mut field = field;
+        loop {
+            scanner.pos += field.len() + 3;
+            if scanner.peek() != Some(b'{') {
+                return false;
+            }
+            scanner.pos = match scanner.value_end() {
+                Some(end) => end,
+                None => return true,
+            };
+            scanner.skip_whitespace();
+            let rest = &self.text[scanner.pos..];
+            match scanner.peek() {
+                Some(b'}') => return rest[1..].trim().is_empty(),
+                Some(b',') => {
+                    let next = FIELDS.iter()
+                        .skip_while(|name| **name != field)
+                        .skip(1)
+                        .find(|name| rest[1..].starts_with(&format!("\"{}\":", name)));
+                    match next {
+                        Some(next) => {
+                            scanner.pos += 1;
+                            field = next;
+                        },
+                        None => return rest[1..].starts_with("\"checksum\":") || !rest[1..].starts_with('"'),
+                    }
+                },
+                _ => return true,
+            }
+        }
+    }
+}
+
+/// Recovers what it can of a damaged DB file, and keeps a description of everything it drops
+struct Salvager {
+    dropped: Vec<String>,
+}
+
+impl Salvager {
+    fn report(&mut self, members: &Members, what: &str) {
+        if members.unreadable_keys > 0 {
+            self.dropped.push(format!("{} entries of {} whose keys can't be read", members.unreadable_keys, what));
+        }
+        match members.stop {
+            Some(Stop::Truncated) => self.dropped.push(format!("the end of {}, since the file is truncated", what)),
+            Some(Stop::Unreadable) => self.dropped.push(format!("the rest of {}, which can't be read", what)),
+            None => (),
+        }
+    }
+
+    /// Scan the object or array `raw`, or report it as dropped if it's neither
+    fn scan<'a>(&mut self, raw: &'a str, keyed: bool, what: &str) -> Option<Members<'a>> {
+        let mut scanner = Scanner::new(raw);
+        if !scanner.open(if keyed { b'{' } else { b'[' }) {
+            self.dropped.push(format!("{}, which can't be read", what));
+            return None;
+        }
+        let members = scanner.members(keyed);
+        self.report(&members, what);
+        Some(members)
+    }
+
+    fn values(&mut self, member: &Member, what: &str, encoding: Encoding) -> HashMap<String, Value> {
+        if member.complete && encoding == Encoding::Json {
+            if let Ok(map) = serde_json::from_str(member.raw) {
+                return map;
+            }
+        }
+        let mut map = HashMap::new();
+        for value in self.scan(member.raw, true, what).map(|members| members.members).unwrap_or_default() {
+            match decode(value.raw, encoding).filter(|_| value.complete) {
+                Some(val) => {
+                    map.insert(value.key, val);
+                },
+                None => self.dropped.push(format!("key '{}' of {}", value.key, what)),
+            }
+        }
+        map
+    }
+
+    fn list(&mut self, member: &Member, what: &str, encoding: Encoding) -> Option<Vec<Value>> {
+        if member.complete && encoding == Encoding::Json {
+            if let Ok(list) = serde_json::from_str(member.raw) {
+                return Some(list);
+            }
+        }
+        let items = self.scan(member.raw, false, what)?;
+        let mut list = Vec::new();
+        for (pos, item) in items.members.iter().enumerate() {
+            match decode(item.raw, encoding).filter(|_| item.complete) {
+                Some(val) => list.push(val),
+                None => self.dropped.push(format!("item {} of {}", pos, what)),
+            }
+        }
+        Some(list)
+    }
+
+    fn lists(&mut self, member: &Member, what: &str, encoding: Encoding) -> HashMap<String, Vec<Value>> {
+        if member.complete && encoding == Encoding::Json {
+            if let Ok(list_map) = serde_json::from_str(member.raw) {
+                return list_map;
+            }
+        }
+        let mut list_map = HashMap::new();
+        for list in self.scan(member.raw, true, what).map(|members| members.members).unwrap_or_default() {
+            if let Some(items) = self.list(&list, &format!("list '{}' of {}", list.key, what), encoding) {
+                list_map.insert(list.key, items);
+            }
+        }
+        list_map
+    }
+
+    /// Recover the indexes or schemas in `member`, each of which is kept or dropped as a whole
+    fn definitions<T: DeserializeOwned>(&mut self, member: &Member, what: &str, kind: &str) -> HashMap<String, T> {
+        if member.complete {
+            if let Ok(definitions) = serde_json::from_str(member.raw) {
+                return definitions;
+            }
+        }
+        let mut definitions = HashMap::new();
+        for definition in self.scan(member.raw, true, what).map(|members| members.members).unwrap_or_default() {
+            match serde_json::from_str(definition.raw).ok().filter(|_| definition.complete) {
+                Some(parsed) => {
+                    definitions.insert(definition.key, parsed);
+                },
+                None => self.dropped.push(format!("{} '{}'", kind, definition.key)),
+            }
+        }
+        definitions
+    }
+
+    fn namespace(&mut self, member: &Member) -> Option<NamespaceData> {
+        if member.complete {
+            if let Ok(data) = serde_json::from_str(member.raw) {
+                return Some(data);
+            }
+        }
+        let fields = self.scan(member.raw, true, &format!("namespace '{}'", member.key))?;
+        let mut data = NamespaceData::default();
+        for field in &fields.members {
+            match field.key.as_str() {
+                "map" => data.map = self.values(field, &format!("the map of namespace '{}'", member.key), Encoding::Json),
+                "list_map" => data.list_map = self.lists(field, &format!("the lists of namespace '{}'", member.key), Encoding::Json),
+                "indexes" => data.indexes = self.definitions(field, &format!("the indexes of namespace '{}'", member.key), "index"),
+                _ => (),
+            }
+        }
+        Some(data)
+    }
+
+    fn namespaces(&mut self, member: &Member) -> HashMap<String, NamespaceData> {
+        let mut namespaces = HashMap::new();
+        for namespace in self.scan(member.raw, true, "the namespaces").map(|members| members.members).unwrap_or_default() {
+            if let Some(data) = self.namespace(&namespace) {
+                namespaces.insert(namespace.key, data);
+            }
+        }
+        namespaces
+    }
+
+    /// Recover the fields of a DB file in the current format, from after its opening brace
+    fn fields(&mut self, scanner: &mut Scanner, db: &mut DbFile) {
+        loop {
+            let fields = scanner.members(true);
+            for field in &fields.members {
+                match field.key.as_str() {
+                    "map" => db.map.extend(self.values(field, "the map", Encoding::Json)),
+                    "list_map" => db.list_map.extend(self.lists(field, "the lists", Encoding::Json)),
+                    "namespaces" => db.namespaces.extend(self.namespaces(field)),
+                    "indexes" => db.indexes.extend(self.definitions(field, "the indexes", "index")),
+                    "key_schemas" => db.key_schemas.extend(self.definitions(field, "the key schemas", "key schema")),
+                    "list_schemas" => db.list_schemas.extend(self.definitions(field, "the list schemas", "list schema")),
+                    "format" | "checksum" => (),
+                    other => self.dropped.push(format!("the unknown field '{}'", other)),
+                }
+            }
+            if fields.unreadable_keys > 0 {
+                self.dropped.push(format!("{} fields of the file whose names can't be read", fields.unreadable_keys));
+            }
+            match fields.stop {
+                None => return,
+                Some(Stop::Truncated) => {
+                    self.dropped.push(String::from("the end of the file, which is truncated"));
+                    return;
+                },
+                Some(Stop::Unreadable) => {
+                    if !scanner.resume() {
+                        self.dropped.push(String::from("the rest of the file, which can't be read"));
+                        return;
+                    }
+                    self.dropped.push(String::from("part of the file, which can't be read"));
+                },
+            }
+        }
+    }
+
+    /// Recover the map and the lists of a DB file in the legacy format, from after its opening bracket
+    fn legacy(&mut self, scanner: &mut Scanner, db: &mut DbFile) {
+        let sections = scanner.members(false);
+        for (pos, section) in sections.members.iter().enumerate() {
+            match pos {
+                0 => db.map = self.values(section, "the map", Encoding::Legacy),
+                1 => db.list_map = self.lists(section, "the lists", Encoding::Legacy),
+                _ => (),
+            }
+        }
+        self.report(&sections, "the file");
+    }
+}
+
+/// Recover whatever can be parsed from the text of a damaged DB file, in either the current or the legacy format.
+/// Returns the recovered data and a description of everything that was dropped
+pub(crate) fn salvage(text: &str) -> (DbFile, Vec<String>) {
+    let mut db = DbFile {
+        map: HashMap::new(),
+        list_map: HashMap::new(),
+        namespaces: HashMap::new(),
+        indexes: HashMap::new(),
+        key_schemas: HashMap::new(),
+        list_schemas: HashMap::new(),
+    };
+    let mut salvager = Salvager { dropped: Vec::new() };
+    let mut scanner = Scanner::new(text);
+    if scanner.open(b'{') {
+        salvager.fields(&mut scanner, &mut db);
+    } else if scanner.open(b'[') {
+        salvager.legacy(&mut scanner, &mut db);
+    } else {
+        salvager.dropped.push(String::from("the whole file, which can't be read"));
+    }
+    (db, salvager.dropped)
+}
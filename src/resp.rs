@@ -0,0 +1,518 @@
+//! Serving a DB to Redis clients.
+//!
+//! [RespServer](struct.RespServer.html) speaks a subset of the Redis protocol (RESP) over TCP or Unix sockets, so services
+//! which aren't written in Rust can use a DB with any Redis client. The `pickledb-resp` binary serves a DB file with it, and
+//! [RespClient](struct.RespClient.html) is a minimal client for trying it out. This module is available only when the `resp`
+//! feature is enabled.
+//!
+//! The supported commands and the `PickleDb` methods they're mapped to are:
+//! * `GET key` - [get()](../struct.PickleDb.html#method.get)
+//! * `SET key value` - [set()](../struct.PickleDb.html#method.set)
+//! * `DEL key [key ...]` - [rem()](../struct.PickleDb.html#method.rem)
+//! * `EXISTS key [key ...]` - [exists()](../struct.PickleDb.html#method.exists)
+//! * `KEYS pattern` - [get_all_matching()](../struct.PickleDb.html#method.get_all_matching)
+//! * `RPUSH list value [value ...]` - [lcreate_with()](../struct.PickleDb.html#method.lcreate_with) for a new list and
+//!   [lextend()](../struct.PickleDb.html#method.lextend) otherwise
+//! * `LINDEX list index` - [lget()](../struct.PickleDb.html#method.lget)
+//! * `LLEN list` - [llen()](../struct.PickleDb.html#method.llen)
+//! * `LPOP list` - [lpop()](../struct.PickleDb.html#method.lpop)
+//! * `LREM list count value` - [lpop()](../struct.PickleDb.html#method.lpop) of every matching item, in a single change
+//! * `EXPIRE key seconds` and `TTL key` - the key is removed by [rem()](../struct.PickleDb.html#method.rem) once it expires
+//! * `SAVE` - [try_dump()](../struct.PickleDb.html#method.try_dump)
+//! * `PING` and `QUIT`
+//!
+//! Values that are sent by clients are stored as JSON if they're valid JSON and as strings otherwise, so `SET count 5` stores
+//! the number 5 and `SET name john` stores the string "john". Values are sent back as strings as they are, and as their JSON
+//! text otherwise. Expiration times are kept only in memory, so they're lost when the server restarts.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde_json::Value;
+use crate::PickleDb;
+
+/// The longest bulk string that is accepted, which is the same as in Redis
+const MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+
+/// The longest line that is accepted, which limits inline commands like in Redis
+const MAX_LINE_LEN: u64 = 64 * 1024;
+
+/// A value of the Redis protocol
+#[derive(Clone, Debug, PartialEq)]
+pub enum RespValue {
+    /// A simple string, for example `OK`
+    Simple(String),
+    /// An error, starting with its kind, for example `ERR unknown command`
+    Error(String),
+    /// An integer
+    Integer(i64),
+    /// A bulk string, or `None` for the null bulk string that is returned for keys that don't exist
+    Bulk(Option<String>),
+    /// An array of values
+    Array(Vec<RespValue>),
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    let read = reader.take(MAX_LINE_LEN).read_line(&mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if read as u64 == MAX_LINE_LEN && !line.ends_with('\n') {
+        return Err(invalid_data("line is too long"));
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(Some(line))
+}
+
+fn parse_len(text: &str) -> io::Result<i64> {
+    text.parse().map_err(|_| invalid_data("invalid length"))
+}
+
+/// Read a value, or `None` if the connection was closed. A line that isn't a RESP value is an inline command,
+/// which is split by whitespace like Redis does
+fn read_value<R: BufRead>(reader: &mut R) -> io::Result<Option<RespValue>> {
+    read_value_in(reader, false)
+}
+
+/// Read a value, which is an item of an array if `in_array` is true. Neither commands nor replies hold nested arrays,
+/// so they're rejected rather than read recursively
+fn read_value_in<R: BufRead>(reader: &mut R, in_array: bool) -> io::Result<Option<RespValue>> {
+    let line = match read_line(reader)? {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+    let value = match line.split_at(line.chars().next().map_or(0, char::len_utf8)) {
+        ("+", text) => RespValue::Simple(String::from(text)),
+        ("-", text) => RespValue::Error(String::from(text)),
+        (":", text) => RespValue::Integer(text.parse().map_err(|_| invalid_data("invalid integer"))?),
+        ("$", len) => {
+            let len = parse_len(len)?;
+            if len < 0 {
+                return Ok(Some(RespValue::Bulk(None)));
+            }
+            let len = len as usize;
+            if len > MAX_BULK_LEN {
+                return Err(invalid_data("bulk string is too long"));
+            }
+            // the bytes are read as they arrive, so a length that is sent without the bytes doesn't allocate them
+            let mut bytes = Vec::new();
+            if reader.take(len as u64).read_to_end(&mut bytes)? < len {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "bulk string is truncated"));
+            }
+            let mut end = [0; 2];
+            reader.read_exact(&mut end)?;
+            if &end != b"\r\n" {
+                return Err(invalid_data("bulk string isn't followed by CRLF"));
+            }
+            RespValue::Bulk(Some(String::from_utf8_lossy(&bytes).into_owned()))
+        },
+        ("*", _) if in_array => return Err(invalid_data("nested arrays aren't supported")),
+        ("*", len) => {
+            let len = parse_len(len)?;
+            let mut items = Vec::new();
+            for _ in 0..len {
+                match read_value_in(reader, true)? {
+                    Some(item) => items.push(item),
+                    None => return Ok(None),
+                }
+            }
+            RespValue::Array(items)
+        },
+        _ => RespValue::Array(line.split_whitespace().map(|arg| RespValue::Bulk(Some(String::from(arg)))).collect()),
+    };
+    Ok(Some(value))
+}
+
+fn write_value<W: Write>(writer: &mut W, value: &RespValue) -> io::Result<()> {
+    match value {
+        RespValue::Simple(text) => write!(writer, "+{}\r\n", text),
+        RespValue::Error(text) => write!(writer, "-{}\r\n", text),
+        RespValue::Integer(n) => write!(writer, ":{}\r\n", n),
+        RespValue::Bulk(None) => write!(writer, "$-1\r\n"),
+        RespValue::Bulk(Some(text)) => write!(writer, "${}\r\n{}\r\n", text.len(), text),
+        RespValue::Array(items) => {
+            write!(writer, "*{}\r\n", items.len())?;
+            items.iter().try_for_each(|item| write_value(writer, item))
+        },
+    }
+}
+
+fn ok() -> RespValue {
+    RespValue::Simple(String::from("OK"))
+}
+
+fn wrong_type() -> RespValue {
+    RespValue::Error(String::from("WRONGTYPE Operation against a key holding the wrong kind of value"))
+}
+
+fn not_an_integer() -> RespValue {
+    RespValue::Error(String::from("ERR value is not an integer or out of range"))
+}
+
+fn wrong_args(command: &str) -> RespValue {
+    RespValue::Error(format!("ERR wrong number of arguments for '{}' command", command.to_lowercase()))
+}
+
+/// The value a client sent, as it's stored in the DB
+fn from_client(text: &str) -> Value {
+    serde_json::from_str(text).unwrap_or_else(|_| Value::String(String::from(text)))
+}
+
+/// A value of the DB, as it's sent to clients
+fn to_client(value: Option<Value>) -> RespValue {
+    match value {
+        Some(Value::String(text)) => RespValue::Bulk(Some(text)),
+        Some(value) => RespValue::Bulk(Some(value.to_string())),
+        None => RespValue::Bulk(None),
+    }
+}
+
+struct State {
+    db: PickleDb,
+    /// The keys that expire, and when
+    expirations: HashMap<String, Instant>,
+}
+
+impl State {
+    /// Remove the keys whose time to live passed
+    fn expire(&mut self) {
+        let now = Instant::now();
+        let expired: Vec<String> = self.expirations.iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            self.expirations.remove(&key);
+            self.db.rem(&key);
+        }
+    }
+
+    fn is_value(&self, key: &str) -> bool {
+        self.db.exists(key) && !self.db.lexists(key)
+    }
+
+    fn list(&self, name: &str) -> Vec<Value> {
+        (0..self.db.llen(name)).filter_map(|pos| self.db.lget(name, pos)).collect()
+    }
+
+    fn execute(&mut self, command: &str, args: &[String], read_only: bool) -> RespValue {
+        let writes = matches!(command, "SET" | "DEL" | "RPUSH" | "LPOP" | "LREM" | "EXPIRE" | "SAVE");
+        if writes && read_only {
+            return RespValue::Error(String::from("READONLY You can't write against a read only server."));
+        }
+        self.expire();
+
+        match (command, args) {
+            ("PING", []) => RespValue::Simple(String::from("PONG")),
+            ("PING", [message]) => RespValue::Bulk(Some(message.clone())),
+            // sent by redis-cli when it connects
+            ("COMMAND", _) => RespValue::Array(Vec::new()),
+            ("GET", [key]) => {
+                if self.db.lexists(key) {
+                    return wrong_type();
+                }
+                to_client(self.db.get(key))
+            },
+            ("SET", [key, value]) => {
+                match self.db.set(key, &from_client(value)) {
+                    Ok(()) => {
+                        self.expirations.remove(key);
+                        ok()
+                    },
+                    Err(err) => RespValue::Error(format!("ERR {}", err)),
+                }
+            },
+            ("DEL", keys) if !keys.is_empty() => {
+                let removed = keys.iter().filter(|key| {
+                    self.expirations.remove(*key);
+                    self.db.rem(key)
+                }).count();
+                RespValue::Integer(removed as i64)
+            },
+            ("EXISTS", keys) if !keys.is_empty() => RespValue::Integer(keys.iter().filter(|key| self.db.exists(key)).count() as i64),
+            ("KEYS", [pattern]) => {
+                let mut keys = self.db.get_all_matching(pattern);
+                keys.sort();
+                RespValue::Array(keys.into_iter().map(|key| RespValue::Bulk(Some(key))).collect())
+            },
+            ("RPUSH", [name, values @ ..]) if !values.is_empty() => {
+                if self.is_value(name) {
+                    return wrong_type();
+                }
+                let values: Vec<Value> = values.iter().map(|value| from_client(value)).collect();
+                // a list that doesn't exist is created only if its items are valid
+                let res = if self.db.lexists(name) {
+                    self.db.lextend(name, &values).map(|_| ())
+                } else {
+                    self.db.lcreate_with(name, &values)
+                };
+                match res {
+                    Ok(()) => RespValue::Integer(self.db.llen(name) as i64),
+                    Err(err) => RespValue::Error(format!("ERR {}", err)),
+                }
+            },
+            ("LINDEX", [name, index]) => {
+                if self.is_value(name) {
+                    return wrong_type();
+                }
+                let index: i64 = match index.parse() {
+                    Ok(index) => index,
+                    Err(_) => return not_an_integer(),
+                };
+                let len = self.db.llen(name) as i64;
+                let pos = if index < 0 { len + index } else { index };
+                if pos < 0 || pos >= len {
+                    return RespValue::Bulk(None);
+                }
+                to_client(self.db.lget(name, pos as usize))
+            },
+            ("LLEN", [name]) => {
+                if self.is_value(name) {
+                    return wrong_type();
+                }
+                RespValue::Integer(self.db.llen(name) as i64)
+            },
+            ("LPOP", [name]) => {
+                if self.is_value(name) {
+                    return wrong_type();
+                }
+                to_client(self.db.lpop(name, 0))
+            },
+            ("LREM", [name, count, value]) => {
+                if self.is_value(name) {
+                    return wrong_type();
+                }
+                let count: i64 = match count.parse() {
+                    Ok(count) => count,
+                    Err(_) => return not_an_integer(),
+                };
+                let value = from_client(value);
+                let mut matching: Vec<usize> = self.list(name).iter()
+                    .enumerate()
+                    .filter(|(_, item)| **item == value)
+                    .map(|(pos, _)| pos)
+                    .collect();
+                // a negative count removes the matching items from the tail, 0 removes all of them
+                if count < 0 {
+                    matching.reverse();
+                }
+                if count != 0 {
+                    matching.truncate(count.unsigned_abs() as usize);
+                }
+                match self.db.lpop_all_in(None, name, &matching) {
+                    Ok(removed) => RespValue::Integer(removed as i64),
+                    Err(err) => RespValue::Error(format!("ERR {}", err)),
+                }
+            },
+            ("EXPIRE", [key, seconds]) => {
+                let seconds: i64 = match seconds.parse() {
+                    Ok(seconds) => seconds,
+                    Err(_) => return not_an_integer(),
+                };
+                if !self.db.exists(key) {
+                    return RespValue::Integer(0);
+                }
+                if seconds <= 0 {
+                    self.expirations.remove(key);
+                    self.db.rem(key);
+                } else {
+                    self.expirations.insert(key.clone(), Instant::now() + Duration::from_secs(seconds as u64));
+                }
+                RespValue::Integer(1)
+            },
+            ("TTL", [key]) => match self.expirations.get(key) {
+                Some(deadline) => RespValue::Integer(deadline.saturating_duration_since(Instant::now()).as_secs_f64().ceil() as i64),
+                None if self.db.exists(key) => RespValue::Integer(-1),
+                None => RespValue::Integer(-2),
+            },
+            ("SAVE", []) => {
+                match self.db.try_dump() {
+                    Ok(()) => ok(),
+                    Err(err) => RespValue::Error(format!("ERR {}", err)),
+                }
+            },
+            ("PING", _) | ("GET", _) | ("SET", _) | ("DEL", _) | ("EXISTS", _) | ("KEYS", _) | ("RPUSH", _) | ("LINDEX", _)
+            | ("LLEN", _) | ("LPOP", _) | ("LREM", _) | ("EXPIRE", _) | ("TTL", _) | ("SAVE", _) => wrong_args(command),
+            _ => RespValue::Error(format!("ERR unknown command '{}'", command.to_lowercase())),
+        }
+    }
+}
+
+/// A server that serves a DB to Redis clients.
+///
+/// Every connection is handled by a thread of its own, and the commands of all the connections are run one at a time on
+/// the same DB, which dumps its changes according to its dump policy. The server can be cloned to serve the same DB on
+/// several sockets.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::net::TcpListener;
+/// use pickledb::resp::RespServer;
+///
+/// let db = PickleDb::load("example.db", PickleDbDumpPolicy::AutoDump).unwrap();
+/// let server = RespServer::new(db);
+/// server.serve_tcp(TcpListener::bind("127.0.0.1:6380").unwrap()).unwrap();
+/// ```
+///
+#[derive(Clone)]
+pub struct RespServer {
+    state: Arc<Mutex<State>>,
+    read_only: bool,
+}
+
+impl RespServer {
+    /// Create a server for a DB.
+    ///
+    pub fn new(db: PickleDb) -> RespServer {
+        RespServer { state: Arc::new(Mutex::new(State { db, expirations: HashMap::new() })), read_only: false }
+    }
+
+    /// Create a server for a DB that rejects every command which changes it.
+    ///
+    pub fn new_read_only(db: PickleDb) -> RespServer {
+        RespServer { read_only: true, ..RespServer::new(db) }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Serve the clients that connect to a TCP socket. This method returns only if the socket fails.
+    ///
+    pub fn serve_tcp(&self, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let server = self.clone();
+            thread::spawn(move || {
+                if let Ok(reader) = stream.try_clone() {
+                    let _ignore = server.handle_connection(reader, stream);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Serve the clients that connect to a Unix socket. This method returns only if the socket fails.
+    ///
+    #[cfg(unix)]
+    pub fn serve_unix(&self, listener: UnixListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let server = self.clone();
+            thread::spawn(move || {
+                if let Ok(reader) = stream.try_clone() {
+                    let _ignore = server.handle_connection(reader, stream);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Serve a single client until it disconnects or sends `QUIT`. A client that sends something which isn't RESP is
+    /// disconnected with an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - where the commands of the client are read from
+    /// * `writer` - where the replies are written to
+    ///
+    pub fn handle_connection<R: Read, W: Write>(&self, reader: R, mut writer: W) -> io::Result<()> {
+        let mut reader = BufReader::new(reader);
+        loop {
+            let request = match read_value(&mut reader) {
+                Ok(Some(request)) => request,
+                Ok(None) => return Ok(()),
+                Err(err) => {
+                    write_value(&mut writer, &RespValue::Error(format!("ERR Protocol error: {}", err)))?;
+                    return Err(err);
+                },
+            };
+            let args: Vec<String> = match request {
+                RespValue::Array(items) => items.into_iter()
+                    .filter_map(|item| match item {
+                        RespValue::Bulk(Some(arg)) | RespValue::Simple(arg) => Some(arg),
+                        RespValue::Integer(n) => Some(n.to_string()),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let (command, args) = match args.split_first() {
+                Some((command, args)) => (command.to_uppercase(), args),
+                None => continue,
+            };
+            if command == "QUIT" {
+                write_value(&mut writer, &ok())?;
+                return writer.flush();
+            }
+            let reply = self.lock().execute(&command, args, self.read_only);
+            write_value(&mut writer, &reply)?;
+            writer.flush()?;
+        }
+    }
+}
+
+/// A minimal client of the Redis protocol, for trying out a [RespServer](struct.RespServer.html) without a Redis client.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use pickledb::resp::{RespClient, RespValue};
+///
+/// let mut client = RespClient::connect_tcp("127.0.0.1:6380").unwrap();
+/// client.command(&["SET", "key1", "100"]).unwrap();
+/// assert_eq!(client.command(&["GET", "key1"]).unwrap(), RespValue::Bulk(Some(String::from("100"))));
+/// ```
+///
+pub struct RespClient {
+    reader: BufReader<Box<dyn Read + Send>>,
+    writer: Box<dyn Write + Send>,
+}
+
+impl RespClient {
+    /// Connect to a server over TCP.
+    ///
+    pub fn connect_tcp<A: ToSocketAddrs>(addr: A) -> io::Result<RespClient> {
+        let stream = TcpStream::connect(addr)?;
+        Ok(RespClient { reader: BufReader::new(Box::new(stream.try_clone()?)), writer: Box::new(stream) })
+    }
+
+    /// Connect to a server over a Unix socket.
+    ///
+    #[cfg(unix)]
+    pub fn connect_unix<P: AsRef<Path>>(path: P) -> io::Result<RespClient> {
+        let stream = UnixStream::connect(path)?;
+        Ok(RespClient { reader: BufReader::new(Box::new(stream.try_clone()?)), writer: Box::new(stream) })
+    }
+
+    /// Send a command and wait for its reply.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - the name of the command followed by its arguments, for example `&["GET", "key1"]`
+    ///
+    pub fn command(&mut self, args: &[&str]) -> io::Result<RespValue> {
+        let request = RespValue::Array(args.iter().map(|arg| RespValue::Bulk(Some(String::from(*arg)))).collect());
+        write_value(&mut self.writer, &request)?;
+        self.writer.flush()?;
+        read_value(&mut self.reader)?.ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "the server closed the connection"))
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use crate::error::Error;
+use crate::query::compare_values;
+
+/// The keywords a schema may contain. Annotations are accepted and ignored, every other keyword is rejected
+/// when the schema is created so that a schema is never silently validated partially
+const VALIDATION_KEYWORDS: [&str; 23] = [
+    "type", "enum", "const",
+    "properties", "required", "additionalProperties", "minProperties", "maxProperties",
+    "items", "minItems", "maxItems", "uniqueItems",
+    "minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum", "multipleOf",
+    "minLength", "maxLength",
+    "allOf", "anyOf", "oneOf", "not",
+];
+
+/// The relative tolerance of `multipleOf`, which covers the rounding errors of the value, the divisor and their quotient
+const MULTIPLE_OF_EPSILON: f64 = 4.0 * f64::EPSILON;
+
+const ANNOTATION_KEYWORDS: [&str; 9] = [
+    "$schema", "$id", "$comment", "title", "description", "default", "examples", "format", "definitions",
+];
+
+/// A JSON Schema which values are validated against.
+///
+/// The supported keywords are a subset of JSON Schema draft 7 which covers the validation of types, objects,
+/// arrays, numbers and strings, and combinations of schemas. References (`$ref`) and regular expressions
+/// (`pattern`, `patternProperties`) aren't supported
+#[derive(Clone, Debug)]
+pub(crate) struct Schema {
+    schema: Value,
+}
+
+/// The reason a value doesn't conform to a schema and the path inside the value in which it was found
+pub(crate) struct SchemaViolation {
+    pub(crate) path: String,
+    pub(crate) reason: String,
+}
+
+fn check_keywords(schema: &Value, schema_path: &str) -> Result<(), String> {
+    let schema = match schema {
+        Value::Bool(_) => return Ok(()),
+        Value::Object(schema) => schema,
+        _ => return Err(format!("{}: a schema must be an object or a boolean", schema_path)),
+    };
+
+    for (keyword, val) in schema {
+        if ANNOTATION_KEYWORDS.contains(&keyword.as_str()) {
+            continue;
+        }
+        if !VALIDATION_KEYWORDS.contains(&keyword.as_str()) {
+            return Err(format!("{}: unsupported keyword '{}'", schema_path, keyword));
+        }
+
+        let sub_path = format!("{}/{}", schema_path, keyword);
+        match (keyword.as_str(), val) {
+            ("properties", Value::Object(properties)) => {
+                for (name, property) in properties {
+                    check_keywords(property, &format!("{}/{}", sub_path, name))?;
+                }
+            },
+            ("allOf", Value::Array(schemas)) | ("anyOf", Value::Array(schemas)) | ("oneOf", Value::Array(schemas)) => {
+                for (i, sub_schema) in schemas.iter().enumerate() {
+                    check_keywords(sub_schema, &format!("{}/{}", sub_path, i))?;
+                }
+            },
+            ("additionalProperties", _) | ("items", _) | ("not", _) => check_keywords(val, &sub_path)?,
+            ("type", Value::String(_)) | ("type", Value::Array(_)) | ("enum", Value::Array(_)) | ("required", Value::Array(_)) => (),
+            ("const", _) => (),
+            ("properties", _) | ("allOf", _) | ("anyOf", _) | ("oneOf", _) | ("type", _) | ("enum", _) | ("required", _) =>
+                return Err(format!("{}: invalid value", sub_path)),
+            ("multipleOf", Value::Number(divisor)) if divisor.as_f64().is_none_or(|divisor| divisor <= 0.0) =>
+                return Err(format!("{}: must be greater than 0", sub_path)),
+            (_, Value::Number(_)) | ("uniqueItems", Value::Bool(_)) => (),
+            _ => return Err(format!("{}: invalid value", sub_path)),
+        }
+    }
+    Ok(())
+}
+
+fn type_matches(type_name: &str, value: &Value) -> bool {
+    match type_name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|num| num.fract() == 0.0),
+        _ => false,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn field_path(path: &str, field: &str) -> String {
+    if !field.is_empty() && field.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        format!("{}.{}", path, field)
+    } else {
+        format!("{}['{}']", path, field)
+    }
+}
+
+/// Whether two values are equal, where numbers are compared by their numeric value, so `1` equals `1.0`
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(_), Value::Number(_)) => compare_values(a, b) == Some(std::cmp::Ordering::Equal),
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| values_equal(a, b)),
+        (Value::Object(a), Value::Object(b)) =>
+            a.len() == b.len() && a.iter().all(|(field, a)| b.get(field).is_some_and(|b| values_equal(a, b))),
+        _ => a == b,
+    }
+}
+
+fn violation(path: &str, reason: String) -> Result<(), SchemaViolation> {
+    Err(SchemaViolation { path: String::from(path), reason })
+}
+
+fn validate(schema: &Value, value: &Value, path: &str) -> Result<(), SchemaViolation> {
+    let schema = match schema {
+        Value::Bool(true) => return Ok(()),
+        Value::Bool(false) => return violation(path, String::from("no value is allowed")),
+        Value::Object(schema) => schema,
+        _ => return Ok(()),
+    };
+
+    if let Some(types) = schema.get("type") {
+        let allowed: Vec<&str> = match types {
+            Value::String(type_name) => vec![type_name.as_str()],
+            Value::Array(types) => types.iter().filter_map(|t| t.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.iter().any(|t| type_matches(t, value)) {
+            return violation(path, format!("expected {} but found {}", allowed.join(" or "), type_name(value)));
+        }
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.iter().any(|option| values_equal(option, value)) {
+            return violation(path, format!("{} is not one of {}", value, Value::Array(options.clone())));
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if !values_equal(expected, value) {
+            return violation(path, format!("expected {} but found {}", expected, value));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for field in required.iter().filter_map(|field| field.as_str()) {
+                if !object.contains_key(field) {
+                    return violation(path, format!("missing required property '{}'", field));
+                }
+            }
+        }
+        if let Some(min) = schema.get("minProperties").and_then(|min| min.as_u64()) {
+            if (object.len() as u64) < min {
+                return violation(path, format!("expected at least {} properties but found {}", min, object.len()));
+            }
+        }
+        if let Some(max) = schema.get("maxProperties").and_then(|max| max.as_u64()) {
+            if object.len() as u64 > max {
+                return violation(path, format!("expected at most {} properties but found {}", max, object.len()));
+            }
+        }
+
+        let properties = schema.get("properties").and_then(|properties| properties.as_object());
+        for (field, field_value) in object {
+            match properties.and_then(|properties| properties.get(field)) {
+                Some(property_schema) => validate(property_schema, field_value, &field_path(path, field))?,
+                None => {
+                    if let Some(additional) = schema.get("additionalProperties") {
+                        if additional == &Value::Bool(false) {
+                            return violation(path, format!("additional property '{}' is not allowed", field));
+                        }
+                        validate(additional, field_value, &field_path(path, field))?;
+                    }
+                },
+            }
+        }
+    }
+
+    if let Value::Array(items) = value {
+        if let Some(min) = schema.get("minItems").and_then(|min| min.as_u64()) {
+            if (items.len() as u64) < min {
+                return violation(path, format!("expected at least {} items but found {}", min, items.len()));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(|max| max.as_u64()) {
+            if items.len() as u64 > max {
+                return violation(path, format!("expected at most {} items but found {}", max, items.len()));
+            }
+        }
+        if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+            for (i, item) in items.iter().enumerate() {
+                if items[..i].iter().any(|earlier| values_equal(earlier, item)) {
+                    return violation(&format!("{}[{}]", path, i), String::from("items must be unique"));
+                }
+            }
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                validate(item_schema, item, &format!("{}[{}]", path, i))?;
+            }
+        }
+    }
+
+    if let Some(num) = value.as_f64() {
+        let bound = |keyword: &str| schema.get(keyword).and_then(|bound| bound.as_f64());
+        if let Some(min) = bound("minimum") {
+            if num < min {
+                return violation(path, format!("{} is less than the minimum of {}", value, min));
+            }
+        }
+        if let Some(max) = bound("maximum") {
+            if num > max {
+                return violation(path, format!("{} is greater than the maximum of {}", value, max));
+            }
+        }
+        if let Some(min) = bound("exclusiveMinimum") {
+            if num <= min {
+                return violation(path, format!("{} is not greater than {}", value, min));
+            }
+        }
+        if let Some(max) = bound("exclusiveMaximum") {
+            if num >= max {
+                return violation(path, format!("{} is not less than {}", value, max));
+            }
+        }
+        if let Some(divisor) = bound("multipleOf") {
+            // the quotient of decimal fractions such as 0.3 / 0.1 is rarely an exact integer in floating point
+            let quotient = num / divisor;
+            if (quotient - quotient.round()).abs() > MULTIPLE_OF_EPSILON * quotient.abs().max(1.0) {
+                return violation(path, format!("{} is not a multiple of {}", value, divisor));
+            }
+        }
+    }
+
+    if let Value::String(string) = value {
+        let len = string.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(|min| min.as_u64()) {
+            if len < min {
+                return violation(path, format!("expected at least {} characters but found {}", min, len));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(|max| max.as_u64()) {
+            if len > max {
+                return violation(path, format!("expected at most {} characters but found {}", max, len));
+            }
+        }
+    }
+
+    if let Some(Value::Array(schemas)) = schema.get("allOf") {
+        for sub_schema in schemas {
+            validate(sub_schema, value, path)?;
+        }
+    }
+
+    if let Some(Value::Array(schemas)) = schema.get("anyOf") {
+        if !schemas.iter().any(|sub_schema| validate(sub_schema, value, path).is_ok()) {
+            return violation(path, String::from("value doesn't match any of the schemas in 'anyOf'"));
+        }
+    }
+
+    if let Some(Value::Array(schemas)) = schema.get("oneOf") {
+        let matches = schemas.iter().filter(|sub_schema| validate(sub_schema, value, path).is_ok()).count();
+        if matches != 1 {
+            return violation(path, format!("value matches {} of the schemas in 'oneOf' instead of exactly one", matches));
+        }
+    }
+
+    if let Some(sub_schema) = schema.get("not") {
+        if validate(sub_schema, value, path).is_ok() {
+            return violation(path, String::from("value matches the schema in 'not'"));
+        }
+    }
+
+    Ok(())
+}
+
+impl Schema {
+    /// Create a schema. An error describing the problem is returned if the schema is invalid
+    /// or uses keywords that aren't supported
+    pub(crate) fn new(schema: Value) -> Result<Schema, String> {
+        check_keywords(&schema, "#")?;
+        Ok(Schema { schema })
+    }
+
+    /// Validate a value against the schema
+    pub(crate) fn validate(&self, value: &Value) -> Result<(), SchemaViolation> {
+        validate(&self.schema, value, "$")
+    }
+
+    /// Validate the value of `key` against the schema
+    pub(crate) fn check_value(&self, key: &str, value: &Value) -> Result<(), Error> {
+        self.validate(value).map_err(|violation| Error::SchemaViolation {
+            key: String::from(key),
+            path: violation.path,
+            reason: violation.reason,
+        })
+    }
+
+    /// Validate the item in position `pos` of list `name` against the schema
+    pub(crate) fn check_item(&self, name: &str, pos: usize, item: &Value) -> Result<(), Error> {
+        self.validate(item).map_err(|violation| Error::SchemaViolation {
+            key: String::from(name),
+            path: violation.path.replacen('$', &format!("$[{}]", pos), 1),
+            reason: violation.reason,
+        })
+    }
+}
+
+impl Serialize for Schema {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.schema.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Schema {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Schema, D::Error> {
+        Schema::new(Value::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+/// Validate the value of `key` against the schemas of all key prefixes it starts with
+pub(crate) fn check_all(key_schemas: &HashMap<String, Schema>, key: &str, value: &Value) -> Result<(), Error> {
+    for (prefix, schema) in key_schemas {
+        if key.starts_with(prefix.as_str()) {
+            schema.check_value(key, value)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,350 @@
+//! Serving a DB over HTTP.
+//!
+//! [HttpServer](struct.HttpServer.html) exposes the keys and lists of a DB as JSON resources, so small tools and browser
+//! dashboards can use a DB without linking Rust. The `pickledb-server` binary serves a DB file with it. This module is
+//! available only when the `server` feature is enabled.
+//!
+//! The endpoints and the `PickleDb` methods they're mapped to are:
+//! * `GET /keys/{key}` - the value of a key, by [get()](../struct.PickleDb.html#method.get)
+//! * `PUT /keys/{key}` - set the value of a key to the JSON body, by [set()](../struct.PickleDb.html#method.set)
+//! * `DELETE /keys/{key}` - remove a key, by [rem()](../struct.PickleDb.html#method.rem)
+//! * `GET /keys?prefix={prefix}` - the names of the keys and lists that start with a prefix, or of all of them if there's no prefix
+//! * `GET /lists/{name}` - the items of a list as a JSON array
+//! * `PUT /lists/{name}` - replace a list with the items of a JSON array, by [lcreate_with()](../struct.PickleDb.html#method.lcreate_with)
+//! * `DELETE /lists/{name}` - remove a list, by [lrem_list()](../struct.PickleDb.html#method.lrem_list)
+//! * `POST /dump` - dump the DB, by [try_dump()](../struct.PickleDb.html#method.try_dump)
+//!
+//! Keys and list names are percent-decoded. Changes are dumped according to the dump policy of the DB. Successful changes
+//! reply with `204 No Content`, and errors reply with a status code and a JSON body such as `{"error": "key 'x' not found"}`.
+//! Bodies that are longer than 16 MiB are rejected with `413 Payload Too Large`.
+//!
+//! Browsers may call the server from pages of other origins only if the server allows their origin, please see
+//! [set_cors_origin()](struct.HttpServer.html#method.set_cors_origin).
+
+use std::io::{self, Read};
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+use crate::PickleDb;
+
+/// The number of threads that handle requests
+const WORKERS: usize = 4;
+
+/// The longest body of a request that is read
+const MAX_BODY_LEN: usize = 16 * 1024 * 1024;
+
+/// The methods and the headers that browsers may use in requests from other origins
+const CORS_METHODS: &str = "GET, PUT, DELETE, POST";
+const CORS_HEADERS: &str = "Authorization, Content-Type";
+
+/// A response: its status code and its JSON body, if it has one
+type Reply = (u16, Option<Value>);
+
+fn error(status: u16, message: String) -> Reply {
+    (status, Some(json!({ "error": message })))
+}
+
+fn no_content() -> Reply {
+    (204, None)
+}
+
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+/// Decode the `%XX` escapes of a part of a URL, and `+` as a space if it's in the query
+fn percent_decode(text: &str, in_query: bool) -> Result<String, Reply> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'%' => {
+                let byte = bytes.get(pos + 1).and_then(|high| hex_digit(*high))
+                    .zip(bytes.get(pos + 2).and_then(|low| hex_digit(*low)))
+                    .map(|(high, low)| high * 16 + low);
+                match byte {
+                    Some(byte) => decoded.push(byte),
+                    None => return Err(error(400, format!("invalid escape in '{}'", text))),
+                }
+                pos += 3;
+            },
+            b'+' if in_query => {
+                decoded.push(b' ');
+                pos += 1;
+            },
+            byte => {
+                decoded.push(byte);
+                pos += 1;
+            },
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| error(400, format!("'{}' isn't UTF-8", text)))
+}
+
+/// The value of a parameter of a query string
+fn query_param(query: &str, name: &str) -> Result<Option<String>, Reply> {
+    for pair in query.split('&') {
+        let (key, value) = pair.split_at(pair.find('=').unwrap_or(pair.len()));
+        if percent_decode(key, true)? == name {
+            return percent_decode(value.trim_start_matches('='), true).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+/// Compare a token in time that doesn't depend on how much of it matches
+fn tokens_equal(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// A server that serves a DB over HTTP.
+///
+/// Requests are handled by a few threads, and they run one at a time on the same DB. If the server has a token, every
+/// request must carry it in an `Authorization: Bearer <token>` header, except for the CORS preflight requests of browsers.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::net::TcpListener;
+/// use pickledb::server::HttpServer;
+///
+/// let db = PickleDb::load("example.db", PickleDbDumpPolicy::AutoDump).unwrap();
+/// let mut server = HttpServer::new(db);
+/// server.set_token("secret");
+/// server.serve_tcp(TcpListener::bind("127.0.0.1:8080").unwrap()).unwrap();
+/// ```
+///
+#[derive(Clone)]
+pub struct HttpServer {
+    db: Arc<Mutex<PickleDb>>,
+    token: Option<String>,
+    cors_origin: Option<String>,
+    read_only: bool,
+}
+
+impl HttpServer {
+    /// Create a server for a DB, which accepts requests without a token.
+    ///
+    pub fn new(db: PickleDb) -> HttpServer {
+        HttpServer { db: Arc::new(Mutex::new(db)), token: None, cors_origin: None, read_only: false }
+    }
+
+    /// Create a server for a DB that rejects every request which changes it.
+    ///
+    pub fn new_read_only(db: PickleDb) -> HttpServer {
+        HttpServer { read_only: true, ..HttpServer::new(db) }
+    }
+
+    /// Require every request to carry a bearer token.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - the token that requests must carry in an `Authorization: Bearer <token>` header
+    ///
+    pub fn set_token(&mut self, token: &str) {
+        self.token = Some(String::from(token));
+    }
+
+    /// Allow browsers to call the server from pages of another origin (CORS).
+    ///
+    /// Responses carry an `Access-Control-Allow-Origin` header with the origin, and `OPTIONS` preflight requests are
+    /// answered with the methods and headers the endpoints accept. Without an origin, which is the default, browsers
+    /// allow only pages that the server itself serves to call it.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - the origin that is allowed, for example `https://dashboard.example.com`, or `*` for any origin
+    ///
+    pub fn set_cors_origin(&mut self, origin: &str) {
+        self.cors_origin = Some(String::from(origin));
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PickleDb> {
+        self.db.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Serve the requests that arrive at a TCP socket. This method returns only if the socket fails.
+    ///
+    pub fn serve_tcp(&self, listener: TcpListener) -> io::Result<()> {
+        self.serve(Server::from_listener(listener, None).map_err(io::Error::other)?)
+    }
+
+    /// Serve the requests that arrive at a Unix socket. This method returns only if the socket fails.
+    ///
+    #[cfg(unix)]
+    pub fn serve_unix(&self, listener: UnixListener) -> io::Result<()> {
+        self.serve(Server::from_listener(listener, None).map_err(io::Error::other)?)
+    }
+
+    fn serve(&self, server: Server) -> io::Result<()> {
+        let server = Arc::new(server);
+        let workers: Vec<_> = (0..WORKERS).map(|_| {
+            let server = Arc::clone(&server);
+            let this = self.clone();
+            thread::spawn(move || -> io::Result<()> {
+                loop {
+                    let request = server.recv()?;
+                    // the client may have disconnected, which doesn't concern the other requests
+                    let _ignore = this.respond(request);
+                }
+            })
+        }).collect();
+        for worker in workers {
+            worker.join().map_err(|_| io::Error::other("a worker of the server panicked"))??;
+        }
+        Ok(())
+    }
+
+    fn respond(&self, mut request: Request) -> io::Result<()> {
+        if let (Some(origin), Method::Options) = (&self.cors_origin, request.method()) {
+            let response = Response::empty(204)
+                .with_header(Header::from_bytes("Access-Control-Allow-Origin", origin.as_bytes()).expect("a valid header"))
+                .with_header(Header::from_bytes("Access-Control-Allow-Methods", CORS_METHODS).expect("a valid header"))
+                .with_header(Header::from_bytes("Access-Control-Allow-Headers", CORS_HEADERS).expect("a valid header"));
+            return request.respond(response);
+        }
+        let (status, body) = self.handle(&mut request);
+        let response = match body {
+            Some(body) => Response::from_string(body.to_string())
+                .with_header(Header::from_bytes("Content-Type", "application/json").expect("a valid header"))
+                .boxed(),
+            None => Response::empty(status).boxed(),
+        };
+        let mut response = response.with_status_code(status);
+        if status == 401 {
+            response.add_header(Header::from_bytes("WWW-Authenticate", "Bearer").expect("a valid header"));
+        }
+        if let Some(origin) = &self.cors_origin {
+            response.add_header(Header::from_bytes("Access-Control-Allow-Origin", origin.as_bytes()).expect("a valid header"));
+        }
+        request.respond(response)
+    }
+
+    fn authorized(&self, request: &Request) -> bool {
+        let token = match &self.token {
+            Some(token) => token,
+            None => return true,
+        };
+        request.headers().iter()
+            .filter(|header| header.field.equiv("Authorization"))
+            .filter_map(|header| header.value.as_str().strip_prefix("Bearer "))
+            .any(|given| tokens_equal(given.trim().as_bytes(), token.as_bytes()))
+    }
+
+    fn body(request: &mut Request) -> Result<Value, Reply> {
+        let too_large = || error(413, format!("the body is longer than {} bytes", MAX_BODY_LEN));
+        if request.body_length().is_some_and(|len| len > MAX_BODY_LEN) {
+            return Err(too_large());
+        }
+        // a body without a length, which is sent in chunks, is read up to the limit
+        let mut body = String::new();
+        Read::take(request.as_reader(), MAX_BODY_LEN as u64 + 1).read_to_string(&mut body)
+            .map_err(|err| error(400, format!("can't read the body: {}", err)))?;
+        if body.len() > MAX_BODY_LEN {
+            return Err(too_large());
+        }
+        serde_json::from_str(&body).map_err(|err| error(400, format!("the body isn't valid JSON: {}", err)))
+    }
+
+    fn handle(&self, request: &mut Request) -> Reply {
+        if !self.authorized(request) {
+            return error(401, String::from("a valid bearer token is required"));
+        }
+        let url = String::from(request.url());
+        let (path, query) = url.split_at(url.find('?').unwrap_or(url.len()));
+        let query = query.trim_start_matches('?');
+        let method = request.method().clone();
+        if self.read_only && !matches!(method, Method::Get | Method::Head) {
+            return error(403, String::from("the DB is read-only"));
+        }
+        match self.route(&method, path, query, request) {
+            Ok(reply) | Err(reply) => reply,
+        }
+    }
+
+    fn route(&self, method: &Method, path: &str, query: &str, request: &mut Request) -> Result<Reply, Reply> {
+        if path == "/keys" {
+            return match method {
+                Method::Get => {
+                    let prefix = query_param(query, "prefix")?.unwrap_or_default();
+                    let mut keys: Vec<String> = self.lock().get_all().into_iter().filter(|key| key.starts_with(&prefix)).collect();
+                    keys.sort();
+                    Ok((200, Some(json!(keys))))
+                },
+                _ => Err(error(405, format!("{} isn't allowed on /keys", method))),
+            };
+        }
+        if path == "/dump" {
+            return match method {
+                Method::Post => {
+                    self.lock().try_dump().map_err(|err| error(500, err.to_string()))?;
+                    Ok(no_content())
+                },
+                _ => Err(error(405, format!("{} isn't allowed on /dump", method))),
+            };
+        }
+        if let Some(key) = path.strip_prefix("/keys/") {
+            let key = percent_decode(key, false)?;
+            return match method {
+                Method::Get => {
+                    let db = self.lock();
+                    match db.get::<Value>(&key) {
+                        Some(value) => Ok((200, Some(value))),
+                        None if db.lexists(&key) => Err(error(404, format!("'{}' is a list, please use /lists/{}", key, key))),
+                        None => Err(error(404, format!("key '{}' not found", key))),
+                    }
+                },
+                Method::Put => {
+                    let value = HttpServer::body(request)?;
+                    self.lock().set(&key, &value).map_err(|err| error(422, err.to_string()))?;
+                    Ok(no_content())
+                },
+                Method::Delete => {
+                    let mut db = self.lock();
+                    if db.lexists(&key) || !db.rem(&key) {
+                        return Err(error(404, format!("key '{}' not found", key)));
+                    }
+                    Ok(no_content())
+                },
+                _ => Err(error(405, format!("{} isn't allowed on keys", method))),
+            };
+        }
+        if let Some(name) = path.strip_prefix("/lists/") {
+            let name = percent_decode(name, false)?;
+            return match method {
+                Method::Get => {
+                    let db = self.lock();
+                    if !db.lexists(&name) {
+                        return Err(error(404, format!("list '{}' not found", name)));
+                    }
+                    let items: Vec<Value> = (0..db.llen(&name)).filter_map(|pos| db.lget(&name, pos)).collect();
+                    Ok((200, Some(Value::Array(items))))
+                },
+                Method::Put => {
+                    let items = match HttpServer::body(request)? {
+                        Value::Array(items) => items,
+                        _ => return Err(error(400, String::from("the body of a list must be a JSON array"))),
+                    };
+                    // the list is replaced only if all the items are valid
+                    self.lock().lcreate_with(&name, &items).map_err(|err| error(422, err.to_string()))?;
+                    Ok(no_content())
+                },
+                Method::Delete => {
+                    let mut db = self.lock();
+                    if !db.lexists(&name) {
+                        return Err(error(404, format!("list '{}' not found", name)));
+                    }
+                    db.lrem_list(&name);
+                    Ok(no_content())
+                },
+                _ => Err(error(405, format!("{} isn't allowed on lists", method))),
+            };
+        }
+        Err(error(404, format!("{} not found", path)))
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use crate::PickleDb;
+use crate::error::Error;
+
+/// A typed handle to a list stored in a [PickleDb](struct.PickleDb.html).
+///
+/// A `TypedList<T>` is returned by [PickleDb::list()](struct.PickleDb.html#method.list). All items in the list
+/// were verified to be of type `T` when the handle was created, so items can be read and written
+/// without giving the type on every call. The handle borrows the DB mutably, which means nothing else
+/// can modify the list while the handle is alive.
+pub struct TypedList<'a, T> {
+    db: &'a mut PickleDb,
+    name: String,
+    item_type: PhantomData<T>,
+}
+
+impl<'a, T> TypedList<'a, T>
+    where
+        T: Serialize + DeserializeOwned
+{
+    pub(crate) fn new(db: &'a mut PickleDb, name: &str) -> Result<TypedList<'a, T>, Error> {
+        if db.map.contains_key(name) {
+            return Err(Error::TypeMismatch {
+                key: String::from(name),
+                reason: String::from("key holds a value and not a list"),
+            });
+        }
+
+        match db.list_map.get(name) {
+            Some(list) => {
+                for (pos, item) in list.iter().enumerate() {
+                    if let Err(err) = T::deserialize(item) {
+                        return Err(Error::TypeMismatch {
+                            key: String::from(name),
+                            reason: format!("item in position {}: {}", pos, err),
+                        });
+                    }
+                }
+            },
+
+            None => db.try_lcreate(name)?,
+        }
+
+        Ok(TypedList { db, name: String::from(name), item_type: PhantomData })
+    }
+
+    /// Get the name of the list.
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the item in a certain position, or `None` if the position is out of bounds.
+    ///
+    pub fn get(&self, pos: usize) -> Option<T> {
+        self.db.lget(&self.name, pos)
+    }
+
+    /// Add an item to the end of the list.
+    ///
+    /// An error is returned if the item doesn't conform to the schema of the list.
+    /// Please see [PickleDb::ladd()](struct.PickleDb.html#method.ladd) for more details.
+    ///
+    pub fn push(&mut self, value: &T) -> Result<(), Error> {
+        self.db.ladd(&self.name, value).map(|_| ())
+    }
+
+    /// Add multiple items to the end of the list.
+    ///
+    /// An error is returned if one of the items doesn't conform to the schema of the list.
+    /// Please see [PickleDb::lextend()](struct.PickleDb.html#method.lextend) for more details.
+    ///
+    pub fn extend(&mut self, seq: &[T]) -> Result<(), Error> {
+        self.db.lextend_in(None, &self.name, seq).map(|_| ())
+    }
+
+    /// Remove the item in a certain position and return it, or `None` if the position is out of bounds.
+    ///
+    pub fn remove(&mut self, pos: usize) -> Option<T> {
+        self.db.lpop(&self.name, pos)
+    }
+
+    /// Get the number of items in the list.
+    ///
+    pub fn len(&self) -> usize {
+        self.db.llen(&self.name)
+    }
+
+    /// Check if the list is empty.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the items of the list.
+    ///
+    /// Since items are stored as JSON values every item returned by the iterator is
+    /// a new instance and not a reference to the item stored in the DB.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        self.db.list_map.get(&self.name)
+            .into_iter()
+            .flatten()
+            .filter_map(|item| T::deserialize(item).ok())
+    }
+}
+
+/// A typed handle to a bucket stored in a [PickleDb](struct.PickleDb.html).
+///
+/// A bucket is a map from string keys to values of the same type `T`, which is stored as a single value
+/// under the bucket name. A `TypedBucket<T>` is returned by [PickleDb::bucket()](struct.PickleDb.html#method.bucket).
+/// All values in the bucket were verified to be of type `T` when the handle was created.
+/// The handle borrows the DB mutably, which means nothing else can modify the bucket while the handle is alive.
+pub struct TypedBucket<'a, T> {
+    db: &'a mut PickleDb,
+    name: String,
+    value_type: PhantomData<T>,
+}
+
+impl<'a, T> TypedBucket<'a, T>
+    where
+        T: Serialize + DeserializeOwned
+{
+    pub(crate) fn new(db: &'a mut PickleDb, name: &str) -> Result<TypedBucket<'a, T>, Error> {
+        if db.list_map.contains_key(name) {
+            return Err(Error::TypeMismatch {
+                key: String::from(name),
+                reason: String::from("key holds a list and not a bucket"),
+            });
+        }
+
+        match db.map.get(name) {
+            Some(bucket) => {
+                if let Err(err) = HashMap::<String, T>::deserialize(bucket) {
+                    return Err(Error::TypeMismatch { key: String::from(name), reason: err.to_string() });
+                }
+            },
+
+            None => db.set(name, &HashMap::<String, T>::new())?,
+        }
+
+        Ok(TypedBucket { db, name: String::from(name), value_type: PhantomData })
+    }
+
+    /// The entries of the bucket, or `None` if the bucket was replaced by a value which isn't an object since the handle
+    /// was created, for example by a before-mutation hook
+    fn entries(&self) -> Option<&serde_json::Map<String, Value>> {
+        match self.db.map.get(&self.name) {
+            Some(Value::Object(entries)) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// A copy of the entries of the bucket, in order to change them
+    fn entries_to_change(&self) -> Result<serde_json::Map<String, Value>, Error> {
+        self.entries().cloned().ok_or_else(|| Error::TypeMismatch {
+            key: self.name.clone(),
+            reason: String::from("key doesn't hold a bucket anymore"),
+        })
+    }
+
+    /// Get the name of the bucket.
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the value of a key in the bucket, or `None` if the key doesn't exist.
+    ///
+    pub fn get(&self, key: &str) -> Option<T> {
+        match self.entries().and_then(|entries| entries.get(key)) {
+            Some(val) => T::deserialize(val).ok(),
+            None => None,
+        }
+    }
+
+    /// Set a key-value pair in the bucket. If the key already exists its value is overridden.
+    ///
+    /// The bucket is a single value in the DB, so an error is returned if setting it violates a unique index.
+    /// An [Error::TypeMismatch](error/enum.Error.html#variant.TypeMismatch) is returned if a before-mutation hook
+    /// replaced the bucket by a value which isn't a bucket. Please see [PickleDb::set()](struct.PickleDb.html#method.set)
+    /// for more details.
+    ///
+    pub fn set(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        let mut entries = self.entries_to_change()?;
+        entries.insert(String::from(key), serde_json::to_value(value)?);
+        self.db.set(&self.name, &entries)
+    }
+
+    /// Remove a key from the bucket and return its value, or `None` if the key doesn't exist.
+    ///
+    /// The bucket is a single value in the DB, so an error is returned if changing it violates a unique index, or if
+    /// the bucket was replaced by a value which isn't a bucket.
+    ///
+    pub fn remove(&mut self, key: &str) -> Result<Option<T>, Error> {
+        let mut entries = self.entries_to_change()?;
+        match entries.remove(key) {
+            Some(res) => {
+                self.db.set(&self.name, &entries)?;
+                Ok(T::deserialize(res).ok())
+            },
+
+            None => Ok(None),
+        }
+    }
+
+    /// Check if a key exists in the bucket.
+    ///
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries().is_some_and(|entries| entries.contains_key(key))
+    }
+
+    /// Get the number of key-value pairs in the bucket.
+    ///
+    pub fn len(&self) -> usize {
+        self.entries().map_or(0, |entries| entries.len())
+    }
+
+    /// Check if the bucket is empty.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the key-value pairs of the bucket, in no particular order.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = (String, T)> + '_ {
+        self.entries()
+            .into_iter()
+            .flatten()
+            .filter_map(|(key, val)| T::deserialize(val).ok().map(|val| (key.clone(), val)))
+    }
+}
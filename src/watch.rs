@@ -0,0 +1,260 @@
+//! Notifications about changes of keys and lists in a PickleDB.
+//!
+//! [PickleDb::subscribe()](../struct.PickleDb.html#method.subscribe) returns a [Subscription](struct.Subscription.html)
+//! which receives a [ChangeEvent](struct.ChangeEvent.html) for every change of a key or list whose name matches
+//! the subscription pattern. A pattern is a glob in which `*` matches any sequence of characters and `?` matches
+//! a single character, for example `config.*` or `user:??`. A pattern without wildcards matches only the key itself.
+//!
+//! Every subscription buffers a bounded number of events. If the subscriber doesn't keep up and the buffer fills up,
+//! the oldest events are dropped and the next receive returns [RecvError::Lagged](enum.RecvError.html#variant.Lagged)
+//! with the number of events that were missed. Receiving then continues from the oldest event that is still buffered.
+//! Once the DB is dropped, the remaining events can still be received, after which
+//! [RecvError::Closed](enum.RecvError.html#variant.Closed) is returned.
+//!
+//! A subscription made by the DB receives only changes of the keys and lists of the DB itself. Changes inside a namespace
+//! are received by subscriptions made by [Namespace::subscribe()](../struct.Namespace.html#method.subscribe).
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use serde_json::Value;
+
+/// The number of events a subscription buffers when created by
+/// [PickleDb::subscribe()](../struct.PickleDb.html#method.subscribe)
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// The kind of change an event reports
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// A value was set by [PickleDb::set()](../struct.PickleDb.html#method.set)
+    Set,
+    /// A value or a list was removed by [PickleDb::rem()](../struct.PickleDb.html#method.rem)
+    Removed,
+    /// A list was created by [PickleDb::lcreate()](../struct.PickleDb.html#method.lcreate)
+    ListCreated,
+    /// An item was added to a list by [PickleDb::ladd()](../struct.PickleDb.html#method.ladd) or
+    /// [PickleDb::lextend()](../struct.PickleDb.html#method.lextend). Every item is reported by a separate event
+    ListPush,
+    /// An item was popped out of a list by [PickleDb::lpop()](../struct.PickleDb.html#method.lpop)
+    ListPop,
+    /// An item was removed from a list by [PickleDb::lrem_value()](../struct.PickleDb.html#method.lrem_value)
+    ListRemoveValue,
+    /// A list was removed by [PickleDb::lrem_list()](../struct.PickleDb.html#method.lrem_list)
+    ListRemoved,
+    /// A key or a list was changed in the DB file by someone else and reloaded by
+    /// [PickleDb::reload()](../struct.PickleDb.html#method.reload)
+    Reloaded,
+}
+
+/// A change of a key or a list.
+///
+/// `old` and `new` hold the value before and after the change: for `Set` and `Removed` these are the values of the key
+/// (a list that was overridden or removed is given as a JSON array), for `ListPush`, `ListPop` and `ListRemoveValue`
+/// these are the item that was added or removed, and for `ListCreated` and `ListRemoved` these are the whole list.
+/// For `Reloaded` these are the value or list (given as a JSON array) before and after the reload.
+/// `None` means there was no value before the change or there is none after it.
+/// `namespace` is the namespace the key or list is in, or `None` if it's a key or list of the DB itself
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChangeEvent {
+    pub key: String,
+    pub kind: ChangeKind,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+    pub namespace: Option<String>,
+}
+
+/// The reasons receiving an event may fail
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvError {
+    /// No event is available yet ([try_recv()](struct.Subscription.html#method.try_recv)),
+    /// or none arrived before the timeout ([recv_timeout()](struct.Subscription.html#method.recv_timeout))
+    Empty,
+    /// The buffer of the subscription filled up and this number of the oldest events was dropped
+    Lagged(u64),
+    /// The DB was dropped and all events were received
+    Closed,
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecvError::Empty => write!(f, "no event is available"),
+            RecvError::Lagged(missed) => write!(f, "subscription lagged behind and missed {} events", missed),
+            RecvError::Closed => write!(f, "the DB was dropped"),
+        }
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+struct State {
+    events: VecDeque<ChangeEvent>,
+    capacity: usize,
+    missed: u64,
+    closed: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    ready: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The receiving end of a subscription to changes in a DB. Please see the [module documentation](index.html) for details
+pub struct Subscription {
+    pattern: String,
+    shared: Arc<Shared>,
+}
+
+impl Subscription {
+    /// Get the pattern of keys the subscription receives events for.
+    ///
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    fn take(state: &mut State) -> Result<ChangeEvent, RecvError> {
+        if state.missed > 0 {
+            let missed = state.missed;
+            state.missed = 0;
+            return Err(RecvError::Lagged(missed));
+        }
+        match state.events.pop_front() {
+            Some(event) => Ok(event),
+            None if state.closed => Err(RecvError::Closed),
+            None => Err(RecvError::Empty),
+        }
+    }
+
+    /// Receive the next event without blocking.
+    ///
+    /// [RecvError::Empty](enum.RecvError.html#variant.Empty) is returned if no event is available.
+    ///
+    pub fn try_recv(&self) -> Result<ChangeEvent, RecvError> {
+        Subscription::take(&mut self.shared.lock())
+    }
+
+    /// Receive the next event, blocking until one is available.
+    ///
+    /// Since the DB is changed by its owner, this is useful when the subscription is moved to another thread.
+    /// [RecvError::Closed](enum.RecvError.html#variant.Closed) is returned once the DB is dropped and all events were received.
+    ///
+    pub fn recv(&self) -> Result<ChangeEvent, RecvError> {
+        let mut state = self.shared.lock();
+        loop {
+            match Subscription::take(&mut state) {
+                Err(RecvError::Empty) => {
+                    state = self.shared.ready.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+                },
+                res => return res,
+            }
+        }
+    }
+
+    /// Receive the next event, blocking until one is available or until `timeout` passes.
+    ///
+    /// [RecvError::Empty](enum.RecvError.html#variant.Empty) is returned if no event arrived in time.
+    ///
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<ChangeEvent, RecvError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.lock();
+        loop {
+            match Subscription::take(&mut state) {
+                Err(RecvError::Empty) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(RecvError::Empty);
+                    }
+                    state = self.shared.ready.wait_timeout(state, deadline - now)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .0;
+                },
+                res => return res,
+            }
+        }
+    }
+}
+
+/// Match a key against a glob pattern in which `*` matches any sequence of characters and `?` matches a single character
+pub(crate) fn matches(pattern: &str, key: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let key: Vec<char> = key.chars().collect();
+
+    // the position of the last `*` in the pattern and the position in the key it was matched at,
+    // so the match can be retried with the `*` consuming one more character
+    let mut backtrack: Option<(usize, usize)> = None;
+    let (mut p, mut k) = (0, 0);
+    while k < key.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == key[k]) {
+            p += 1;
+            k += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, k));
+            p += 1;
+        } else if let Some((star, star_k)) = backtrack {
+            backtrack = Some((star, star_k + 1));
+            p = star + 1;
+            k = star_k + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+struct Subscriber {
+    namespace: Option<String>,
+    pattern: String,
+    shared: Arc<Shared>,
+}
+
+/// The subscriptions of a DB, which changes are reported to
+#[derive(Default)]
+pub(crate) struct Watchers {
+    subscribers: Vec<Subscriber>,
+}
+
+impl Watchers {
+    pub(crate) fn subscribe(&mut self, ns: Option<&str>, pattern: &str, capacity: usize) -> Subscription {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State { events: VecDeque::new(), capacity: capacity.max(1), missed: 0, closed: false }),
+            ready: Condvar::new(),
+        });
+        self.subscribers.push(Subscriber { namespace: ns.map(String::from), pattern: String::from(pattern), shared: shared.clone() });
+        Subscription { pattern: String::from(pattern), shared }
+    }
+
+    /// Report a change to all subscriptions of the namespace `ns` (or of the DB itself) whose pattern matches `key`.
+    /// The values are cloned only if there is such a subscription
+    pub(crate) fn notify(&mut self, ns: Option<&str>, key: &str, kind: ChangeKind, old: Option<&Value>, new: Option<&Value>) {
+        // subscriptions that were dropped don't need events anymore
+        self.subscribers.retain(|subscriber| Arc::strong_count(&subscriber.shared) > 1);
+
+        let subscribers = self.subscribers.iter()
+            .filter(|subscriber| subscriber.namespace.as_deref() == ns && matches(&subscriber.pattern, key));
+        for subscriber in subscribers {
+            let event = ChangeEvent { key: String::from(key), kind, old: old.cloned(), new: new.cloned(), namespace: ns.map(String::from) };
+            let mut state = subscriber.shared.lock();
+            if state.events.len() == state.capacity {
+                state.events.pop_front();
+                state.missed += 1;
+            }
+            state.events.push_back(event);
+            subscriber.shared.ready.notify_all();
+        }
+    }
+
+    /// Close all subscriptions, after the DB is dropped
+    pub(crate) fn close(&mut self) {
+        for subscriber in self.subscribers.drain(..) {
+            subscriber.shared.lock().closed = true;
+            subscriber.shared.ready.notify_all();
+        }
+    }
+}
@@ -1,12 +1,14 @@
 use pickledb::{PickleDb, PickleDbDumpPolicy};
 use std::iter;
 use std::collections::HashMap;
+use std::fs;
 use rand::{Rng, thread_rng};
 use rand::distributions::Alphanumeric;
 use rand::seq::SliceRandom;
 
 mod common;
 
+#[macro_use]
 extern crate serde_derive;
 
 #[test]
@@ -16,17 +18,17 @@ fn lists_and_values() {
     let mut db = PickleDb::new("lists_and_values.db", PickleDbDumpPolicy::AutoDump);
 
     // set a few values
-    db.set("key1", &String::from("val1"));
-    db.set("key2", &1);
-    db.set("key3", &vec![1,2,3]);
+    db.set("key1", &String::from("val1")).unwrap();
+    db.set("key2", &1).unwrap();
+    db.set("key3", &vec![1,2,3]).unwrap();
 
     // create a few lists and add values to them
     db.lcreate("list1");
-    db.lextend("list1", &vec![1,2,3]);
+    db.lextend("list1", &vec![1,2,3]).unwrap();
 
     db.lcreate("list2");
-    db.ladd("list2", &1.1);
-    db.ladd("list2", &String::from("some val"));
+    db.ladd("list2", &1.1).unwrap();
+    db.ladd("list2", &String::from("some val")).unwrap();
 
     // read keys and lists
     {
@@ -41,7 +43,7 @@ fn lists_and_values() {
     }
 
     // create key and list with the same name, make sure they override one another
-    db.set("key_or_list1", &1);
+    db.set("key_or_list1", &1).unwrap();
     db.lcreate("key_or_list1");
 
     assert!(db.exists("key_or_list1"));
@@ -49,7 +51,7 @@ fn lists_and_values() {
     assert!(db.lexists("key_or_list1"));
 
     // now set the key again and verify list is removed
-    db.set("key_or_list1", &2);
+    db.set("key_or_list1", &2).unwrap();
 
     assert!(db.exists("key_or_list1"));
     assert_eq!(db.get::<i32>("key_or_list1").unwrap(), 2);
@@ -65,6 +67,7 @@ fn gen_random_string<T: Rng>(rng: &mut T, size: usize) -> String {
 }
 
 #[test]
+#[allow(clippy::useless_conversion, clippy::needless_borrow, clippy::match_ref_pats, clippy::unnecessary_get_then_check, non_fmt_panics)]
 fn load_test() {
     set_test_rsc!("load_test.db");
 
@@ -96,16 +99,16 @@ fn load_test() {
         // randomly choose a type
         match possible_value_types.choose(&mut rng).unwrap() {
             1 => { // add a i32 value
-                db.set(&key, &rng.gen::<i32>());
+                db.set(&key, &rng.gen::<i32>()).unwrap();
                 map.insert(String::from(key), "i32");
             },
             2 => { // add a f32 value
-                db.set(&key, &rng.gen::<f32>());
+                db.set(&key, &rng.gen::<f32>()).unwrap();
                 map.insert(String::from(key), "f32");
             },
             3 => { // add a String value
                 let val_size = rng.gen_range(1, 50);
-                db.set(&key, &gen_random_string(&mut rng, val_size));
+                db.set(&key, &gen_random_string(&mut rng, val_size)).unwrap();
                 map.insert(String::from(key), "string");
             },
             4 => { // add a Vec<i32> value
@@ -117,7 +120,7 @@ fn load_test() {
                 for _ in 1..vec_size {
                     vec.push(rng.gen::<i32>());
                 }
-                db.set(&key, &vec);
+                db.set(&key, &vec).unwrap();
                 map.insert(String::from(key), "vec");
             },
             5 => { // add a List value
@@ -140,14 +143,14 @@ fn load_test() {
                     // randomly choose an element type: [i32, f32, String, Vec]
                     match possible_value_types[..4].choose(&mut rng).unwrap() {
                         1 => { // add a i32 element to the list
-                            assert!(db.ladd(&list_key, &rng.gen::<i32>()))
+                            assert!(db.ladd(&list_key, &rng.gen::<i32>()).unwrap())
                         },
                         2 => { // add a f32 element to the list
-                            assert!(db.ladd(&list_key, &rng.gen::<f32>()));
+                            assert!(db.ladd(&list_key, &rng.gen::<f32>()).unwrap());
                         }, 
                         3 => { // add a String element to the list
                             let val_size = rng.gen_range(1, 50);
-                            assert!(db.ladd(&list_key, &gen_random_string(&mut rng, val_size)));
+                            assert!(db.ladd(&list_key, &gen_random_string(&mut rng, val_size)).unwrap());
                         },
                         4 => { // add a Vec<i32> element to the list
                             // randomize vec size 1..10
@@ -160,7 +163,7 @@ fn load_test() {
                             }
 
                             // add vec as an element to the list
-                            assert!(db.ladd(&list_key, &vec));
+                            assert!(db.ladd(&list_key, &vec).unwrap());
                         },
                         _ => panic!("Cannot add list inside a list!"),
                     }
@@ -198,4 +201,55 @@ fn load_test() {
 
     // check that the total number of keys in db equals to number of keys generated
     assert_eq!(read_db.total_keys(), generate_keys);
-}
\ No newline at end of file
+}
+
+#[test]
+fn dump_file_is_plain_json() {
+    set_test_rsc!("dump_file_is_plain_json.db");
+
+    #[derive(Serialize, Deserialize)]
+    struct Rectangle {
+        width: i32,
+        length: i32,
+    }
+
+    let mut db = PickleDb::new("dump_file_is_plain_json.db", PickleDbDumpPolicy::AutoDump);
+    db.set("rect", &Rectangle { width: 1, length: 2 }).unwrap();
+    db.lcreate("list1");
+    db.lextend("list1", &vec![String::from("aa"), String::from("bb")]).unwrap();
+
+    // values are stored as nested JSON and not as escaped strings
+    let contents = fs::read_to_string("dump_file_is_plain_json.db").unwrap();
+    let file_as_json: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    assert_eq!(file_as_json["map"]["rect"]["width"], 1);
+    assert_eq!(file_as_json["list_map"]["list1"][1], "bb");
+}
+
+#[test]
+fn load_legacy_format() {
+    set_test_rsc!("load_legacy_format.db");
+
+    // a file in which every value is an escaped JSON string
+    fs::write("load_legacy_format.db",
+        r#"[{"key1":"100","key2":"\"hello\"","key3":"{\"width\":1,\"length\":2}"},{"list1":["1","\"two\"","[3,4]"]}]"#).unwrap();
+
+    {
+        let mut db = PickleDb::load("load_legacy_format.db", PickleDbDumpPolicy::DumpUponRequest).unwrap();
+        assert_eq!(db.get::<i32>("key1").unwrap(), 100);
+        assert_eq!(db.get::<String>("key2").unwrap(), "hello");
+        assert_eq!(db.get::<HashMap<String, i32>>("key3").unwrap()["length"], 2);
+        assert_eq!(db.lget::<i32>("list1", 0).unwrap(), 1);
+        assert_eq!(db.lget::<String>("list1", 1).unwrap(), "two");
+        assert_eq!(db.lget::<Vec<i32>>("list1", 2).unwrap(), vec![3, 4]);
+
+        // dumping the DB writes the new format
+        assert!(db.dump());
+    }
+
+    let contents = fs::read_to_string("load_legacy_format.db").unwrap();
+    assert!(contents.starts_with('{'));
+
+    let db = PickleDb::load_read_only("load_legacy_format.db").unwrap();
+    assert_eq!(db.get::<String>("key2").unwrap(), "hello");
+    assert_eq!(db.lget::<Vec<i32>>("list1", 2).unwrap(), vec![3, 4]);
+}
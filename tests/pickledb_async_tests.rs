@@ -0,0 +1,236 @@
+#![cfg(feature = "tokio")]
+
+use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::async_db::AsyncPickleDb;
+
+mod common;
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+fn read_file(db_file: &str) -> PickleDb {
+    PickleDb::load_read_only(db_file).unwrap()
+}
+
+#[test]
+fn async_auto_dump() {
+    set_test_rsc!("async_auto_dump.db");
+
+    block_on(async {
+        let db = AsyncPickleDb::new("async_auto_dump.db", PickleDbDumpPolicy::AutoDump);
+        db.set("key1", &1).await.unwrap();
+        assert_eq!(read_file("async_auto_dump.db").get::<i32>("key1"), Some(1));
+
+        db.lcreate("list1").await.unwrap();
+        assert!(db.lextend("list1", &[1, 2, 3]).await.unwrap());
+        assert!(db.ladd("list1", &4).await.unwrap());
+        assert_eq!(db.lpop::<i32>("list1", 0).await.unwrap(), Some(1));
+        assert!(db.lrem_value("list1", &2).await.unwrap());
+        assert_eq!(read_file("async_auto_dump.db").llen("list1"), 2);
+
+        assert!(db.rem("key1").await.unwrap());
+        assert_eq!(db.lrem_list("list1").await.unwrap(), 2);
+        assert_eq!(read_file("async_auto_dump.db").total_keys(), 0);
+    });
+}
+
+#[test]
+fn async_concurrent_changes() {
+    set_test_rsc!("async_concurrent_changes.db");
+
+    block_on(async {
+        let db = AsyncPickleDb::new("async_concurrent_changes.db", PickleDbDumpPolicy::AutoDump);
+        let tasks: Vec<_> = (0..50)
+            .map(|i| {
+                let db = db.clone();
+                tokio::spawn(async move { db.set(&format!("key{}", i), &i).await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert_eq!(db.total_keys(), 50);
+        let read = read_file("async_concurrent_changes.db");
+        assert_eq!(read.total_keys(), 50);
+        assert_eq!(read.get::<i32>("key49"), Some(49));
+    });
+}
+
+#[test]
+fn async_dump_upon_request() {
+    set_test_rsc!("async_dump_upon_request.db");
+
+    block_on(async {
+        let db = AsyncPickleDb::new("async_dump_upon_request.db", PickleDbDumpPolicy::DumpUponRequest);
+        db.set("key1", &"value").await.unwrap();
+        assert!(!Path::new("async_dump_upon_request.db").exists());
+
+        db.dump().await.unwrap();
+        assert_eq!(read_file("async_dump_upon_request.db").get::<String>("key1"), Some(String::from("value")));
+
+        // dropping the DB dumps the changes that weren't dumped yet
+        db.set("key2", &2).await.unwrap();
+        drop(db);
+        assert_eq!(read_file("async_dump_upon_request.db").get::<i32>("key2"), Some(2));
+    });
+}
+
+#[test]
+fn async_periodic_dump() {
+    set_test_rsc!("async_periodic_dump.db");
+
+    block_on(async {
+        let db = AsyncPickleDb::new("async_periodic_dump.db", PickleDbDumpPolicy::PeriodicDump(Duration::from_millis(50)));
+        db.set("key1", &1).await.unwrap();
+        assert!(!Path::new("async_periodic_dump.db").exists());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(read_file("async_periodic_dump.db").get::<i32>("key1"), Some(1));
+
+        db.set("key1", &2).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(read_file("async_periodic_dump.db").get::<i32>("key1"), Some(2));
+    });
+}
+
+#[test]
+fn async_never_dump() {
+    set_test_rsc!("async_never_dump.db");
+
+    block_on(async {
+        let db = AsyncPickleDb::new("async_never_dump.db", PickleDbDumpPolicy::NeverDump);
+        db.set("key1", &1).await.unwrap();
+        db.dump().await.unwrap();
+        drop(db);
+        assert!(!Path::new("async_never_dump.db").exists());
+    });
+}
+
+#[test]
+fn async_load_and_update() {
+    set_test_rsc!("async_load_and_update.db");
+
+    let mut db = PickleDb::new("async_load_and_update.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &1).unwrap();
+    db.lcreate("list1");
+    db.ladd("list1", &"item").unwrap();
+
+    block_on(async {
+        assert!(AsyncPickleDb::load("no_such_file.db", PickleDbDumpPolicy::AutoDump).await.is_err());
+
+        let db = AsyncPickleDb::load("async_load_and_update.db", PickleDbDumpPolicy::AutoDump).await.unwrap();
+        assert_eq!(db.get::<i32>("key1"), Some(1));
+        assert!(db.exists("list1"));
+        assert!(db.lexists("list1"));
+        assert_eq!(db.lget::<String>("list1", 0), Some(String::from("item")));
+        let mut keys = db.get_all();
+        keys.sort();
+        assert_eq!(keys, vec!["key1", "list1"]);
+
+        let total = db.update(|db| {
+            db.set("key2", &2).unwrap();
+            db.total_keys()
+        }).await.unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(db.read(|db| db.get::<i32>("key2")), Some(2));
+        assert_eq!(read_file("async_load_and_update.db").get::<i32>("key2"), Some(2));
+
+        // changes of a read-only DB aren't dumped
+        let read_only = AsyncPickleDb::load_read_only("async_load_and_update.db").await.unwrap();
+        read_only.set("key3", &3).await.unwrap();
+        drop(read_only);
+        assert!(!read_file("async_load_and_update.db").exists("key3"));
+    });
+}
+
+#[test]
+fn async_dump_every_n_writes() {
+    set_test_rsc!("async_dump_every_n_writes.db");
+
+    block_on(async {
+        let db = AsyncPickleDb::new("async_dump_every_n_writes.db", PickleDbDumpPolicy::DumpEveryNWrites(2));
+        db.set("key1", &1).await.unwrap();
+        assert!(!Path::new("async_dump_every_n_writes.db").exists());
+        db.set("key2", &2).await.unwrap();
+        assert_eq!(read_file("async_dump_every_n_writes.db").total_keys(), 2);
+        db.set("key3", &3).await.unwrap();
+        assert_eq!(read_file("async_dump_every_n_writes.db").total_keys(), 2);
+        db.dump().await.unwrap();
+        db.set("key4", &4).await.unwrap();
+        assert_eq!(read_file("async_dump_every_n_writes.db").total_keys(), 3);
+    });
+}
+
+#[test]
+fn async_dump_after_bytes_changed() {
+    set_test_rsc!("async_dump_after_bytes_changed.db");
+
+    block_on(async {
+        let db = AsyncPickleDb::new("async_dump_after_bytes_changed.db", PickleDbDumpPolicy::Any(vec![
+            PickleDbDumpPolicy::DumpAfterBytesChanged(50),
+            PickleDbDumpPolicy::DumpEveryNWrites(100),
+        ]));
+        db.set("key1", &1).await.unwrap();
+        assert!(!Path::new("async_dump_after_bytes_changed.db").exists());
+        db.set("key2", &"x".repeat(50)).await.unwrap();
+        assert_eq!(read_file("async_dump_after_bytes_changed.db").total_keys(), 2);
+    });
+}
+
+#[test]
+fn async_backup_rotation() {
+    set_test_rsc!("async_backup_rotation.db");
+    set_test_rsc!("async_backup_rotation.db.1");
+
+    block_on(async {
+        let db = AsyncPickleDb::new("async_backup_rotation.db", PickleDbDumpPolicy::AutoDump);
+        db.update(|db| db.set_backup_rotation(1)).await.unwrap();
+        db.set("key1", &1).await.unwrap();
+        db.set("key2", &2).await.unwrap();
+        assert_eq!(read_file("async_backup_rotation.db.1").total_keys(), 1);
+        assert_eq!(read_file("async_backup_rotation.db").total_keys(), 2);
+    });
+}
+
+#[test]
+fn async_update_without_changes() {
+    set_test_rsc!("async_update_without_changes.db");
+
+    block_on(async {
+        let db = AsyncPickleDb::new("async_update_without_changes.db", PickleDbDumpPolicy::AutoDump);
+        db.set("key1", &1).await.unwrap();
+        std::fs::remove_file("async_update_without_changes.db").unwrap();
+
+        // reading or removing nothing doesn't dump the DB
+        assert_eq!(db.update(|db| db.get::<i32>("key1")).await.unwrap(), Some(1));
+        assert!(!db.rem("no_such_key").await.unwrap());
+        assert!(!Path::new("async_update_without_changes.db").exists());
+
+        assert!(!db.lextend("list1", &[1, 2]).await.unwrap());
+        assert!(!Path::new("async_update_without_changes.db").exists());
+        db.update(|db| db.lcreate("list1")).await.unwrap();
+        assert!(db.lextend("list1", &[1, 2]).await.unwrap());
+        assert_eq!(read_file("async_update_without_changes.db").llen("list1"), 2);
+    });
+}
+
+#[test]
+fn async_failed_dump_is_retried() {
+    block_on(async {
+        let db = AsyncPickleDb::new("no_such_dir/async_failed_dump_is_retried.db", PickleDbDumpPolicy::DumpEveryNWrites(2));
+        db.set("key1", &1).await.unwrap();
+        assert!(db.set("key2", &2).await.is_err());
+
+        // the changes weren't dumped, so they're still counted
+        assert!(db.set("key3", &3).await.is_err());
+    });
+}
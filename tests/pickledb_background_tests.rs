@@ -0,0 +1,95 @@
+use std::path::Path;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::error::Error;
+
+mod common;
+
+#[test]
+fn background_dump_writes_changes() {
+    set_test_rsc!("background_dump_writes_changes.db");
+
+    let mut db = PickleDb::new("background_dump_writes_changes.db", PickleDbDumpPolicy::AutoDump);
+    db.set_background_dump(true).unwrap();
+
+    db.lcreate("list1");
+    for i in 0..200 {
+        db.set(&format!("key{}", i), &i).unwrap();
+        db.ladd("list1", &i).unwrap();
+    }
+    db.namespace("ns").set("key1", &"in a namespace").unwrap();
+    db.set("key0", &"last").unwrap();
+    db.flush().unwrap();
+
+    let mut read = PickleDb::load_read_only("background_dump_writes_changes.db").unwrap();
+    assert_eq!(read.total_keys(), 201);
+    assert_eq!(read.get::<String>("key0").unwrap(), "last");
+    assert_eq!(read.get::<i32>("key199").unwrap(), 199);
+    assert_eq!(read.llen("list1"), 200);
+    assert_eq!(read.namespace("ns").get::<String>("key1").unwrap(), "in a namespace");
+}
+
+#[test]
+fn background_dump_writes_snapshot() {
+    set_test_rsc!("background_dump_writes_snapshot.db");
+
+    let mut db = PickleDb::new("background_dump_writes_snapshot.db", PickleDbDumpPolicy::DumpUponRequest);
+    db.set_background_dump(true).unwrap();
+    db.set("key1", &1).unwrap();
+    db.lcreate("list1");
+    db.ladd("list1", &1).unwrap();
+    assert!(db.dump());
+
+    // changes after the dump aren't part of its snapshot
+    db.set("key1", &2).unwrap();
+    db.set("key2", &2).unwrap();
+    db.ladd("list1", &2).unwrap();
+    db.flush().unwrap();
+
+    let read = PickleDb::load_read_only("background_dump_writes_snapshot.db").unwrap();
+    assert_eq!(read.get::<i32>("key1").unwrap(), 1);
+    assert!(!read.exists("key2"));
+    assert_eq!(read.llen("list1"), 1);
+
+    // dropping the DB writes the changes that weren't dumped yet
+    drop(db);
+    let read = PickleDb::load_read_only("background_dump_writes_snapshot.db").unwrap();
+    assert_eq!(read.get::<i32>("key1").unwrap(), 2);
+    assert_eq!(read.llen("list1"), 2);
+}
+
+#[test]
+fn background_dump_reports_errors() {
+    let mut db = PickleDb::new("no_such_dir/background_dump_reports_errors.db", PickleDbDumpPolicy::AutoDump);
+    db.set_background_dump(true).unwrap();
+
+    db.set("key1", &1).unwrap();
+    match db.flush() {
+        Err(Error::Io(_)) => (),
+        _ => panic!("expected an IO error"),
+    }
+    // the error is reported once
+    assert!(db.flush().is_ok());
+
+    // dropping the DB ignores errors of the background thread
+    db.set("key1", &2).unwrap();
+}
+
+#[test]
+fn background_dump_disable() {
+    set_test_rsc!("background_dump_disable.db");
+
+    let mut db = PickleDb::new("background_dump_disable.db", PickleDbDumpPolicy::AutoDump);
+    db.set_background_dump(true).unwrap();
+    db.set_background_dump(true).unwrap();
+    db.set("key1", &1).unwrap();
+
+    // disabling waits for the dumps that were handed to the background thread
+    db.set_background_dump(false).unwrap();
+    assert_eq!(PickleDb::load_read_only("background_dump_disable.db").unwrap().get::<i32>("key1").unwrap(), 1);
+
+    // from now on dumps are written before they return
+    db.set("key2", &2).unwrap();
+    assert_eq!(PickleDb::load_read_only("background_dump_disable.db").unwrap().get::<i32>("key2").unwrap(), 2);
+    assert!(db.flush().is_ok());
+    assert!(Path::new("background_dump_disable.db").exists());
+}
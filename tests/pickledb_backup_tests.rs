@@ -0,0 +1,106 @@
+use std::fs;
+use std::path::Path;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+
+mod common;
+
+fn keys_in_file(db_file: &str) -> Vec<String> {
+    let mut keys = PickleDb::load_read_only(db_file).unwrap().get_all();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn backup_to_writes_copy() {
+    set_test_rsc!("backup_to_writes_copy.db");
+    set_test_rsc!("backup_to_writes_copy.bak");
+
+    let mut db = PickleDb::new("backup_to_writes_copy.db", PickleDbDumpPolicy::DumpUponRequest);
+    db.set("key1", &1).unwrap();
+    db.lcreate("list1");
+    db.ladd("list1", &"item").unwrap();
+
+    // changes that weren't dumped yet are part of the backup
+    db.backup_to("backup_to_writes_copy.bak").unwrap();
+    assert!(!Path::new("backup_to_writes_copy.db").exists());
+    assert!(!Path::new("backup_to_writes_copy.bak.tmp").exists());
+    assert_eq!(keys_in_file("backup_to_writes_copy.bak"), vec!["key1", "list1"]);
+
+    // the DB keeps dumping to its own file
+    db.set("key2", &2).unwrap();
+    db.dump();
+    assert_eq!(keys_in_file("backup_to_writes_copy.db"), vec!["key1", "key2", "list1"]);
+    assert_eq!(keys_in_file("backup_to_writes_copy.bak"), vec!["key1", "list1"]);
+
+    assert!(db.backup_to("no_such_dir/backup_to_writes_copy.bak").is_err());
+}
+
+#[test]
+fn backup_rotation() {
+    set_test_rsc!("backup_rotation.db");
+    set_test_rsc!("backup_rotation.db.1");
+    set_test_rsc!("backup_rotation.db.2");
+    set_test_rsc!("backup_rotation.db.3");
+
+    let mut db = PickleDb::new("backup_rotation.db", PickleDbDumpPolicy::AutoDump);
+    db.set_backup_rotation(2);
+
+    // there is nothing to back up before the first dump
+    db.set("key1", &1).unwrap();
+    assert!(!Path::new("backup_rotation.db.1").exists());
+
+    db.set("key2", &2).unwrap();
+    assert_eq!(keys_in_file("backup_rotation.db.1"), vec!["key1"]);
+    assert!(!Path::new("backup_rotation.db.2").exists());
+
+    db.set("key3", &3).unwrap();
+    db.set("key4", &4).unwrap();
+    assert_eq!(keys_in_file("backup_rotation.db"), vec!["key1", "key2", "key3", "key4"]);
+    assert_eq!(keys_in_file("backup_rotation.db.1"), vec!["key1", "key2", "key3"]);
+    assert_eq!(keys_in_file("backup_rotation.db.2"), vec!["key1", "key2"]);
+    assert!(!Path::new("backup_rotation.db.3").exists());
+
+    // backups are rotated by dumps in the background as well
+    db.set_background_dump(true).unwrap();
+    db.set("key5", &5).unwrap();
+    db.flush().unwrap();
+    assert_eq!(keys_in_file("backup_rotation.db.1"), vec!["key1", "key2", "key3", "key4"]);
+    assert_eq!(keys_in_file("backup_rotation.db.2"), vec!["key1", "key2", "key3"]);
+
+    // no more backups once the rotation is disabled
+    db.set_background_dump(false).unwrap();
+    db.set_backup_rotation(0);
+    db.set("key6", &6).unwrap();
+    assert_eq!(keys_in_file("backup_rotation.db.1"), vec!["key1", "key2", "key3", "key4"]);
+}
+
+#[test]
+fn restore_from_backup() {
+    set_test_rsc!("restore_from_backup.db");
+    set_test_rsc!("restore_from_backup.db.1");
+    set_test_rsc!("restore_from_backup.bad");
+
+    let mut db = PickleDb::new("restore_from_backup.db", PickleDbDumpPolicy::AutoDump);
+    db.set_backup_rotation(1);
+    db.lcreate("list1");
+    db.lextend("list1", &vec![1, 2, 3]).unwrap();
+
+    // an accidental removal
+    db.lrem_list("list1");
+    drop(db);
+    assert!(!PickleDb::load_read_only("restore_from_backup.db").unwrap().exists("list1"));
+
+    // a backup that can't be loaded doesn't replace the DB file
+    fs::write("restore_from_backup.bad", "not a DB").unwrap();
+    assert!(PickleDb::restore_from("restore_from_backup.bad", "restore_from_backup.db", PickleDbDumpPolicy::AutoDump).is_err());
+    assert!(PickleDb::restore_from("no_such_file.db", "restore_from_backup.db", PickleDbDumpPolicy::AutoDump).is_err());
+    assert!(!PickleDb::load_read_only("restore_from_backup.db").unwrap().exists("list1"));
+
+    let mut db = PickleDb::restore_from("restore_from_backup.db.1", "restore_from_backup.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    assert_eq!(db.llen("list1"), 3);
+    assert_eq!(PickleDb::load_read_only("restore_from_backup.db").unwrap().llen("list1"), 3);
+
+    // the restored DB dumps to its location
+    db.ladd("list1", &4).unwrap();
+    assert_eq!(PickleDb::load_read_only("restore_from_backup.db").unwrap().llen("list1"), 4);
+}
@@ -0,0 +1,100 @@
+use std::fs;
+use std::io::ErrorKind;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::error::Error;
+
+mod common;
+
+fn is_corrupted(err: &std::io::Error) -> bool {
+    err.kind() == ErrorKind::InvalidData && matches!(err.get_ref().and_then(|inner| inner.downcast_ref::<Error>()), Some(Error::Corrupted(_)))
+}
+
+#[test]
+fn corruption_detected() {
+    set_test_rsc!("corruption_detected.db");
+
+    let mut db = PickleDb::new("corruption_detected.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &12345).unwrap();
+    db.lcreate("list1");
+    db.lextend("list1", &vec!["a", "b"]).unwrap();
+    assert!(PickleDb::verify("corruption_detected.db").is_ok());
+
+    // a flipped bit that still yields valid JSON
+    let contents = fs::read_to_string("corruption_detected.db").unwrap();
+    fs::write("corruption_detected.db", contents.replace("12345", "12344")).unwrap();
+    match PickleDb::verify("corruption_detected.db") {
+        Err(Error::Corrupted(reason)) => assert!(reason.contains("checksum mismatch")),
+        _ => panic!("expected the corruption to be detected"),
+    }
+    assert!(is_corrupted(&PickleDb::load_read_only("corruption_detected.db").err().unwrap()));
+
+    // so is a corrupted checksum
+    let checksum_pos = contents.rfind("crc32c:").unwrap() + "crc32c:".len();
+    let mut corrupted = contents.clone();
+    corrupted.replace_range(checksum_pos..checksum_pos + 1, if contents[checksum_pos..].starts_with('0') { "1" } else { "0" });
+    fs::write("corruption_detected.db", &corrupted).unwrap();
+    assert!(matches!(PickleDb::verify("corruption_detected.db"), Err(Error::Corrupted(_))));
+
+    // a trailing newline, for example added by an editor, is fine
+    fs::write("corruption_detected.db", format!("{}\n", contents)).unwrap();
+    assert!(PickleDb::verify("corruption_detected.db").is_ok());
+    assert_eq!(PickleDb::load_read_only("corruption_detected.db").unwrap().get::<i32>("key1").unwrap(), 12345);
+}
+
+#[test]
+fn file_without_checksum() {
+    set_test_rsc!("file_without_checksum.db");
+
+    fs::write("file_without_checksum.db", r#"{"map": {"key1": 1}, "list_map": {"list1": [1, 2]}}"#).unwrap();
+    assert!(PickleDb::verify("file_without_checksum.db").is_ok());
+    let mut db = PickleDb::load("file_without_checksum.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    assert_eq!(db.llen("list1"), 2);
+
+    // the next dump adds a checksum
+    db.set("key2", &2).unwrap();
+    assert!(fs::read_to_string("file_without_checksum.db").unwrap().contains("\"checksum\":\"crc32c:"));
+    assert!(PickleDb::verify("file_without_checksum.db").is_ok());
+
+    // content that can't be parsed isn't valid either
+    fs::write("file_without_checksum.db", r#"{"map": {"key1": "#).unwrap();
+    assert!(matches!(PickleDb::verify("file_without_checksum.db"), Err(Error::Serialization(_))));
+    assert!(matches!(PickleDb::verify("no_such_file.db"), Err(Error::Io(_))));
+}
+
+#[test]
+fn reload_rejects_corruption() {
+    set_test_rsc!("reload_rejects_corruption.db");
+
+    let mut writer = PickleDb::new("reload_rejects_corruption.db", PickleDbDumpPolicy::AutoDump);
+    writer.set("key1", &"value1").unwrap();
+    let mut reader = PickleDb::load_read_only("reload_rejects_corruption.db").unwrap();
+
+    writer.set("key1", &"value2").unwrap();
+    let contents = fs::read_to_string("reload_rejects_corruption.db").unwrap();
+    fs::write("reload_rejects_corruption.db", contents.replace("value2", "value3")).unwrap();
+    assert!(matches!(reader.reload(), Err(Error::Corrupted(_))));
+    assert_eq!(reader.get::<String>("key1").unwrap(), "value1");
+
+    fs::write("reload_rejects_corruption.db", contents).unwrap();
+    assert_eq!(reader.reload().unwrap(), vec!["key1"]);
+    assert_eq!(reader.get::<String>("key1").unwrap(), "value2");
+}
+
+#[test]
+fn checksum_required_with_version() {
+    set_test_rsc!("checksum_required_with_version.db");
+
+    let mut db = PickleDb::new("checksum_required_with_version.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &1).unwrap();
+    let contents = fs::read_to_string("checksum_required_with_version.db").unwrap();
+    assert!(contents.starts_with("{\"format\":1,"));
+
+    // files that have a version can't drop their checksum
+    let checksum_pos = contents.rfind(",\"checksum\":").unwrap();
+    fs::write("checksum_required_with_version.db", format!("{}}}", &contents[..checksum_pos])).unwrap();
+    match PickleDb::verify("checksum_required_with_version.db") {
+        Err(Error::Corrupted(reason)) => assert!(reason.contains("checksum is missing")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(PickleDb::load_read_only("checksum_required_with_version.db").is_err());
+}
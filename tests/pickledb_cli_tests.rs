@@ -0,0 +1,132 @@
+use std::fs;
+use std::process::{Command, Output};
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use serde_json::json;
+
+mod common;
+
+fn pickledb(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pickledb")).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn cli_keys_and_values() {
+    set_test_rsc!("cli_keys_and_values.db");
+
+    let mut db = PickleDb::new("cli_keys_and_values.db", PickleDbDumpPolicy::AutoDump);
+    db.set("user:1", &json!({"name": "john", "age": 30})).unwrap();
+    drop(db);
+
+    assert_eq!(stdout(&pickledb(&["get", "cli_keys_and_values.db", "user:1"])), "{\n  \"age\": 30,\n  \"name\": \"john\"\n}\n");
+    stdout(&pickledb(&["set", "cli_keys_and_values.db", "user:2", r#"{"name": "jane"}"#]));
+    stdout(&pickledb(&["set", "cli_keys_and_values.db", "greeting", "hello world"]));
+    stdout(&pickledb(&["set", "cli_keys_and_values.db", "count", "5"]));
+    assert_eq!(stdout(&pickledb(&["keys", "cli_keys_and_values.db", "user:*"])), "user:1\nuser:2\n");
+    assert_eq!(stdout(&pickledb(&["keys", "cli_keys_and_values.db"])), "count\ngreeting\nuser:1\nuser:2\n");
+
+    let db = PickleDb::load_read_only("cli_keys_and_values.db").unwrap();
+    assert_eq!(db.get::<String>("greeting").unwrap(), "hello world");
+    assert_eq!(db.get::<i32>("count").unwrap(), 5);
+    assert_eq!(db.get::<serde_json::Value>("user:2").unwrap(), json!({"name": "jane"}));
+
+    stdout(&pickledb(&["rem", "cli_keys_and_values.db", "user:1"]));
+    let missing = pickledb(&["get", "cli_keys_and_values.db", "user:1"]);
+    assert_eq!(missing.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("key 'user:1' not found"));
+    assert_eq!(pickledb(&["rem", "cli_keys_and_values.db", "user:1"]).status.code(), Some(1));
+}
+
+#[test]
+fn cli_lists() {
+    set_test_rsc!("cli_lists.db");
+
+    PickleDb::new("cli_lists.db", PickleDbDumpPolicy::AutoDump).dump();
+    stdout(&pickledb(&["ladd", "cli_lists.db", "list1", "1", "two", "[3]"]));
+    assert_eq!(stdout(&pickledb(&["llen", "cli_lists.db", "list1"])), "3\n");
+    assert_eq!(stdout(&pickledb(&["lget", "cli_lists.db", "list1", "1"])), "\"two\"\n");
+    assert_eq!(stdout(&pickledb(&["lpop", "cli_lists.db", "list1", "0"])), "1\n");
+    assert_eq!(stdout(&pickledb(&["llen", "cli_lists.db", "list1"])), "2\n");
+    assert_eq!(pickledb(&["lget", "cli_lists.db", "list1", "5"]).status.code(), Some(1));
+    assert_eq!(pickledb(&["llen", "cli_lists.db", "list2"]).status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&pickledb(&["get", "cli_lists.db", "list1"]).stderr).contains("is a list"));
+}
+
+#[test]
+fn cli_file_commands() {
+    set_test_rsc!("cli_file_commands.db");
+    set_test_rsc!("cli_file_commands.ndjson");
+    set_test_rsc!("cli_file_commands.csv");
+    set_test_rsc!("cli_file_commands_copy.db");
+
+    fs::write("cli_file_commands.db", r#"[{"key1":"1","key2":"\"two\""},{"list1":["1","2"]}]"#).unwrap();
+    let stats: serde_json::Value = serde_json::from_str(&stdout(&pickledb(&["stats", "cli_file_commands.db"]))).unwrap();
+    assert_eq!(stats["keys"], 2);
+    assert_eq!(stats["lists"], 1);
+    assert_eq!(stats["list_items"], 2);
+    assert_eq!(stats["compression"], "none");
+
+    // the legacy file is rewritten in the current format
+    stdout(&pickledb(&["dump", "cli_file_commands.db"]));
+    assert!(fs::read_to_string("cli_file_commands.db").unwrap().starts_with('{'));
+    assert!(PickleDb::verify("cli_file_commands.db").is_ok());
+
+    stdout(&pickledb(&["export", "cli_file_commands.db", "cli_file_commands.ndjson"]));
+    // CSV can't hold the list
+    let output = pickledb(&["export", "cli_file_commands.db", "cli_file_commands.csv", "--format", "csv"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("please export them as NDJSON"));
+    assert!(!std::path::Path::new("cli_file_commands.csv").exists());
+
+    PickleDb::new("cli_file_commands_copy.db", PickleDbDumpPolicy::AutoDump).dump();
+    stdout(&pickledb(&["import", "cli_file_commands_copy.db", "cli_file_commands.ndjson", "--format", "ndjson"]));
+    let db = PickleDb::load_read_only("cli_file_commands_copy.db").unwrap();
+    assert_eq!(db.get::<i32>("key1").unwrap(), 1);
+    assert_eq!(db.get::<String>("key2").unwrap(), "two");
+    assert_eq!(db.llen("list1"), 2);
+
+    assert_eq!(pickledb(&["export", "cli_file_commands.db"]).status.code(), Some(2));
+    assert_eq!(pickledb(&["export", "cli_file_commands.db", "cli_file_commands.csv", "--format", "xml"]).status.code(), Some(2));
+    assert_eq!(pickledb(&["import", "cli_file_commands.db", "missing.ndjson"]).status.code(), Some(1));
+}
+
+#[test]
+fn cli_backups() {
+    set_test_rsc!("cli_backups.db");
+    set_test_rsc!("cli_backups.db.1");
+    set_test_rsc!("cli_backups.db.2");
+
+    PickleDb::new("cli_backups.db", PickleDbDumpPolicy::AutoDump).dump();
+    for i in 1..4 {
+        stdout(&pickledb(&["--backups", "2", "set", "cli_backups.db", "key1", &i.to_string()]));
+    }
+    // reading doesn't rotate the backups
+    stdout(&pickledb(&["--backups", "2", "get", "cli_backups.db", "key1"]));
+    assert_eq!(PickleDb::load_read_only("cli_backups.db").unwrap().get::<i32>("key1").unwrap(), 3);
+    assert_eq!(PickleDb::load_read_only("cli_backups.db.1").unwrap().get::<i32>("key1").unwrap(), 2);
+    assert_eq!(PickleDb::load_read_only("cli_backups.db.2").unwrap().get::<i32>("key1").unwrap(), 1);
+    assert!(fs::metadata("cli_backups.db.tmp").is_err());
+
+    assert_eq!(pickledb(&["--backups", "two", "get", "cli_backups.db", "key1"]).status.code(), Some(2));
+    assert_eq!(pickledb(&["--backups"]).status.code(), Some(2));
+}
+
+#[test]
+fn cli_errors() {
+    set_test_rsc!("cli_errors.db");
+
+    let missing = pickledb(&["get", "cli_errors.db", "key1"]);
+    assert_eq!(missing.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("can't load 'cli_errors.db'"));
+
+    PickleDb::new("cli_errors.db", PickleDbDumpPolicy::AutoDump).dump();
+    assert_eq!(pickledb(&[]).status.code(), Some(2));
+    assert_eq!(pickledb(&["frobnicate", "cli_errors.db"]).status.code(), Some(2));
+    assert_eq!(pickledb(&["set", "cli_errors.db", "key1"]).status.code(), Some(2));
+    assert_eq!(pickledb(&["lget", "cli_errors.db", "list1", "first"]).status.code(), Some(2));
+    assert!(stdout(&pickledb(&["help"])).contains("usage: pickledb"));
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::compression::Compression;
+
+mod common;
+
+#[test]
+fn uncompressed_by_default() {
+    set_test_rsc!("uncompressed_by_default.db");
+
+    let mut db = PickleDb::new("uncompressed_by_default.db", PickleDbDumpPolicy::AutoDump);
+    assert_eq!(db.compression(), Compression::None);
+    db.set("key1", &1).unwrap();
+    assert!(fs::read_to_string("uncompressed_by_default.db").unwrap().starts_with('{'));
+    assert_eq!(PickleDb::load_read_only("uncompressed_by_default.db").unwrap().compression(), Compression::None);
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn compressed_file_without_feature() {
+    set_test_rsc!("compressed_file_without_feature.db");
+
+    fs::write("compressed_file_without_feature.db", [0x1f, 0x8b, 0x08, 0x00]).unwrap();
+    let err = PickleDb::load_read_only("compressed_file_without_feature.db").err().unwrap();
+    assert!(err.to_string().contains("`gzip` feature"));
+}
+
+/// Dump a DB with `compression`, check that the file is compressed with the given magic bytes and that it's loaded back
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+fn check_compression(db_file: &str, compression: Compression, magic: &[u8]) {
+    use serde_json::json;
+
+    let mut db = PickleDb::new(db_file, PickleDbDumpPolicy::AutoDump);
+    db.set_compression(compression);
+    for i in 0..100 {
+        db.set(&format!("user:{}", i), &json!({"name": format!("user {}", i), "email": "user@example.com", "active": true})).unwrap();
+    }
+    db.lcreate("list1");
+    db.lextend("list1", &vec![1, 2, 3]).unwrap();
+
+    let compressed_size = fs::metadata(db_file).unwrap().len();
+    assert!(fs::read(db_file).unwrap().starts_with(magic));
+    db.set_compression(Compression::None);
+    db.save_as(&format!("{}.plain", db_file)).unwrap();
+    db.set_compression(compression);
+    let plain_size = fs::metadata(format!("{}.plain", db_file)).unwrap().len();
+    fs::remove_file(format!("{}.plain", db_file)).unwrap();
+    assert!(compressed_size * 5 < plain_size);
+
+    // the compression is detected upon load, and the loaded DB keeps it
+    let mut loaded = PickleDb::load(db_file, PickleDbDumpPolicy::AutoDump).unwrap();
+    assert_eq!(loaded.compression(), compression);
+    assert_eq!(loaded.total_keys(), 101);
+    assert_eq!(loaded.get::<serde_json::Value>("user:7").unwrap()["name"], "user 7");
+    assert_eq!(loaded.llen("list1"), 3);
+    loaded.ladd("list1", &4).unwrap();
+    assert!(fs::read(db_file).unwrap().starts_with(magic));
+
+    // changes to a compressed file are reloaded
+    db.set("user:0", &json!({"name": "changed"})).unwrap();
+    assert_eq!(loaded.reload().unwrap(), vec!["list1", "user:0"]);
+
+    // back to plain JSON
+    loaded.set_compression(Compression::None);
+    loaded.dump();
+    assert!(fs::read_to_string(db_file).unwrap().starts_with('{'));
+    assert_eq!(PickleDb::load_read_only(db_file).unwrap().compression(), Compression::None);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip_compression() {
+    set_test_rsc!("gzip_compression.db");
+    check_compression("gzip_compression.db", Compression::Gzip, &[0x1f, 0x8b]);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd_compression() {
+    set_test_rsc!("zstd_compression.db");
+    check_compression("zstd_compression.db", Compression::Zstd, &[0x28, 0xb5, 0x2f, 0xfd]);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn compressed_background_dump() {
+    set_test_rsc!("compressed_background_dump.db");
+
+    let mut db = PickleDb::new("compressed_background_dump.db", PickleDbDumpPolicy::AutoDump);
+    db.set_compression(Compression::Zstd);
+    db.set_background_dump(true).unwrap();
+    db.set("key1", &1).unwrap();
+    db.flush().unwrap();
+    let loaded = PickleDb::load_read_only("compressed_background_dump.db").unwrap();
+    assert_eq!(loaded.compression(), Compression::Zstd);
+    assert_eq!(loaded.get::<i32>("key1").unwrap(), 1);
+}
@@ -12,7 +12,7 @@ fn auto_dump_poilcy_test() {
     let mut db = PickleDb::new("auto_dump_poilcy_test.db", PickleDbDumpPolicy::AutoDump);
 
     // set a key-value pair
-    db.set("key1", &1);
+    db.set("key1", &1).unwrap();
 
     // verify the change in the DB
     {
@@ -40,7 +40,7 @@ fn auto_dump_poilcy_test() {
     }
 
     // add values to list
-    db.lextend("list1", &vec![1,2,3]);
+    db.lextend("list1", &vec![1,2,3]).unwrap();
 
     // verify the change in the DB
     {
@@ -82,13 +82,13 @@ fn read_only_policy_test() {
 
     // create a DB and set a value
     let mut db = PickleDb::new("read_only_policy_test.db", PickleDbDumpPolicy::AutoDump);
-    db.set("key1", &String::from("value1"));
+    db.set("key1", &String::from("value1")).unwrap();
 
     // create a read only instance of the same DB
     let mut read_db1 = PickleDb::load_read_only("read_only_policy_test.db").unwrap();
 
     // set a key-value pair in the read-only DB
-    read_db1.set("key2", &String::from("value2"));
+    read_db1.set("key2", &String::from("value2")).unwrap();
     assert!(read_db1.exists("key2"));
 
     // verify the change isn't dumped to the file
@@ -125,7 +125,7 @@ fn dump_upon_request_policy_test() {
 
     // create a DB and set a value
     let mut db = PickleDb::new("dump_upon_request_policy_test.db", PickleDbDumpPolicy::DumpUponRequest);
-    db.set("key1", &String::from("value1"));
+    db.set("key1", &String::from("value1")).unwrap();
 
     // verify file is not yet created
     assert!(PickleDb::load_read_only("dump_upon_request_policy_test.db").is_err());
@@ -140,7 +140,7 @@ fn dump_upon_request_policy_test() {
     }
 
     // set another key
-    db.set("key2", &String::from("value2"));
+    db.set("key2", &String::from("value2")).unwrap();
 
     // drop DB object
     drop(db);
@@ -159,7 +159,7 @@ fn periodic_dump_policy_test() {
 
     // create a DB and set a value
     let mut db = PickleDb::new("periodic_dump_policy_test.db", PickleDbDumpPolicy::PeriodicDump(Duration::new(1, 0)));
-    db.set("key1", &String::from("value1"));
+    db.set("key1", &String::from("value1")).unwrap();
 
     // verify file is not yet created
     assert!(PickleDb::load_read_only("periodic_dump_policy_test.db").is_err());
@@ -174,7 +174,7 @@ fn periodic_dump_policy_test() {
     thread::sleep(time::Duration::from_millis(550));
 
     // make another change in the DB
-    db.set("key2", &String::from("value2"));
+    db.set("key2", &String::from("value2")).unwrap();
 
     // verify the change is dumped to the file
     {
@@ -184,7 +184,7 @@ fn periodic_dump_policy_test() {
     }
 
     // make another change in the DB
-    db.set("key3", &String::from("value3"));
+    db.set("key3", &String::from("value3")).unwrap();
 
     // verify the change is not yet dumped to the file
     {
@@ -205,7 +205,7 @@ fn periodic_dump_policy_test() {
     thread::sleep(time::Duration::from_secs(1));
 
     // make another change in the DB
-    db.set("key4", &String::from("value4"));
+    db.set("key4", &String::from("value4")).unwrap();
 
     // verify the change is dumped to the file
     {
@@ -214,7 +214,7 @@ fn periodic_dump_policy_test() {
     }
 
     // make another change in the DB
-    db.set("key5", &String::from("value5"));
+    db.set("key5", &String::from("value5")).unwrap();
 
     // drop DB and verify change is written to DB
     drop(db);
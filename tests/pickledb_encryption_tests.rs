@@ -0,0 +1,157 @@
+#![cfg(feature = "encryption")]
+
+use std::fs;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::encryption::EncryptionKey;
+use pickledb::error::Error;
+
+mod common;
+
+const KEY: [u8; 32] = [7; 32];
+
+#[test]
+fn encrypted_with_key() {
+    set_test_rsc!("encrypted_with_key.db");
+
+    let mut db = PickleDb::new_encrypted("encrypted_with_key.db", PickleDbDumpPolicy::AutoDump, EncryptionKey::new(KEY)).unwrap();
+    db.set("secret", &"the password is 1234").unwrap();
+    db.lcreate("list1");
+    db.lextend("list1", &vec![1, 2, 3]).unwrap();
+
+    let contents = fs::read("encrypted_with_key.db").unwrap();
+    assert!(contents.starts_with(b"PKLDBENC"));
+    assert!(!String::from_utf8_lossy(&contents).contains("password"));
+
+    let mut loaded = PickleDb::load_encrypted("encrypted_with_key.db", PickleDbDumpPolicy::AutoDump, EncryptionKey::new(KEY)).unwrap();
+    assert_eq!(loaded.get::<String>("secret").unwrap(), "the password is 1234");
+    assert_eq!(loaded.llen("list1"), 3);
+
+    // the loaded DB keeps encrypting its file
+    loaded.set("key2", &2).unwrap();
+    let loaded = PickleDb::load_encrypted("encrypted_with_key.db", PickleDbDumpPolicy::NeverDump, EncryptionKey::new(KEY)).unwrap();
+    assert_eq!(loaded.get::<i32>("key2").unwrap(), 2);
+
+    // an encrypted file can't be loaded as a plain one
+    let err = PickleDb::load_read_only("encrypted_with_key.db").err().unwrap();
+    assert!(err.to_string().contains("load_encrypted"));
+}
+
+#[test]
+fn encrypted_with_passphrase() {
+    set_test_rsc!("encrypted_with_passphrase.db");
+
+    let key = EncryptionKey::from_passphrase("correct horse battery staple");
+    let mut db = PickleDb::new_encrypted("encrypted_with_passphrase.db", PickleDbDumpPolicy::AutoDump, key.clone()).unwrap();
+    db.set("key1", &1).unwrap();
+    assert!(!format!("{:?}", key).contains("horse"));
+
+    // changes of another writer with the same passphrase are reloaded
+    let mut reader = PickleDb::load_encrypted("encrypted_with_passphrase.db", PickleDbDumpPolicy::NeverDump, key.clone()).unwrap();
+    db.set("key1", &10).unwrap();
+    assert_eq!(reader.reload().unwrap(), vec!["key1"]);
+    assert_eq!(reader.get::<i32>("key1").unwrap(), 10);
+
+    let wrong = EncryptionKey::from_passphrase("Tr0ub4dor&3");
+    assert!(matches!(PickleDb::load_encrypted("encrypted_with_passphrase.db", PickleDbDumpPolicy::NeverDump, wrong), Err(Error::Decryption)));
+    // a raw key doesn't open a file that is encrypted with a passphrase
+    assert!(matches!(
+        PickleDb::load_encrypted("encrypted_with_passphrase.db", PickleDbDumpPolicy::NeverDump, EncryptionKey::new(KEY)),
+        Err(Error::Decryption)
+    ));
+}
+
+#[test]
+fn wrong_key_or_tampered_file() {
+    set_test_rsc!("wrong_key_or_tampered_file.db");
+
+    let mut db = PickleDb::new_encrypted("wrong_key_or_tampered_file.db", PickleDbDumpPolicy::AutoDump, EncryptionKey::new(KEY)).unwrap();
+    db.set("key1", &1).unwrap();
+
+    let err = PickleDb::load_encrypted("wrong_key_or_tampered_file.db", PickleDbDumpPolicy::NeverDump, EncryptionKey::new([8; 32])).err().unwrap();
+    assert!(matches!(err, Error::Decryption));
+    assert!(err.to_string().contains("key is wrong"));
+
+    // every byte is authenticated, including the header
+    let contents = fs::read("wrong_key_or_tampered_file.db").unwrap();
+    for pos in [9, 20, contents.len() / 2, contents.len() - 1] {
+        let mut tampered = contents.clone();
+        tampered[pos] ^= 1;
+        fs::write("wrong_key_or_tampered_file.db", &tampered).unwrap();
+        assert!(matches!(
+            PickleDb::load_encrypted("wrong_key_or_tampered_file.db", PickleDbDumpPolicy::NeverDump, EncryptionKey::new(KEY)),
+            Err(Error::Decryption)
+        ));
+    }
+
+    // so is a truncated file and a file that isn't encrypted
+    fs::write("wrong_key_or_tampered_file.db", &contents[..contents.len() - 1]).unwrap();
+    assert!(matches!(
+        PickleDb::load_encrypted("wrong_key_or_tampered_file.db", PickleDbDumpPolicy::NeverDump, EncryptionKey::new(KEY)),
+        Err(Error::Decryption)
+    ));
+    fs::write("wrong_key_or_tampered_file.db", r#"{"map": {"key1": 2}, "list_map": {}}"#).unwrap();
+    assert!(matches!(
+        PickleDb::load_encrypted("wrong_key_or_tampered_file.db", PickleDbDumpPolicy::NeverDump, EncryptionKey::new(KEY)),
+        Err(Error::Decryption)
+    ));
+}
+
+#[test]
+fn rekey() {
+    set_test_rsc!("rekey.db");
+
+    let mut db = PickleDb::new("rekey.db", PickleDbDumpPolicy::DumpUponRequest);
+    db.set("key1", &1).unwrap();
+    db.dump();
+
+    // a plain DB becomes encrypted, including changes that weren't dumped yet
+    db.set("key2", &2).unwrap();
+    db.rekey(EncryptionKey::new(KEY)).unwrap();
+    let loaded = PickleDb::load_encrypted("rekey.db", PickleDbDumpPolicy::NeverDump, EncryptionKey::new(KEY)).unwrap();
+    assert_eq!(loaded.get::<i32>("key2").unwrap(), 2);
+
+    // the previous key no longer opens the file
+    db.rekey(EncryptionKey::from_passphrase("new passphrase")).unwrap();
+    assert!(matches!(
+        PickleDb::load_encrypted("rekey.db", PickleDbDumpPolicy::NeverDump, EncryptionKey::new(KEY)),
+        Err(Error::Decryption)
+    ));
+    let loaded = PickleDb::load_encrypted("rekey.db", PickleDbDumpPolicy::NeverDump, EncryptionKey::from_passphrase("new passphrase")).unwrap();
+    assert_eq!(loaded.total_keys(), 2);
+}
+
+#[test]
+fn encrypted_background_dump_and_backups() {
+    set_test_rsc!("encrypted_background_dump.db");
+    set_test_rsc!("encrypted_background_dump.db.1");
+
+    let mut db = PickleDb::new_encrypted("encrypted_background_dump.db", PickleDbDumpPolicy::AutoDump, EncryptionKey::new(KEY)).unwrap();
+    db.set_backup_rotation(1);
+    db.set_background_dump(true).unwrap();
+    db.set("key1", &1).unwrap();
+    db.flush().unwrap();
+    db.set("key1", &2).unwrap();
+    db.flush().unwrap();
+
+    let loaded = PickleDb::load_encrypted("encrypted_background_dump.db", PickleDbDumpPolicy::NeverDump, EncryptionKey::new(KEY)).unwrap();
+    assert_eq!(loaded.get::<i32>("key1").unwrap(), 2);
+    let backup = PickleDb::load_encrypted("encrypted_background_dump.db.1", PickleDbDumpPolicy::NeverDump, EncryptionKey::new(KEY)).unwrap();
+    assert_eq!(backup.get::<i32>("key1").unwrap(), 1);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn compressed_and_encrypted() {
+    use pickledb::compression::Compression;
+
+    set_test_rsc!("compressed_and_encrypted.db");
+
+    let mut db = PickleDb::new_encrypted("compressed_and_encrypted.db", PickleDbDumpPolicy::AutoDump, EncryptionKey::new(KEY)).unwrap();
+    db.set_compression(Compression::Gzip);
+    db.set("key1", &"a".repeat(10000)).unwrap();
+    assert!(fs::metadata("compressed_and_encrypted.db").unwrap().len() < 1000);
+
+    let loaded = PickleDb::load_encrypted("compressed_and_encrypted.db", PickleDbDumpPolicy::NeverDump, EncryptionKey::new(KEY)).unwrap();
+    assert_eq!(loaded.compression(), Compression::Gzip);
+    assert_eq!(loaded.get::<String>("key1").unwrap().len(), 10000);
+}
@@ -0,0 +1,197 @@
+use std::fs;
+use pickledb::error::Error;
+use pickledb::export::{ConflictPolicy, ExportFormat};
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use serde_json::{json, Value};
+
+mod common;
+
+fn sample_db(location: &str) -> PickleDb {
+    let mut db = PickleDb::new(location, PickleDbDumpPolicy::AutoDump);
+    db.set("num", &100).unwrap();
+    db.set("text", &String::from("hello, \"world\"\nbye")).unwrap();
+    db.set("numeric text", &String::from("123")).unwrap();
+    db.set("user", &json!({"name": "john", "age": 30})).unwrap();
+    db.lcreate("list1");
+    db.lextend("list1", &vec![json!(1), json!("two")]).unwrap();
+    db
+}
+
+#[test]
+fn export_import_ndjson() {
+    set_test_rsc!("export_import_ndjson.db");
+    set_test_rsc!("export_import_ndjson.ndjson");
+    set_test_rsc!("export_import_ndjson_copy.db");
+
+    let db = sample_db("export_import_ndjson.db");
+    assert_eq!(db.export("export_import_ndjson.ndjson", ExportFormat::Ndjson).unwrap(), 5);
+
+    let exported = fs::read_to_string("export_import_ndjson.ndjson").unwrap();
+    let lines: Vec<Value> = exported.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines[0], json!({"key": "num", "type": "value", "value": 100}));
+    assert_eq!(lines[4], json!({"key": "list1", "type": "list", "value": [1, "two"]}));
+
+    let mut copy = PickleDb::new("export_import_ndjson_copy.db", PickleDbDumpPolicy::AutoDump);
+    assert_eq!(copy.import("export_import_ndjson.ndjson", ExportFormat::Ndjson, ConflictPolicy::Fail).unwrap(), 5);
+
+    // the import was dumped according to the dump policy
+    let copy = PickleDb::load_read_only("export_import_ndjson_copy.db").unwrap();
+    assert_eq!(copy.get::<i32>("num").unwrap(), 100);
+    assert_eq!(copy.get::<String>("text").unwrap(), "hello, \"world\"\nbye");
+    assert_eq!(copy.get::<Value>("user").unwrap(), json!({"name": "john", "age": 30}));
+    assert_eq!(copy.llen("list1"), 2);
+    assert_eq!(copy.lget::<String>("list1", 1).unwrap(), "two");
+}
+
+#[test]
+fn export_import_csv() {
+    set_test_rsc!("export_import_csv.db");
+    set_test_rsc!("export_import_csv.csv");
+    set_test_rsc!("export_import_csv_copy.db");
+
+    let mut db = sample_db("export_import_csv.db");
+    // CSV can't hold lists, so nothing is written while the DB has them
+    match db.export("export_import_csv.csv", ExportFormat::Csv) {
+        Err(Error::TypeMismatch { key, .. }) => assert_eq!(key, "list1"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(!std::path::Path::new("export_import_csv.csv").exists());
+    db.lrem_list("list1");
+    assert_eq!(db.export("export_import_csv.csv", ExportFormat::Csv).unwrap(), 4);
+    let exported = fs::read_to_string("export_import_csv.csv").unwrap();
+    assert!(exported.starts_with("key,value\nnum,100\nnumeric text,\"\"\"123\"\"\"\n"));
+    assert!(exported.contains("text,\"hello, \"\"world\"\"\nbye\"\n"));
+
+    let mut copy = PickleDb::new("export_import_csv_copy.db", PickleDbDumpPolicy::NeverDump);
+    assert_eq!(copy.import("export_import_csv.csv", ExportFormat::Csv, ConflictPolicy::Overwrite).unwrap(), 4);
+    assert_eq!(copy.get::<i32>("num").unwrap(), 100);
+    assert_eq!(copy.get::<String>("numeric text").unwrap(), "123");
+    assert_eq!(copy.get::<String>("text").unwrap(), "hello, \"world\"\nbye");
+    assert_eq!(copy.get::<Value>("user").unwrap(), json!({"name": "john", "age": 30}));
+    assert!(!copy.exists("list1"));
+
+    // CSV written by other tools takes values that aren't JSON as strings
+    fs::write("export_import_csv.csv", "key,value\r\nk1,plain text\r\nk2,2.5\r\n").unwrap();
+    assert_eq!(copy.import("export_import_csv.csv", ExportFormat::Csv, ConflictPolicy::Overwrite).unwrap(), 2);
+    assert_eq!(copy.get::<String>("k1").unwrap(), "plain text");
+    assert_eq!(copy.get::<f64>("k2").unwrap(), 2.5);
+}
+
+#[test]
+fn export_import_json() {
+    set_test_rsc!("export_import_json.db");
+    set_test_rsc!("export_import_json.json");
+    set_test_rsc!("export_import_json_copy.db");
+
+    let mut db = sample_db("export_import_json.db");
+    assert!(matches!(db.export("export_import_json.json", ExportFormat::Json), Err(Error::TypeMismatch { .. })));
+    db.lrem_list("list1");
+    assert_eq!(db.export("export_import_json.json", ExportFormat::Json).unwrap(), 4);
+    let exported: Value = serde_json::from_str(&fs::read_to_string("export_import_json.json").unwrap()).unwrap();
+    assert_eq!(exported["num"], json!(100));
+    assert_eq!(exported["user"], json!({"name": "john", "age": 30}));
+
+    let mut copy = PickleDb::new("export_import_json_copy.db", PickleDbDumpPolicy::NeverDump);
+    assert_eq!(copy.import("export_import_json.json", ExportFormat::Json, ConflictPolicy::Fail).unwrap(), 4);
+    assert_eq!(copy.get::<String>("numeric text").unwrap(), "123");
+    assert_eq!(copy.get::<Value>("user").unwrap(), json!({"name": "john", "age": 30}));
+}
+
+#[test]
+fn import_conflicts() {
+    set_test_rsc!("import_conflicts.db");
+    set_test_rsc!("import_conflicts.ndjson");
+
+    fs::write("import_conflicts.ndjson", concat!(
+        "{\"key\":\"key1\",\"type\":\"value\",\"value\":\"new\"}\n",
+        "{\"key\":\"key2\",\"type\":\"value\",\"value\":\"new\"}\n",
+    )).unwrap();
+    let mut db = PickleDb::new("import_conflicts.db", PickleDbDumpPolicy::NeverDump);
+    db.set("key2", &String::from("old")).unwrap();
+
+    // nothing is imported when a key conflicts
+    match db.import("import_conflicts.ndjson", ExportFormat::Ndjson, ConflictPolicy::Fail) {
+        Err(Error::Conflict(key)) => assert_eq!(key, "key2"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(!db.exists("key1"));
+
+    assert_eq!(db.import("import_conflicts.ndjson", ExportFormat::Ndjson, ConflictPolicy::Skip).unwrap(), 1);
+    assert_eq!(db.get::<String>("key1").unwrap(), "new");
+    assert_eq!(db.get::<String>("key2").unwrap(), "old");
+
+    assert_eq!(db.import("import_conflicts.ndjson", ExportFormat::Ndjson, ConflictPolicy::Overwrite).unwrap(), 2);
+    assert_eq!(db.get::<String>("key2").unwrap(), "new");
+}
+
+#[test]
+fn import_invalid_files() {
+    set_test_rsc!("import_invalid_files.db");
+    set_test_rsc!("import_invalid_files.txt");
+
+    let mut db = PickleDb::new("import_invalid_files.db", PickleDbDumpPolicy::NeverDump);
+    let mut import = |contents: &str, format: ExportFormat| {
+        fs::write("import_invalid_files.txt", contents).unwrap();
+        db.import("import_invalid_files.txt", format, ConflictPolicy::Overwrite)
+    };
+
+    let ndjson = "{\"key\":\"k1\",\"type\":\"value\",\"value\":1}\n{\"key\":\"k2\",\"type\":\"list\",\"value\":1}\n";
+    assert!(matches!(import(ndjson, ExportFormat::Ndjson), Err(Error::InvalidImport { line: 2, .. })));
+    assert!(matches!(import("k1,1\n", ExportFormat::Csv), Err(Error::InvalidImport { line: 1, .. })));
+    assert!(matches!(import("key,value\nk1,1,2\n", ExportFormat::Csv), Err(Error::InvalidImport { line: 2, .. })));
+    assert!(matches!(import("key,value\nk1,\"open\n", ExportFormat::Csv), Err(Error::InvalidImport { line: 2, .. })));
+    assert!(matches!(import("{\n  \"k1\": 1,\n  \"k2\": \n}", ExportFormat::Json), Err(Error::InvalidImport { line: 4, .. })));
+    assert!(matches!(import("[1, 2]", ExportFormat::Json), Err(Error::InvalidImport { .. })));
+}
+
+#[test]
+fn import_vetoed_list() {
+    set_test_rsc!("import_vetoed_list.db");
+    set_test_rsc!("import_vetoed_list.ndjson");
+
+    fs::write("import_vetoed_list.ndjson", "{\"key\":\"list1\",\"type\":\"list\",\"value\":[1]}\n").unwrap();
+    let mut db = PickleDb::new("import_vetoed_list.db", PickleDbDumpPolicy::NeverDump);
+    db.add_before_hook(|mutation| if mutation.key() == "list1" { Err(String::from("no")) } else { Ok(()) });
+
+    // a list whose creation is vetoed isn't counted as imported
+    assert!(matches!(db.import("import_vetoed_list.ndjson", ExportFormat::Ndjson, ConflictPolicy::Overwrite), Err(Error::Vetoed { .. })));
+    assert!(!db.lexists("list1"));
+}
+
+#[test]
+fn import_dump_fails() {
+    set_test_rsc!("import_dump_fails.ndjson");
+
+    fs::write("import_dump_fails.ndjson", "{\"key\":\"key1\",\"type\":\"value\",\"value\":1}\n").unwrap();
+    let mut db = PickleDb::new("import_dump_fails_missing/import_dump_fails.db", PickleDbDumpPolicy::AutoDump);
+
+    // the keys are imported, but the error of the dump is returned
+    assert!(db.import("import_dump_fails.ndjson", ExportFormat::Ndjson, ConflictPolicy::Overwrite).is_err());
+    assert_eq!(db.get::<i32>("key1").unwrap(), 1);
+}
+
+#[test]
+fn failed_import_changes_nothing() {
+    set_test_rsc!("failed_import_changes_nothing.db");
+    set_test_rsc!("failed_import_changes_nothing.ndjson");
+
+    fs::write("failed_import_changes_nothing.ndjson", concat!(
+        "{\"key\":\"key1\",\"type\":\"value\",\"value\":\"new\"}\n",
+        "{\"key\":\"list1\",\"type\":\"list\",\"value\":[1]}\n",
+        "{\"key\":\"nums\",\"type\":\"list\",\"value\":[2, \"three\"]}\n",
+    )).unwrap();
+    let mut db = PickleDb::new("failed_import_changes_nothing.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &String::from("old")).unwrap();
+    db.lcreate_with("nums", &[1]).unwrap();
+    db.set_list_schema("nums", &json!({"type": "number"})).unwrap();
+
+    // the last list is invalid, so the keys and lists imported before it are restored
+    assert!(matches!(db.import("failed_import_changes_nothing.ndjson", ExportFormat::Ndjson, ConflictPolicy::Overwrite),
+                     Err(Error::SchemaViolation { .. })));
+    assert_eq!(db.get::<String>("key1").unwrap(), "old");
+    assert!(!db.lexists("list1"));
+    assert_eq!(db.llen("nums"), 1);
+    let read = PickleDb::load_read_only("failed_import_changes_nothing.db").unwrap();
+    assert_eq!(read.get::<String>("key1").unwrap(), "old");
+    assert!(!read.lexists("list1"));
+}
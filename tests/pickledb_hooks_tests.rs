@@ -0,0 +1,225 @@
+use std::sync::{Arc, Mutex};
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::error::Error;
+use pickledb::watch::ChangeKind;
+use serde_json::{json, Value};
+
+mod common;
+
+#[macro_use]
+extern crate serde_derive;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Config {
+    theme: String,
+    #[serde(default)]
+    version: u32,
+}
+
+#[test]
+fn before_hook_transforms_values() {
+    set_test_rsc!("before_hook_transforms_values.db");
+
+    let mut db = PickleDb::new("before_hook_transforms_values.db", PickleDbDumpPolicy::AutoDump);
+
+    // stamp a version on every object that is set
+    let mut version = 0;
+    db.add_before_hook(move |mutation| {
+        if mutation.kind() == ChangeKind::Set {
+            if let Some(Value::Object(fields)) = mutation.value_mut() {
+                version += 1;
+                fields.insert(String::from("version"), json!(version));
+            }
+        }
+        Ok(())
+    });
+    // double every number added to a list
+    db.add_before_hook(|mutation| {
+        if let (ChangeKind::ListPush, Some(num)) = (mutation.kind(), mutation.value().and_then(Value::as_i64)) {
+            mutation.set_value(json!(num * 2));
+        }
+        Ok(())
+    });
+
+    db.set("config", &Config { theme: String::from("dark"), version: 0 }).unwrap();
+    db.set("config", &Config { theme: String::from("light"), version: 0 }).unwrap();
+    db.set("num", &1).unwrap();
+    assert_eq!(db.get::<Config>("config").unwrap(), Config { theme: String::from("light"), version: 2 });
+    assert_eq!(db.get::<i32>("num").unwrap(), 1);
+
+    db.lcreate("list1");
+    db.lextend("list1", &vec![1, 2]).unwrap();
+    db.ladd("list1", &"a").unwrap();
+    assert_eq!(db.lget::<i32>("list1", 1).unwrap(), 4);
+    assert_eq!(db.lget::<String>("list1", 2).unwrap(), "a");
+
+    // the transformed values are the ones stored in the file
+    let read_db = PickleDb::load_read_only("before_hook_transforms_values.db").unwrap();
+    assert_eq!(read_db.get::<Config>("config").unwrap().version, 2);
+    assert_eq!(read_db.lget::<i32>("list1", 0).unwrap(), 2);
+}
+
+#[test]
+fn before_hook_vetoes_mutations() {
+    set_test_rsc!("before_hook_vetoes_mutations.db");
+
+    let mut db = PickleDb::new("before_hook_vetoes_mutations.db", PickleDbDumpPolicy::AutoDump);
+    db.set("locked", &1).unwrap();
+    db.lcreate("locked_list");
+    db.lextend("locked_list", &vec![1, 2, 3]).unwrap();
+
+    let id = db.add_before_hook(|mutation| {
+        let negative = mutation.value().and_then(Value::as_i64).is_some_and(|num| num < 0);
+        if mutation.key().starts_with("locked") || negative {
+            Err(format!("{} is read-only", mutation.key()))
+        } else {
+            Ok(())
+        }
+    });
+
+    match db.set("locked", &2) {
+        Err(Error::Vetoed { key, reason }) => {
+            assert_eq!(key, "locked");
+            assert_eq!(reason, "locked is read-only");
+        },
+        _ => panic!("expected the change to be vetoed"),
+    }
+    assert!(!db.rem("locked"));
+    assert_eq!(db.get::<i32>("locked").unwrap(), 1);
+
+    assert!(db.ladd("locked_list", &4).is_err());
+    assert!(db.lpop::<i32>("locked_list", 0).is_none());
+    assert!(!db.lrem_value("locked_list", &1));
+    assert_eq!(db.lrem_list("locked_list"), 0);
+    db.lcreate("locked_list");
+    assert_eq!(db.llen("locked_list"), 3);
+
+    // the try_ variants report the veto
+    assert!(matches!(db.try_rem("locked"), Err(Error::Vetoed { .. })));
+    assert!(matches!(db.try_lpop::<i32>("locked_list", 0), Err(Error::Vetoed { .. })));
+    assert!(matches!(db.try_lrem_value("locked_list", &1), Err(Error::Vetoed { .. })));
+    assert!(matches!(db.try_lrem_list("locked_list"), Err(Error::Vetoed { .. })));
+    assert!(matches!(db.try_lcreate("locked_list"), Err(Error::Vetoed { .. })));
+    assert_eq!(db.llen("locked_list"), 3);
+    assert!(!db.try_rem("missing").unwrap());
+    assert!(db.try_lpop::<i32>("locked_list", 3).unwrap().is_none());
+    assert!(!db.try_lrem_value("locked_list", &4).unwrap());
+
+    // a single vetoed item vetoes the whole extend
+    db.lcreate("list1");
+    assert!(db.lextend("list1", &vec![1, -2, 3]).is_err());
+    assert_eq!(db.llen("list1"), 0);
+
+    // the vetoed changes weren't stored
+    {
+        let read_db = PickleDb::load_read_only("before_hook_vetoes_mutations.db").unwrap();
+        assert_eq!(read_db.llen("locked_list"), 3);
+    }
+
+    assert!(db.remove_hook(id));
+    assert!(!db.remove_hook(id));
+    assert!(db.rem("locked"));
+}
+
+#[test]
+fn before_hook_skips_calls_that_change_nothing() {
+    set_test_rsc!("before_hook_skips_calls_that_change_nothing.db");
+
+    let mut db = PickleDb::new("before_hook_skips_calls_that_change_nothing.db", PickleDbDumpPolicy::NeverDump);
+    db.lcreate("list1");
+    db.lextend("list1", &vec![1, 2]).unwrap();
+
+    let mutations = Arc::new(Mutex::new(Vec::new()));
+    let mutations_log = mutations.clone();
+    db.add_before_hook(move |mutation| {
+        mutations_log.lock().unwrap().push((String::from(mutation.key()), mutation.kind(), mutation.value().cloned()));
+        Ok(())
+    });
+
+    assert!(!db.rem("key1"));
+    assert!(db.lpop::<i32>("list1", 2).is_none());
+    assert!(db.lpop::<i32>("list2", 0).is_none());
+    assert!(!db.lrem_value("list1", &3));
+    assert!(!db.lrem_value("list2", &1));
+    assert_eq!(db.lrem_list("list2"), 0);
+    assert!(mutations.lock().unwrap().is_empty());
+
+    assert!(db.lrem_value("list1", &2));
+    assert_eq!(*mutations.lock().unwrap(), vec![(String::from("list1"), ChangeKind::ListRemoveValue, Some(json!(2)))]);
+    assert_eq!(db.llen("list1"), 1);
+}
+
+#[test]
+fn after_hook_receives_changes() {
+    set_test_rsc!("after_hook_receives_changes.db");
+
+    let mut db = PickleDb::new("after_hook_receives_changes.db", PickleDbDumpPolicy::NeverDump);
+    let audit = Arc::new(Mutex::new(Vec::new()));
+    let audit_log = audit.clone();
+    let id = db.add_after_hook(move |event| audit_log.lock().unwrap().push((event.key.clone(), event.kind, event.new.clone())));
+
+    // a vetoed change doesn't reach the after hooks
+    db.add_before_hook(|mutation| if mutation.key() == "vetoed" { Err(String::from("no")) } else { Ok(()) });
+
+    db.set("key1", &1).unwrap();
+    assert!(db.set("vetoed", &1).is_err());
+    assert!(db.rem("key1"));
+    assert!(!db.rem("key1"));
+    db.lcreate("list1");
+    db.ladd("list1", &"a").unwrap();
+    db.lpop::<String>("list1", 0).unwrap();
+    assert_eq!(db.lrem_list("list1"), 0);
+
+    assert_eq!(*audit.lock().unwrap(), vec![
+        (String::from("key1"), ChangeKind::Set, Some(json!(1))),
+        (String::from("key1"), ChangeKind::Removed, None),
+        (String::from("list1"), ChangeKind::ListCreated, Some(json!([]))),
+        (String::from("list1"), ChangeKind::ListPush, Some(json!("a"))),
+        (String::from("list1"), ChangeKind::ListPop, None),
+        (String::from("list1"), ChangeKind::ListRemoved, None),
+    ]);
+
+    assert!(db.remove_hook(id));
+    db.set("key2", &1).unwrap();
+    assert_eq!(audit.lock().unwrap().len(), 6);
+}
+
+#[test]
+fn hooks_run_on_namespaces() {
+    set_test_rsc!("hooks_run_on_namespaces.db");
+
+    let mut db = PickleDb::new("hooks_run_on_namespaces.db", PickleDbDumpPolicy::NeverDump);
+    let audit = Arc::new(Mutex::new(Vec::new()));
+    let audit_log = audit.clone();
+    db.add_after_hook(move |event| audit_log.lock().unwrap().push((event.namespace.clone(), event.key.clone(), event.kind)));
+    db.add_before_hook(|mutation| match (mutation.namespace(), mutation.key()) {
+        (Some("billing"), "locked") => Err(String::from("locked in billing")),
+        (Some(_), "stamped") => {
+            mutation.set_value(json!("stamped"));
+            Ok(())
+        },
+        _ => Ok(()),
+    });
+
+    // a vetoed change doesn't create the namespace
+    assert!(matches!(db.namespace("billing").try_lcreate("locked"), Err(Error::Vetoed { .. })));
+    assert!(matches!(db.namespace("billing").set("locked", &1), Err(Error::Vetoed { .. })));
+    assert!(db.list_namespaces().is_empty());
+
+    db.namespace("billing").set("stamped", &1).unwrap();
+    assert_eq!(db.namespace("billing").get::<String>("stamped").unwrap(), "stamped");
+    db.set("locked", &1).unwrap();
+    db.namespace("other").lcreate("locked");
+    assert!(db.namespace("other").ladd("locked", &2).unwrap());
+    assert!(matches!(db.namespace("billing").try_rem("stamped"), Ok(true)));
+
+    let billing = Some(String::from("billing"));
+    let other = Some(String::from("other"));
+    assert_eq!(*audit.lock().unwrap(), vec![
+        (billing.clone(), String::from("stamped"), ChangeKind::Set),
+        (None, String::from("locked"), ChangeKind::Set),
+        (other.clone(), String::from("locked"), ChangeKind::ListCreated),
+        (other, String::from("locked"), ChangeKind::ListPush),
+        (billing, String::from("stamped"), ChangeKind::Removed),
+    ]);
+}
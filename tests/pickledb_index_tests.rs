@@ -0,0 +1,173 @@
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::error::Error;
+
+mod common;
+
+#[macro_use]
+extern crate serde_derive;
+
+#[derive(Serialize, Deserialize)]
+struct User {
+    name: String,
+    email: String,
+    age: u32,
+}
+
+fn user(name: &str, email: &str, age: u32) -> User {
+    User { name: String::from(name), email: String::from(email), age }
+}
+
+#[test]
+fn find_by_index() {
+    set_test_rsc!("find_by_index.db");
+
+    let mut db = PickleDb::new("find_by_index.db", PickleDbDumpPolicy::AutoDump);
+
+    // values that exist before the index is created are indexed
+    db.set("user1", &user("John", "john@example.com", 30)).unwrap();
+    db.create_index("by_email", "$.email").unwrap();
+    db.create_index("by_age", "$.age").unwrap();
+
+    db.set("user2", &user("Jane", "jane@example.com", 30)).unwrap();
+    db.set("user3", &user("Bob", "bob@example.com", 40)).unwrap();
+    db.set("num", &1).unwrap();
+
+    assert_eq!(db.find_by_index("by_email", &"jane@example.com"), vec!["user2"]);
+    assert_eq!(db.find_by_index("by_age", &30), vec!["user1", "user2"]);
+    assert!(db.find_by_index("by_email", &"nobody@example.com").is_empty());
+    assert!(db.find_by_index("no_such_index", &30).is_empty());
+
+    // overriding a value updates the index
+    db.set("user2", &user("Jane", "jane@example.org", 31)).unwrap();
+    assert!(db.find_by_index("by_email", &"jane@example.com").is_empty());
+    assert_eq!(db.find_by_index("by_email", &"jane@example.org"), vec!["user2"]);
+    assert_eq!(db.find_by_index("by_age", &30), vec!["user1"]);
+
+    // removing a value or overriding it with a list removes it from the index
+    assert!(db.rem("user1"));
+    assert!(db.find_by_index("by_age", &30).is_empty());
+    db.lcreate("user3");
+    assert!(db.find_by_index("by_email", &"bob@example.com").is_empty());
+
+    // drop an index
+    assert!(db.drop_index("by_age"));
+    assert!(!db.drop_index("by_age"));
+    assert!(db.find_by_index("by_age", &31).is_empty());
+
+    assert!(db.create_index("bad", "email").is_err());
+}
+
+#[test]
+fn indexes_are_rebuilt_upon_load() {
+    set_test_rsc!("indexes_are_rebuilt_upon_load.db");
+
+    {
+        let mut db = PickleDb::new("indexes_are_rebuilt_upon_load.db", PickleDbDumpPolicy::AutoDump);
+        db.create_index("by_email", "$.email").unwrap();
+        db.set("user1", &user("John", "john@example.com", 30)).unwrap();
+        db.set("user2", &user("Jane", "jane@example.com", 30)).unwrap();
+    }
+
+    let mut db = PickleDb::load("indexes_are_rebuilt_upon_load.db", PickleDbDumpPolicy::DumpUponRequest).unwrap();
+    assert_eq!(db.find_by_index("by_email", &"john@example.com"), vec!["user1"]);
+
+    db.set("user3", &user("John", "john@example.com", 50)).unwrap();
+    assert_eq!(db.find_by_index("by_email", &"john@example.com"), vec!["user1", "user3"]);
+}
+
+#[test]
+fn index_paths_with_quotes_and_brackets_are_reloaded() {
+    set_test_rsc!("index_paths_with_quotes_and_brackets_are_reloaded.db");
+
+    {
+        let mut db = PickleDb::new("index_paths_with_quotes_and_brackets_are_reloaded.db", PickleDbDumpPolicy::AutoDump);
+        db.create_index("by_odd_field", "$.a]b").unwrap();
+        db.create_index("by_quoted_field", "$['it\\'s']").unwrap();
+        db.set("key1", &serde_json::json!({"a]b": 1, "it's": 2})).unwrap();
+    }
+
+    let db = PickleDb::load_read_only("index_paths_with_quotes_and_brackets_are_reloaded.db").unwrap();
+    assert_eq!(db.find_by_index("by_odd_field", &1), vec!["key1"]);
+    assert_eq!(db.find_by_index("by_quoted_field", &2), vec!["key1"]);
+}
+
+#[test]
+fn unique_index() {
+    set_test_rsc!("unique_index.db");
+
+    let mut db = PickleDb::new("unique_index.db", PickleDbDumpPolicy::AutoDump);
+    db.set("user:1", &user("John", "john@example.com", 30)).unwrap();
+    db.set("admin:1", &user("Admin", "john@example.com", 30)).unwrap();
+
+    // existing duplicates under the prefix fail the index creation
+    db.set("user:2", &user("Johnny", "john@example.com", 30)).unwrap();
+    match db.create_unique_index("unique_email", "$.email", "user:") {
+        Err(Error::ConstraintViolation { index, .. }) => assert_eq!(index, "unique_email"),
+        _ => panic!("expected a constraint violation"),
+    }
+    assert!(db.rem("user:2"));
+
+    db.create_unique_index("unique_email", "$.email", "user:").unwrap();
+
+    // a duplicate under the prefix is rejected and the old value is kept
+    match db.set("user:2", &user("Jane", "john@example.com", 25)) {
+        Err(Error::ConstraintViolation { index, key, existing_key }) => {
+            assert_eq!(index, "unique_email");
+            assert_eq!(key, "user:2");
+            assert_eq!(existing_key, "user:1");
+        },
+        _ => panic!("expected a constraint violation"),
+    }
+    assert!(!db.exists("user:2"));
+
+    // keys outside the prefix and values without the field aren't constrained
+    db.set("admin:2", &user("Admin2", "john@example.com", 25)).unwrap();
+    db.set("user:3", &1).unwrap();
+    db.set("user:4", &1).unwrap();
+
+    // setting the same key again and non-unique fields are fine
+    db.set("user:1", &user("John Smith", "john@example.com", 31)).unwrap();
+    db.set("user:2", &user("Jane", "jane@example.com", 31)).unwrap();
+
+    // once the value is changed or removed its field can be reused
+    db.set("user:1", &user("John", "john@example.org", 31)).unwrap();
+    db.set("user:5", &user("Johnny", "john@example.com", 40)).unwrap();
+    assert!(db.rem("user:5"));
+    db.set("user:6", &user("Johnny", "john@example.com", 40)).unwrap();
+
+    // the constraint is kept after loading the DB
+    let mut read_db = PickleDb::load_read_only("unique_index.db").unwrap();
+    assert!(read_db.set("user:7", &user("Jane2", "jane@example.com", 20)).is_err());
+    assert_eq!(read_db.find_by_index("unique_email", &"jane@example.com"), vec!["user:2"]);
+}
+
+#[test]
+fn namespace_unique_index() {
+    set_test_rsc!("namespace_unique_index.db");
+
+    let mut db = PickleDb::new("namespace_unique_index.db", PickleDbDumpPolicy::AutoDump);
+
+    {
+        let mut users = db.namespace("users");
+        users.create_unique_index("unique_email", "$.email", "").unwrap();
+        users.set("john", &user("John", "john@example.com", 30)).unwrap();
+        assert!(users.set("jane", &user("Jane", "john@example.com", 30)).is_err());
+        users.set("jane", &user("Jane", "jane@example.com", 30)).unwrap();
+        assert_eq!(users.find_by_index("unique_email", &"jane@example.com"), vec!["jane"]);
+    }
+
+    // other namespaces and the DB itself aren't constrained
+    db.namespace("admins").set("john", &user("John", "john@example.com", 30)).unwrap();
+    db.set("john", &user("John", "john@example.com", 30)).unwrap();
+
+    {
+        let mut read_db = PickleDb::load_read_only("namespace_unique_index.db").unwrap();
+        let mut users = read_db.namespace("users");
+        assert!(users.set("bob", &user("Bob", "jane@example.com", 30)).is_err());
+        assert!(users.rem("jane"));
+        users.set("bob", &user("Bob", "jane@example.com", 30)).unwrap();
+    }
+
+    assert!(db.namespace("users").drop_index("unique_email"));
+    db.namespace("users").set("bob", &user("Bob", "john@example.com", 30)).unwrap();
+}
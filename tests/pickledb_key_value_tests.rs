@@ -13,19 +13,19 @@ fn basic_set_get() {
 
     // set a number
     let num = 100;
-    db.set("num", &num);
+    db.set("num", &num).unwrap();
 
     // set a floating point number
     let float_num = 1.224;
-    db.set("float", &float_num);
+    db.set("float", &float_num).unwrap();
 
     // set a String
     let mystr = String::from("my string");
-    db.set("string", &mystr);
+    db.set("string", &mystr).unwrap();
 
     // set a Vec
     let myvec = vec![1,2,3];
-    db.set("vec", &myvec);
+    db.set("vec", &myvec).unwrap();
 
     // set a struct
     #[derive(Serialize, Deserialize, Debug)]
@@ -35,7 +35,7 @@ fn basic_set_get() {
     }
     let mycoor = Coor { x: 1, y
     : 2 };
-    db.set("struct", &mycoor);
+    db.set("struct", &mycoor).unwrap();
 
 
     // read a num
@@ -61,19 +61,19 @@ fn set_load_get() {
 
     // set a number
     let num = 100;
-    db.set("num", &num);
+    db.set("num", &num).unwrap();
 
     // set a floating point number
     let float_num = 1.224;
-    db.set("float", &float_num);
+    db.set("float", &float_num).unwrap();
 
     // set a String
     let mystr = String::from("my string");
-    db.set("string", &mystr);
+    db.set("string", &mystr).unwrap();
 
     // set a Vec
     let myvec = vec![1,2,3];
-    db.set("vec", &myvec);
+    db.set("vec", &myvec).unwrap();
 
     // set a struct
     #[derive(Serialize, Deserialize, Debug)]
@@ -83,7 +83,7 @@ fn set_load_get() {
     }
     let mycoor = Coor { x: 1, y
     : 2 };
-    db.set("struct", &mycoor);
+    db.set("struct", &mycoor).unwrap();
 
 
     // dump db to file
@@ -115,19 +115,19 @@ fn set_load_get_auto_dump() {
 
     // set a number
     let num = 100;
-    db.set("num", &num);
+    db.set("num", &num).unwrap();
 
     // set a floating point number
     let float_num = 1.224;
-    db.set("float", &float_num);
+    db.set("float", &float_num).unwrap();
 
     // set a String
     let mystr = String::from("my string");
-    db.set("string", &mystr);
+    db.set("string", &mystr).unwrap();
 
     // set a Vec
     let myvec = vec![1,2,3];
-    db.set("vec", &myvec);
+    db.set("vec", &myvec).unwrap();
 
     // set a struct
     #[derive(Serialize, Deserialize, Debug)]
@@ -137,7 +137,7 @@ fn set_load_get_auto_dump() {
     }
     let mycoor = Coor { x: 1, y
     : 2 };
-    db.set("struct", &mycoor);
+    db.set("struct", &mycoor).unwrap();
 
 
     let read_db = PickleDb::load_read_only("set_load_get_auto_dump.db").unwrap();
@@ -164,7 +164,7 @@ fn set_load_get_auto_dump2() {
 
     // set a number
     let num = 100;
-    db.set("num", &num);
+    db.set("num", &num).unwrap();
 
     // read this number immediately
     {
@@ -174,7 +174,7 @@ fn set_load_get_auto_dump2() {
 
     // set another number
     let num2 = 200;
-    db.set("num2", &num2);
+    db.set("num2", &num2).unwrap();
 
     // read this other number immediately
     {
@@ -183,7 +183,7 @@ fn set_load_get_auto_dump2() {
     }
 
     // set a different value for a given key
-    db.set("num", &101);
+    db.set("num", &101).unwrap();
 
     // read the new value
     assert_eq!(db.get::<i32>("num").unwrap(), 101);
@@ -193,7 +193,7 @@ fn set_load_get_auto_dump2() {
     }
 
     // set a different value of a different type for a given key
-    db.set("num", &vec![1,2,3]);
+    db.set("num", &vec![1,2,3]).unwrap();
 
     // read the new value
     assert!(db.get::<i32>("num").is_none());
@@ -213,12 +213,12 @@ fn set_special_strings() {
     // create a db with auto_dump == true
     let mut db = PickleDb::new("set_special_strings.db", PickleDbDumpPolicy::AutoDump);
 
-    db.set("string1", &String::from("\"dobule_quotes\""));
-    db.set("string2", &String::from("\'single_quotes\'"));
-    db.set("string3", &String::from("שָׁלוֹם"));
-    db.set("string4", &String::from("😻"));
-    db.set("string5", &String::from("\nescapes\t\r"));
-    db.set("string6", &String::from("my\\folder"));
+    db.set("string1", &String::from("\"dobule_quotes\"")).unwrap();
+    db.set("string2", &String::from("\'single_quotes\'")).unwrap();
+    db.set("string3", &String::from("שָׁלוֹם")).unwrap();
+    db.set("string4", &String::from("😻")).unwrap();
+    db.set("string5", &String::from("\nescapes\t\r")).unwrap();
+    db.set("string6", &String::from("my\\folder")).unwrap();
 
     let read_db = PickleDb::load_read_only("set_special_strings.db").unwrap();
     assert_eq!(read_db.get::<String>("string1").unwrap(), String::from("\"dobule_quotes\""));
@@ -237,7 +237,7 @@ fn edge_cases() {
     let mut db = PickleDb::new("edge_cases.db", PickleDbDumpPolicy::AutoDump);
 
     let x = 123;
-    db.set("num", &x);
+    db.set("num", &x).unwrap();
 
     // load a read only version of the db from file
     let read_db = PickleDb::load_read_only("edge_cases.db").unwrap();
@@ -258,7 +258,7 @@ fn get_all_keys() {
     // insert 10 keys: key0..key9
     let num = 100;
     for i in 0..10 {
-        db.set(&format!("{}{}", "key", i), &num);
+        db.set(&format!("{}{}", "key", i), &num).unwrap();
     }
 
     // verify we have 10 keys
@@ -277,6 +277,26 @@ fn get_all_keys() {
 }
 
 #[test]
+fn get_all_matching_keys() {
+    set_test_rsc!("get_all_matching_keys.db");
+
+    let mut db = PickleDb::new("get_all_matching_keys.db", PickleDbDumpPolicy::AutoDump);
+    db.set("user:1", &1).unwrap();
+    db.set("user:22", &22).unwrap();
+    db.set("config", &"x").unwrap();
+    db.lcreate("user:list");
+
+    let mut keys = db.get_all_matching("user:*");
+    keys.sort();
+    assert_eq!(keys, vec!["user:1", "user:22", "user:list"]);
+    assert_eq!(db.get_all_matching("user:?"), vec!["user:1"]);
+    assert_eq!(db.get_all_matching("config"), vec!["config"]);
+    assert!(db.get_all_matching("conf").is_empty());
+    assert_eq!(db.get_all_matching("*").len(), 4);
+}
+
+#[test]
+#[allow(clippy::useless_vec, clippy::bool_assert_comparison)]
 fn rem_keys() {
     set_test_rsc!("rem_keys.db");
 
@@ -286,7 +306,7 @@ fn rem_keys() {
     // insert 10 keys: key0..key9
     let num = 100;
     for i in 0..10 {
-        db.set(&format!("{}{}", "key", i), &num);
+        db.set(&format!("{}{}", "key", i), &num).unwrap();
     }
 
     // remove 2 keys
@@ -15,19 +15,19 @@ fn basic_lists() {
 
     // add a number to list1
     let num = 100;
-    assert!(db.ladd("list1", &num));
+    assert!(db.ladd("list1", &num).unwrap());
 
     // add a floating point number to list1
     let float_num = 1.224;
-    assert!(db.ladd("list1", &float_num));
+    assert!(db.ladd("list1", &float_num).unwrap());
 
     // add a string to list1
     let mystr = String::from("my string");
-    assert!(db.ladd("list1", &mystr));
+    assert!(db.ladd("list1", &mystr).unwrap());
 
     // add a Vec to list1
     let myvec = vec![1,2,3];
-    assert!(db.ladd("list1", &myvec));
+    assert!(db.ladd("list1", &myvec).unwrap());
 
     // add a struct to list1
     #[derive(Serialize, Deserialize, Debug)]
@@ -37,18 +37,18 @@ fn basic_lists() {
     }
     let mycoor = Coor { x: 1, y
     : 2 };
-    assert!(db.ladd("list1", &mycoor));
+    assert!(db.ladd("list1", &mycoor).unwrap());
 
     // create another list
     db.lcreate("list2");
 
     // add a number to list2
     let num2 = 200;
-    assert!(db.ladd("list2", &num2));
+    assert!(db.ladd("list2", &num2).unwrap());
 
     // add a string to list2
     let mystr2 = String::from("hello world");
-    assert!(db.ladd("list2", &mystr2));
+    assert!(db.ladd("list2", &mystr2).unwrap());
 
 
     // read first item in list1 - int
@@ -122,21 +122,21 @@ fn add_and_extend_lists() {
     db.lcreate("list3");
 
     // list1 - add 6 elements using lextend
-    assert!(db.lextend("list1", &vec![1,2,3,4,5,6]));
+    assert!(db.lextend("list1", &vec![1,2,3,4,5,6]).unwrap());
 
     // list1 - add 6 elements using ladd
-    assert!(db.ladd("list2", &1));
-    assert!(db.ladd("list2", &2));
-    assert!(db.ladd("list2", &3));
-    assert!(db.ladd("list2", &4));
-    assert!(db.ladd("list2", &5));
-    assert!(db.ladd("list2", &6));
+    assert!(db.ladd("list2", &1).unwrap());
+    assert!(db.ladd("list2", &2).unwrap());
+    assert!(db.ladd("list2", &3).unwrap());
+    assert!(db.ladd("list2", &4).unwrap());
+    assert!(db.ladd("list2", &5).unwrap());
+    assert!(db.ladd("list2", &6).unwrap());
 
     // list3 - add 6 elements using lextend and ladd
-    assert!(db.ladd("list3", &1));
-    assert!(db.lextend("list3", &vec![2,3]));
-    assert!(db.ladd("list3", &4));
-    assert!(db.lextend("list3", &vec![5,6]));
+    assert!(db.ladd("list3", &1).unwrap());
+    assert!(db.lextend("list3", &vec![2,3]).unwrap());
+    assert!(db.ladd("list3", &4).unwrap());
+    assert!(db.lextend("list3", &vec![5,6]).unwrap());
 
     // verify lists length
     assert_eq!(db.llen("list1"), 6);
@@ -169,7 +169,7 @@ fn override_lists() {
 
     // create a list and add some values to it
     db.lcreate("list1");
-    assert!(db.lextend("list1", &vec!["aa", "bb", "cc"]));
+    assert!(db.lextend("list1", &vec!["aa", "bb", "cc"]).unwrap());
 
     // verify list len is 3
     assert_eq!(db.llen("list1"), 3);
@@ -189,7 +189,7 @@ fn override_lists() {
     }
 
     // add items to the override list
-    assert!(db.lextend("list1", &vec![1,2,3,4]));
+    assert!(db.lextend("list1", &vec![1,2,3,4]).unwrap());
 
     // verify list contains the new data
     assert!(db.lexists("list1"));
@@ -211,8 +211,8 @@ fn lget_corner_cases() {
 
     // create a list and add some values
     db.lcreate("list1");
-    assert!(db.lextend("list1", &vec!["hello", "world", "good", "morning"]));
-    assert!(db.ladd("list1", &100));
+    assert!(db.lextend("list1", &vec!["hello", "world", "good", "morning"]).unwrap());
+    assert!(db.ladd("list1", &100).unwrap());
 
     // lget values that exist
     assert_eq!(db.lget::<String>("list1", 0).unwrap(), "hello");
@@ -232,6 +232,7 @@ fn lget_corner_cases() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn add_to_non_existent_list() {
     set_test_rsc!("add_to_non_existent_list.db");
 
@@ -241,26 +242,26 @@ fn add_to_non_existent_list() {
     let vec_of_nums = vec![1,2,3];
 
     // add items to list that doesn't exist
-    assert_eq!(db.ladd("list1", &num), false);
-    assert_eq!(db.lextend("list1", &vec_of_nums), false);
+    assert_eq!(db.ladd("list1", &num).unwrap(), false);
+    assert_eq!(db.lextend("list1", &vec_of_nums).unwrap(), false);
 
     // creat a list
     db.lcreate("list1");
 
     // add items to list that doesn't exist
-    assert_eq!(db.ladd("list2", &num), false);
-    assert_eq!(db.lextend("list2", &vec_of_nums), false);
+    assert_eq!(db.ladd("list2", &num).unwrap(), false);
+    assert_eq!(db.lextend("list2", &vec_of_nums).unwrap(), false);
 
     // add items to the list that was created
-    assert!(db.ladd("list1", &num));
-    assert!(db.lextend("list1", &vec_of_nums));
+    assert!(db.ladd("list1", &num).unwrap());
+    assert!(db.lextend("list1", &vec_of_nums).unwrap());
 
     // delete the list
     assert!(db.rem("list1"));
 
     // add items to list that doesn't exist
-    assert_eq!(db.ladd("list1", &num), false);
-    assert_eq!(db.lextend("list1", &vec_of_nums), false);
+    assert_eq!(db.ladd("list1", &num).unwrap(), false);
+    assert_eq!(db.lextend("list1", &vec_of_nums).unwrap(), false);
 }
 
 #[test]
@@ -276,10 +277,10 @@ fn remove_list() {
     db.lcreate("list4");
 
     // add values to lists
-    assert!(db.lextend("list1", &vec![1,2,3,4,5,6,7,8,9,10]));
-    assert!(db.lextend("list2", &vec!['a', 'b', 'c', 'd', 'e']));
-    assert!(db.lextend("list3", &vec![1.2, 1.3, 2.1, 3.1, 3.3, 7.889]));
-    assert!(db.lextend("list4", &vec!["aaa", "bbb", "ccc", "ddd", "eee"]));
+    assert!(db.lextend("list1", &vec![1,2,3,4,5,6,7,8,9,10]).unwrap());
+    assert!(db.lextend("list2", &vec!['a', 'b', 'c', 'd', 'e']).unwrap());
+    assert!(db.lextend("list3", &vec![1.2, 1.3, 2.1, 3.1, 3.3, 7.889]).unwrap());
+    assert!(db.lextend("list4", &vec!["aaa", "bbb", "ccc", "ddd", "eee"]).unwrap());
 
     // verify number of lists in file
     {
@@ -327,10 +328,10 @@ fn remove_values_from_list() {
 
     // create a list and add some values
     db.lcreate("list1");
-    assert!(db.lextend("list1", &vec![1,2,3]));
-    assert!(db.ladd("list1", &String::from("hello")));
-    assert!(db.ladd("list1", &1.234));
-    assert!(db.lextend("list1", &vec![MySquare { x: 4 }, MySquare { x: 10 }]));
+    assert!(db.lextend("list1", &vec![1,2,3]).unwrap());
+    assert!(db.ladd("list1", &String::from("hello")).unwrap());
+    assert!(db.ladd("list1", &1.234).unwrap());
+    assert!(db.lextend("list1", &vec![MySquare { x: 4 }, MySquare { x: 10 }]).unwrap());
 
     // list now looks like this:
     // Indices: [0, 1, 2, 3,       4,     5,           6           ]
@@ -408,12 +409,12 @@ fn list_with_special_strings() {
     db.lcreate("list1");
 
     // add special strings to the list
-    assert!(db.ladd("list1", &String::from("\"dobule_quotes\"")));
-    assert!(db.ladd("list1", &String::from("\'single_quotes\'")));
-    assert!(db.ladd("list1", &String::from("שָׁלוֹם")));
-    assert!(db.ladd("list1", &String::from("😻")));
-    assert!(db.ladd("list1", &String::from("\nescapes\t\r")));
-    assert!(db.ladd("list1", &String::from("my\\folder")));
+    assert!(db.ladd("list1", &String::from("\"dobule_quotes\"")).unwrap());
+    assert!(db.ladd("list1", &String::from("\'single_quotes\'")).unwrap());
+    assert!(db.ladd("list1", &String::from("שָׁלוֹם")).unwrap());
+    assert!(db.ladd("list1", &String::from("😻")).unwrap());
+    assert!(db.ladd("list1", &String::from("\nescapes\t\r")).unwrap());
+    assert!(db.ladd("list1", &String::from("my\\folder")).unwrap());
 
     // read special strings
     assert_eq!(db.lget::<String>("list1", 0).unwrap(), String::from("\"dobule_quotes\""));
@@ -0,0 +1,153 @@
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use std::fs;
+
+mod common;
+
+#[test]
+fn namespaces_are_separate() {
+    set_test_rsc!("namespaces_are_separate.db");
+
+    let mut db = PickleDb::new("namespaces_are_separate.db", PickleDbDumpPolicy::AutoDump);
+
+    db.set("key1", &1).unwrap();
+    db.namespace("billing").set("key1", &2).unwrap();
+    db.namespace("users").set("key1", &3).unwrap();
+    db.namespace("users").set("key2", &String::from("val2")).unwrap();
+
+    // the same key holds different values in each scope
+    assert_eq!(db.get::<i32>("key1").unwrap(), 1);
+    assert_eq!(db.namespace("billing").get::<i32>("key1").unwrap(), 2);
+    assert_eq!(db.namespace("users").get::<i32>("key1").unwrap(), 3);
+
+    // keys of namespaces aren't visible in the DB scope
+    assert!(!db.exists("key2"));
+    assert_eq!(db.total_keys(), 1);
+    assert_eq!(db.get_all(), vec![String::from("key1")]);
+
+    let users = db.namespace("users");
+    assert_eq!(users.total_keys(), 2);
+    let mut keys = users.get_all();
+    keys.sort();
+    assert_eq!(keys, vec![String::from("key1"), String::from("key2")]);
+
+    let mut namespaces = db.list_namespaces();
+    namespaces.sort();
+    assert_eq!(namespaces, vec![String::from("billing"), String::from("users")]);
+
+    // remove a key from a namespace
+    assert!(db.namespace("users").rem("key1"));
+    assert!(!db.namespace("users").rem("key1"));
+    assert!(db.exists("key1"));
+    assert!(db.namespace("billing").exists("key1"));
+}
+
+#[test]
+fn namespace_lists() {
+    set_test_rsc!("namespace_lists.db");
+
+    let mut db = PickleDb::new("namespace_lists.db", PickleDbDumpPolicy::AutoDump);
+
+    {
+        let mut ns = db.namespace("ns1");
+        ns.lcreate("list1");
+        assert!(ns.ladd("list1", &1).unwrap());
+        assert!(ns.lextend("list1", &[2, 3, 4]).unwrap());
+        assert!(!ns.ladd("list2", &1).unwrap());
+
+        // an item that can't be serialized is an error and nothing is added
+        let mut unserializable = std::collections::HashMap::new();
+        unserializable.insert((1, 2), 3);
+        assert!(ns.lextend("list1", &[unserializable]).is_err());
+        assert_eq!(ns.llen("list1"), 4);
+        assert_eq!(ns.lget::<i32>("list1", 1).unwrap(), 2);
+        assert_eq!(ns.lpop::<i32>("list1", 0).unwrap(), 1);
+        assert!(ns.lrem_value("list1", &3));
+        assert!(!ns.lrem_value("list1", &3));
+        assert_eq!(ns.llen("list1"), 2);
+
+        // setting a value overrides a list with the same name
+        ns.lcreate("list2");
+        ns.set("list2", &1).unwrap();
+        assert!(!ns.lexists("list2"));
+    }
+
+    assert!(!db.lexists("list1"));
+
+    assert_eq!(db.namespace("ns1").lrem_list("list1"), 2);
+    assert!(!db.namespace("ns1").lexists("list1"));
+}
+
+#[test]
+fn namespaces_dump_and_load() {
+    set_test_rsc!("namespaces_dump_and_load.db");
+
+    let mut db = PickleDb::new("namespaces_dump_and_load.db", PickleDbDumpPolicy::AutoDump);
+
+    db.set("key1", &1).unwrap();
+    db.namespace("billing").set("key1", &2).unwrap();
+    db.namespace("billing").lcreate("list1");
+    db.namespace("billing").ladd("list1", &String::from("item")).unwrap();
+    db.namespace("users").set("key1", &3).unwrap();
+
+    {
+        let mut read_db = PickleDb::load_read_only("namespaces_dump_and_load.db").unwrap();
+        assert_eq!(read_db.list_namespaces().len(), 2);
+        assert_eq!(read_db.get::<i32>("key1").unwrap(), 1);
+        let billing = read_db.namespace("billing");
+        assert_eq!(billing.get::<i32>("key1").unwrap(), 2);
+        assert_eq!(billing.lget::<String>("list1", 0).unwrap(), "item");
+    }
+
+    // drop a namespace
+    assert!(db.drop_namespace("billing"));
+    assert!(!db.drop_namespace("billing"));
+
+    {
+        let read_db = PickleDb::load_read_only("namespaces_dump_and_load.db").unwrap();
+        assert_eq!(read_db.list_namespaces(), vec![String::from("users")]);
+    }
+}
+
+#[test]
+fn load_file_without_namespaces() {
+    set_test_rsc!("load_file_without_namespaces.db");
+
+    // a file written before namespaces were introduced
+    fs::write("load_file_without_namespaces.db", r#"[{"key1":"1"},{"list1":["2","3"]}]"#).unwrap();
+
+    let db = PickleDb::load_read_only("load_file_without_namespaces.db").unwrap();
+    assert_eq!(db.get::<i32>("key1").unwrap(), 1);
+    assert_eq!(db.llen("list1"), 2);
+    assert!(db.list_namespaces().is_empty());
+}
+
+#[test]
+fn namespace_created_upon_first_change() {
+    set_test_rsc!("namespace_created_upon_first_change.db");
+
+    let mut db = PickleDb::new("namespace_created_upon_first_change.db", PickleDbDumpPolicy::AutoDump);
+
+    // reading from a namespace that doesn't exist neither creates it nor dumps the DB
+    {
+        let mut ns = db.namespace("billing");
+        assert!(ns.get::<i32>("key1").is_none());
+        assert!(!ns.exists("key1"));
+        assert!(ns.get_all().is_empty());
+        assert_eq!(ns.llen("list1"), 0);
+        assert!(!ns.rem("key1"));
+        assert!(!ns.ladd("list1", &1).unwrap());
+        assert!(ns.lpop::<i32>("list1", 0).is_none());
+        assert_eq!(ns.lrem_list("list1"), 0);
+
+        // neither do changes that fail
+        assert!(ns.create_index("index1", "not a path").is_err());
+        assert!(ns.create_unique_index("index1", "$[", "").is_err());
+    }
+    assert!(db.list_namespaces().is_empty());
+    assert!(fs::metadata("namespace_created_upon_first_change.db").is_err());
+
+    db.namespace("billing").set("key1", &1).unwrap();
+    assert_eq!(db.list_namespaces(), vec![String::from("billing")]);
+    let read_db = PickleDb::load_read_only("namespace_created_upon_first_change.db").unwrap();
+    assert_eq!(read_db.list_namespaces(), vec![String::from("billing")]);
+}
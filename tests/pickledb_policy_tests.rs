@@ -0,0 +1,154 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::policy::{DumpPolicy, DumpStats};
+
+mod common;
+
+fn total_keys_in_file(db_file: &str) -> usize {
+    PickleDb::load_read_only(db_file).map_or(0, |db| db.total_keys())
+}
+
+#[test]
+fn dump_every_n_writes() {
+    set_test_rsc!("dump_every_n_writes.db");
+
+    let mut db = PickleDb::new("dump_every_n_writes.db", PickleDbDumpPolicy::DumpEveryNWrites(3));
+    db.set("key1", &1).unwrap();
+    db.lcreate("list1");
+    assert!(!Path::new("dump_every_n_writes.db").exists());
+
+    db.ladd("list1", &1).unwrap();
+    assert_eq!(total_keys_in_file("dump_every_n_writes.db"), 2);
+
+    // the count starts over after every dump, including dumps that are requested
+    db.set("key2", &2).unwrap();
+    db.set("key3", &3).unwrap();
+    assert_eq!(total_keys_in_file("dump_every_n_writes.db"), 2);
+    assert!(db.dump());
+    db.set("key4", &4).unwrap();
+    db.set("key5", &5).unwrap();
+    assert_eq!(total_keys_in_file("dump_every_n_writes.db"), 4);
+    db.rem("key1");
+    assert_eq!(total_keys_in_file("dump_every_n_writes.db"), 5);
+}
+
+#[test]
+fn removing_nothing_isnt_a_write() {
+    set_test_rsc!("removing_nothing_isnt_a_write.db");
+
+    let mut db = PickleDb::new("removing_nothing_isnt_a_write.db", PickleDbDumpPolicy::DumpEveryNWrites(2));
+    db.set("key1", &1).unwrap();
+    assert!(!db.rem("no_such_key"));
+    assert_eq!(db.lrem_list("no_such_list"), 0);
+    assert!(!db.drop_index("no_such_index"));
+    assert!(!db.drop_namespace("no_such_namespace"));
+    assert!(!db.remove_key_schema("no_such_prefix"));
+    assert!(!db.remove_list_schema("no_such_list"));
+    assert!(!db.namespace("ns").rem("no_such_key"));
+    assert_eq!(db.namespace("ns").lrem_list("no_such_list"), 0);
+    assert!(!Path::new("removing_nothing_isnt_a_write.db").exists());
+
+    db.set("key2", &2).unwrap();
+    assert_eq!(total_keys_in_file("removing_nothing_isnt_a_write.db"), 2);
+}
+
+#[test]
+fn dump_after_bytes_changed() {
+    set_test_rsc!("dump_after_bytes_changed.db");
+
+    let mut db = PickleDb::new("dump_after_bytes_changed.db", PickleDbDumpPolicy::DumpAfterBytesChanged(100));
+    // every change counts the key and the JSON of the value, 5 bytes each
+    for i in 0..10 {
+        db.set(&format!("key{}", i), &i).unwrap();
+    }
+    assert!(!Path::new("dump_after_bytes_changed.db").exists());
+
+    db.set("big", &"x".repeat(50)).unwrap();
+    assert_eq!(total_keys_in_file("dump_after_bytes_changed.db"), 11);
+
+    // removing a value counts its size as well, and so do changes in namespaces
+    db.set("small", &0).unwrap();
+    db.rem("big");
+    assert!(PickleDb::load_read_only("dump_after_bytes_changed.db").unwrap().exists("big"));
+    db.namespace("ns").set("key1", &"x".repeat(50)).unwrap();
+    let read = PickleDb::load_read_only("dump_after_bytes_changed.db").unwrap();
+    assert!(read.exists("small"));
+    assert!(!read.exists("big"));
+}
+
+#[test]
+fn dump_any_policy() {
+    set_test_rsc!("dump_any_policy.db");
+
+    let mut db = PickleDb::new("dump_any_policy.db", PickleDbDumpPolicy::Any(vec![
+        PickleDbDumpPolicy::DumpEveryNWrites(3),
+        PickleDbDumpPolicy::PeriodicDump(Duration::from_millis(100)),
+    ]));
+    db.set("key1", &1).unwrap();
+    db.set("key2", &2).unwrap();
+    assert!(!Path::new("dump_any_policy.db").exists());
+    db.set("key3", &3).unwrap();
+    assert_eq!(total_keys_in_file("dump_any_policy.db"), 3);
+
+    db.set("key4", &4).unwrap();
+    assert_eq!(total_keys_in_file("dump_any_policy.db"), 3);
+    std::thread::sleep(Duration::from_millis(150));
+    db.set("key5", &5).unwrap();
+    assert_eq!(total_keys_in_file("dump_any_policy.db"), 5);
+}
+
+struct RecordingPolicy {
+    seen: Arc<Mutex<Vec<DumpStats>>>,
+    uses_bytes: bool,
+}
+
+impl DumpPolicy for RecordingPolicy {
+    fn should_dump(&mut self, stats: &DumpStats) -> bool {
+        self.seen.lock().unwrap().push(*stats);
+        stats.writes == 2
+    }
+
+    fn uses_bytes(&self) -> bool {
+        self.uses_bytes
+    }
+}
+
+#[test]
+fn dump_custom_policy() {
+    set_test_rsc!("dump_custom_policy.db");
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let policy = RecordingPolicy { seen: seen.clone(), uses_bytes: true };
+    let mut db = PickleDb::new("dump_custom_policy.db", PickleDbDumpPolicy::Custom(Box::new(policy)));
+    db.set("key1", &1).unwrap();
+    assert!(!Path::new("dump_custom_policy.db").exists());
+    db.set("key2", &"ab").unwrap();
+    assert_eq!(total_keys_in_file("dump_custom_policy.db"), 2);
+    db.set("key3", &3).unwrap();
+
+    let seen: Vec<(u64, u64)> = seen.lock().unwrap().iter().map(|stats| (stats.writes, stats.bytes)).collect();
+    assert_eq!(seen, vec![(1, 5), (2, 13), (1, 5)]);
+}
+
+#[test]
+fn bytes_counted_only_when_used() {
+    set_test_rsc!("bytes_counted_only_when_used.db");
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let policy = RecordingPolicy { seen: seen.clone(), uses_bytes: false };
+    let mut db = PickleDb::new("bytes_counted_only_when_used.db", PickleDbDumpPolicy::Custom(Box::new(policy)));
+    db.set("key1", &1).unwrap();
+    db.lcreate("list1");
+    db.set_dump_policy(PickleDbDumpPolicy::Any(vec![
+        PickleDbDumpPolicy::DumpEveryNWrites(100),
+        PickleDbDumpPolicy::DumpAfterBytesChanged(100),
+        PickleDbDumpPolicy::Custom(Box::new(RecordingPolicy { seen: seen.clone(), uses_bytes: false })),
+    ]));
+    db.ladd("list1", &"ab").unwrap();
+
+    // the bytes are counted only while a policy uses them, even if it's nested in Any
+    let seen: Vec<(u64, u64)> = seen.lock().unwrap().iter().map(|stats| (stats.writes, stats.bytes)).collect();
+    assert_eq!(seen, vec![(1, 0), (2, 0), (1, 9)]);
+}
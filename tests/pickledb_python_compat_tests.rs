@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::fs;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use serde_json::{json, Value};
+
+mod common;
+
+/// The file Python's PickleDB writes after `set("num", 100)`, `set("name", "john")`, `lcreate("list1")`,
+/// `ladd("list1", 1)`, `ladd("list1", "two")` and `dcreate("dict1")`, `dadd("dict1", ("a", 1))`
+const PYTHON_DB: &str = r#"{"num": 100, "name": "john", "list1": [1, "two"], "dict1": {"a": 1}}"#;
+
+fn read_json(location: &str) -> Value {
+    serde_json::from_str(&fs::read_to_string(location).unwrap()).unwrap()
+}
+
+#[test]
+fn load_python_compat() {
+    set_test_rsc!("load_python_compat.db");
+
+    fs::write("load_python_compat.db", PYTHON_DB).unwrap();
+    let mut db = PickleDb::load_python_compat("load_python_compat.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    assert!(db.python_compat());
+    assert_eq!(db.get::<i32>("num").unwrap(), 100);
+    assert_eq!(db.get::<String>("name").unwrap(), "john");
+    assert_eq!(db.get::<HashMap<String, i32>>("dict1").unwrap()["a"], 1);
+    assert!(db.lexists("list1"));
+    assert_eq!(db.lget::<i32>("list1", 0).unwrap(), 1);
+    assert_eq!(db.lget::<String>("list1", 1).unwrap(), "two");
+
+    // changes are dumped in the same format, so Python can read them
+    db.set("num", &200).unwrap();
+    db.ladd("list1", &json!({"three": 3})).unwrap();
+    db.rem("name");
+    assert_eq!(read_json("load_python_compat.db"), json!({
+        "num": 200,
+        "list1": [1, "two", {"three": 3}],
+        "dict1": {"a": 1},
+    }));
+}
+
+#[test]
+fn python_compat_dump_mode() {
+    set_test_rsc!("python_compat_dump_mode.db");
+
+    let mut db = PickleDb::new("python_compat_dump_mode.db", PickleDbDumpPolicy::AutoDump);
+    assert!(!db.python_compat());
+    db.set_python_compat(true);
+    db.set("key1", &String::from("value1")).unwrap();
+    db.lcreate("list1");
+    db.lextend("list1", &vec![1, 2]).unwrap();
+    assert_eq!(read_json("python_compat_dump_mode.db"), json!({"key1": "value1", "list1": [1, 2]}));
+
+    // the file isn't in the format of this crate
+    let err = PickleDb::load_read_only("python_compat_dump_mode.db").err().unwrap();
+    assert!(err.to_string().contains("load_python_compat()"));
+
+    let db = PickleDb::load_python_compat("python_compat_dump_mode.db", PickleDbDumpPolicy::NeverDump).unwrap();
+    assert_eq!(db.get::<String>("key1").unwrap(), "value1");
+    assert_eq!(db.llen("list1"), 2);
+
+    // turning the mode off dumps the format of this crate again
+    let mut db = PickleDb::load_python_compat("python_compat_dump_mode.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    db.set_python_compat(false);
+    db.dump();
+    let db = PickleDb::load_read_only("python_compat_dump_mode.db").unwrap();
+    assert_eq!(db.get::<String>("key1").unwrap(), "value1");
+    assert_eq!(db.llen("list1"), 2);
+}
+
+#[test]
+fn python_compat_reload() {
+    set_test_rsc!("python_compat_reload.db");
+
+    fs::write("python_compat_reload.db", PYTHON_DB).unwrap();
+    let mut db = PickleDb::load_python_compat("python_compat_reload.db", PickleDbDumpPolicy::NeverDump).unwrap();
+
+    // a Python service changes the file
+    fs::write("python_compat_reload.db", r#"{"num": 101, "list1": [1, "two", 3]}"#).unwrap();
+    let mut changed = db.reload().unwrap();
+    changed.sort();
+    assert_eq!(changed, vec!["dict1", "list1", "name", "num"]);
+    assert_eq!(db.get::<i32>("num").unwrap(), 101);
+    assert_eq!(db.llen("list1"), 3);
+    assert!(!db.exists("dict1"));
+}
@@ -0,0 +1,141 @@
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::error::Error;
+use pickledb::query::{JsonPath, Query};
+
+mod common;
+
+#[macro_use]
+extern crate serde_derive;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Address {
+    city: String,
+    zip: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct User {
+    name: String,
+    age: u32,
+    address: Address,
+    tags: Vec<String>,
+}
+
+fn user(name: &str, age: u32, city: &str) -> User {
+    User {
+        name: String::from(name),
+        age,
+        address: Address { city: String::from(city), zip: 1000 + age },
+        tags: vec![String::from("tag1"), String::from(name)],
+    }
+}
+
+fn sorted(mut keys: Vec<String>) -> Vec<String> {
+    keys.sort();
+    keys
+}
+
+#[test]
+fn query_values() {
+    set_test_rsc!("query_values.db");
+
+    let mut db = PickleDb::new("query_values.db", PickleDbDumpPolicy::DumpUponRequest);
+    db.set("john", &user("john", 35, "London")).unwrap();
+    db.set("jane", &user("jane", 25, "Paris")).unwrap();
+    db.set("bob", &user("bob", 30, "London")).unwrap();
+    db.set("num", &100).unwrap();
+
+    assert_eq!(sorted(db.query("$.age > 30").unwrap()), vec!["john"]);
+    assert_eq!(sorted(db.query("$.age >= 30").unwrap()), vec!["bob", "john"]);
+    assert_eq!(sorted(db.query("$.age < 30").unwrap()), vec!["jane"]);
+    assert_eq!(sorted(db.query("$.age <= 30.0").unwrap()), vec!["bob", "jane"]);
+    assert_eq!(sorted(db.query("$.address.city == \"London\"").unwrap()), vec!["bob", "john"]);
+    assert_eq!(sorted(db.query("$['address']['city'] != \"London\"").unwrap()), vec!["jane"]);
+    assert_eq!(sorted(db.query("$.tags[1] == \"jane\"").unwrap()), vec!["jane"]);
+
+    // a path without a condition matches all values in which it exists
+    assert_eq!(sorted(db.query("$.address").unwrap()), vec!["bob", "jane", "john"]);
+    assert_eq!(sorted(db.query("$ > 50").unwrap()), vec!["num"]);
+
+    // comparing values of different types doesn't match
+    assert!(db.query("$.name > 3").unwrap().is_empty());
+
+    // builder API
+    let query = Query::path("$.address.city").unwrap().eq(&"Paris");
+    assert_eq!(db.query_with(&query), vec!["jane"]);
+    let query = Query::path("$.age").unwrap().le(&25);
+    assert_eq!(db.query_with(&query), vec!["jane"]);
+}
+
+#[test]
+fn query_lists() {
+    set_test_rsc!("query_lists.db");
+
+    let mut db = PickleDb::new("query_lists.db", PickleDbDumpPolicy::DumpUponRequest);
+    db.lcreate("users");
+    db.lextend("users", &vec![user("john", 35, "London"), user("jane", 25, "Paris"), user("bob", 30, "London")]).unwrap();
+    db.ladd("users", &String::from("not a user")).unwrap();
+
+    assert_eq!(db.lquery("users", "$.address.city == \"London\"").unwrap(), vec![0, 2]);
+    assert_eq!(db.lquery("users", "$.age > 100").unwrap(), Vec::<usize>::new());
+    assert_eq!(db.lquery_with("users", &Query::path("$.name").unwrap()), vec![0, 1, 2]);
+    assert!(db.lquery("no_such_list", "$.age > 1").unwrap().is_empty());
+}
+
+#[test]
+fn get_by_path() {
+    set_test_rsc!("get_by_path.db");
+
+    let mut db = PickleDb::new("get_by_path.db", PickleDbDumpPolicy::DumpUponRequest);
+    db.set("john", &user("john", 35, "London")).unwrap();
+    db.lcreate("users");
+    db.ladd("users", &user("jane", 25, "Paris")).unwrap();
+
+    assert_eq!(db.get_path::<String>("john", "$.address.city").unwrap(), "London");
+    assert_eq!(db.get_path::<u32>("john", "$.age").unwrap(), 35);
+    assert_eq!(db.get_path::<Address>("john", "$.address").unwrap(), Address { city: String::from("London"), zip: 1035 });
+    assert_eq!(db.get_path::<String>("john", "$.tags[0]").unwrap(), "tag1");
+    assert_eq!(db.get_path::<User>("john", "$").unwrap(), user("john", 35, "London"));
+
+    // missing key, missing path, wrong type and invalid path
+    assert!(db.get_path::<String>("jane", "$.name").is_none());
+    assert!(db.get_path::<String>("john", "$.phone").is_none());
+    assert!(db.get_path::<String>("john", "$.tags[5]").is_none());
+    assert!(db.get_path::<u32>("john", "$.name").is_none());
+    assert!(db.get_path::<String>("john", "name").is_none());
+
+    assert_eq!(db.lget_path::<String>("users", 0, "$.address.city").unwrap(), "Paris");
+    assert!(db.lget_path::<String>("users", 1, "$.address.city").is_none());
+}
+
+#[test]
+fn invalid_queries() {
+    let db = PickleDb::new("invalid_queries.db", PickleDbDumpPolicy::NeverDump);
+
+    for expr in &["age > 30", "$.age >> 30", "$.age > thirty", "$.tags[x]", "$.tags[0", "$. > 1", "$.age ~ 1"] {
+        match db.query(expr) {
+            Err(Error::InvalidQuery { query, .. }) => assert_eq!(&query, expr),
+            _ => panic!("expected query '{}' to be invalid", expr),
+        }
+    }
+
+    assert!(JsonPath::parse("$.a.b[2]['c d']").is_ok());
+    assert!(JsonPath::parse("$.a > 1").is_err());
+}
+
+#[test]
+fn json_path_round_trip() {
+    for path in &["$.a]b", "$['it\\'s']", "$['a\\\\b'][0]", "$[\"x']y\"]", "$.tags[2]"] {
+        let parsed = JsonPath::parse(path).unwrap();
+        assert_eq!(JsonPath::parse(&parsed.to_string()).unwrap(), parsed);
+    }
+    assert_eq!(JsonPath::parse("$.a]b").unwrap().to_string(), "$['a]b']");
+    assert_eq!(JsonPath::parse("$['it\\'s']").unwrap().to_string(), "$['it\\'s']");
+
+    let value = serde_json::json!({"a]b": 1, "it's": 2, "a\\b": 3});
+    assert_eq!(JsonPath::parse("$.a]b").unwrap().select(&value), Some(&serde_json::json!(1)));
+    assert_eq!(JsonPath::parse("$['it\\'s']").unwrap().select(&value), Some(&serde_json::json!(2)));
+    assert_eq!(JsonPath::parse("$['a\\\\b']").unwrap().select(&value), Some(&serde_json::json!(3)));
+    assert!(JsonPath::parse("$['a").is_err());
+    assert!(JsonPath::parse("$['a'").is_err());
+}
@@ -0,0 +1,155 @@
+use std::fs;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::error::Error;
+use pickledb::watch::{ChangeEvent, ChangeKind};
+use serde_json::json;
+
+mod common;
+
+#[test]
+fn reload_changes() {
+    set_test_rsc!("reload_changes.db");
+
+    let mut writer = PickleDb::new("reload_changes.db", PickleDbDumpPolicy::AutoDump);
+    writer.set("key1", &1).unwrap();
+    writer.set("key2", &2).unwrap();
+    writer.lcreate("list1");
+    writer.lextend("list1", &vec![1, 2]).unwrap();
+
+    let mut reader = PickleDb::load_read_only("reload_changes.db").unwrap();
+    let sub = reader.subscribe("*");
+    assert!(reader.reload().unwrap().is_empty());
+
+    writer.set("key1", &10).unwrap();
+    assert!(writer.rem("key2"));
+    writer.ladd("list1", &3).unwrap();
+    writer.set("key3", &"new").unwrap();
+
+    assert_eq!(reader.reload().unwrap(), vec!["key1", "key2", "key3", "list1"]);
+    assert_eq!(reader.get::<i32>("key1").unwrap(), 10);
+    assert!(!reader.exists("key2"));
+    assert_eq!(reader.llen("list1"), 3);
+
+    let events: Vec<ChangeEvent> = std::iter::from_fn(|| sub.try_recv().ok()).collect();
+    assert_eq!(events, vec![
+        ChangeEvent { key: String::from("key1"), kind: ChangeKind::Reloaded, old: Some(json!(1)), new: Some(json!(10)), namespace: None },
+        ChangeEvent { key: String::from("key2"), kind: ChangeKind::Reloaded, old: Some(json!(2)), new: None, namespace: None },
+        ChangeEvent { key: String::from("key3"), kind: ChangeKind::Reloaded, old: None, new: Some(json!("new")), namespace: None },
+        ChangeEvent { key: String::from("list1"), kind: ChangeKind::Reloaded, old: Some(json!([1, 2])), new: Some(json!([1, 2, 3])), namespace: None },
+    ]);
+
+    // nothing changed since the last reload
+    assert!(reader.reload().unwrap().is_empty());
+    assert!(sub.try_recv().is_err());
+}
+
+#[test]
+fn reload_validates_content() {
+    set_test_rsc!("reload_validates_content.db");
+
+    let mut writer = PickleDb::new("reload_validates_content.db", PickleDbDumpPolicy::AutoDump);
+    writer.set("key1", &1).unwrap();
+    writer.set("user:1", &json!({"name": "john"})).unwrap();
+
+    let mut reader = PickleDb::load_read_only("reload_validates_content.db").unwrap();
+    reader.set_key_schema("key", &json!({"type": "integer"})).unwrap();
+    reader.create_unique_index("names", "$.name", "user:").unwrap();
+
+    // a value that violates a schema of the reader
+    writer.set("key1", &"not a number").unwrap();
+    match reader.reload() {
+        Err(Error::SchemaViolation { key, .. }) => assert_eq!(key, "key1"),
+        _ => panic!("expected a schema violation"),
+    }
+    assert_eq!(reader.get::<i32>("key1").unwrap(), 1);
+
+    // a value that violates a unique index of the reader
+    writer.set("key1", &2).unwrap();
+    writer.set("user:2", &json!({"name": "john"})).unwrap();
+    assert!(reader.reload().is_err());
+    assert!(!reader.exists("user:2"));
+
+    // a file that can't be parsed
+    writer.rem("user:2");
+    fs::write("reload_validates_content.db", "{\"map\": {\"key1\": ").unwrap();
+    assert!(reader.reload().is_err());
+    assert_eq!(reader.get::<i32>("key1").unwrap(), 1);
+
+    writer.dump();
+    assert_eq!(reader.reload().unwrap(), vec!["key1"]);
+    assert_eq!(reader.get::<i32>("key1").unwrap(), 2);
+}
+
+#[cfg(feature = "file-watch")]
+mod file_watch {
+    use std::fs;
+    use std::time::{Duration, Instant};
+    use pickledb::{PickleDb, PickleDbDumpPolicy};
+    use pickledb::file_watch::WatchedPickleDb;
+    use pickledb::watch::{ChangeKind, Subscription};
+    use serde_json::json;
+    use super::common;
+    use crate::set_test_rsc;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn wait_for(sub: &Subscription, key: &str) -> Option<serde_json::Value> {
+        loop {
+            let event = sub.recv_timeout(TIMEOUT).expect("the file change wasn't reloaded");
+            assert_eq!(event.kind, ChangeKind::Reloaded);
+            if event.key == key {
+                return event.new;
+            }
+        }
+    }
+
+    fn check_watched(db_file: &str, watched: WatchedPickleDb) {
+        let mut writer = PickleDb::load(db_file, PickleDbDumpPolicy::AutoDump).unwrap();
+        let sub = watched.lock().subscribe("*");
+
+        writer.set("key1", &2).unwrap();
+        assert_eq!(wait_for(&sub, "key1"), Some(json!(2)));
+        assert_eq!(watched.lock().get::<i32>("key1").unwrap(), 2);
+
+        // a file that is replaced by another one
+        fs::write(format!("{}.tmp", db_file), r#"{"map": {"key1": 3}, "list_map": {}}"#).unwrap();
+        fs::rename(format!("{}.tmp", db_file), db_file).unwrap();
+        assert_eq!(wait_for(&sub, "key1"), Some(json!(3)));
+
+        // content that can't be parsed is reported and doesn't replace the data
+        fs::write(db_file, "[1, 2").unwrap();
+        let deadline = Instant::now() + TIMEOUT;
+        while watched.take_reload_error().is_none() {
+            assert!(Instant::now() < deadline, "the invalid content wasn't reported");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(watched.lock().get::<i32>("key1").unwrap(), 3);
+
+        writer.set("key2", &"hello").unwrap();
+        assert_eq!(wait_for(&sub, "key2"), Some(json!("hello")));
+        assert_eq!(watched.lock().get::<i32>("key1").unwrap(), 2);
+    }
+
+    #[test]
+    fn watch_file() {
+        set_test_rsc!("watch_file.db");
+
+        let mut db = PickleDb::new("watch_file.db", PickleDbDumpPolicy::AutoDump);
+        db.set("key1", &1).unwrap();
+        let watched = PickleDb::load_read_only("watch_file.db").unwrap().watch_file().unwrap();
+        check_watched("watch_file.db", watched);
+    }
+
+    #[test]
+    fn watch_file_polling() {
+        set_test_rsc!("watch_file_polling.db");
+
+        let mut db = PickleDb::new("watch_file_polling.db", PickleDbDumpPolicy::AutoDump);
+        db.set("key1", &1).unwrap();
+        let watched = PickleDb::load_read_only("watch_file_polling.db").unwrap()
+            .watch_file_polling(Duration::from_millis(20))
+            .unwrap();
+        assert!(watched.is_polling());
+        check_watched("watch_file_polling.db", watched);
+    }
+}
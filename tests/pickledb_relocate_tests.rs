@@ -0,0 +1,78 @@
+use std::path::Path;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+
+mod common;
+
+#[test]
+fn set_dump_policy_around_bulk_changes() {
+    set_test_rsc!("set_dump_policy_around_bulk_changes.db");
+
+    let mut db = PickleDb::new("set_dump_policy_around_bulk_changes.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key0", &0).unwrap();
+
+    let previous = db.set_dump_policy(PickleDbDumpPolicy::DumpUponRequest);
+    assert!(matches!(previous, PickleDbDumpPolicy::AutoDump));
+    for i in 1..10 {
+        db.set(&format!("key{}", i), &i).unwrap();
+    }
+    assert_eq!(PickleDb::load_read_only("set_dump_policy_around_bulk_changes.db").unwrap().total_keys(), 1);
+
+    db.set_dump_policy(previous);
+    db.set("key10", &10).unwrap();
+    assert_eq!(PickleDb::load_read_only("set_dump_policy_around_bulk_changes.db").unwrap().total_keys(), 11);
+
+    // nothing is dumped after switching to NeverDump, not even when the DB is dropped
+    db.set_dump_policy(PickleDbDumpPolicy::NeverDump);
+    db.set("key11", &11).unwrap();
+    drop(db);
+    assert_eq!(PickleDb::load_read_only("set_dump_policy_around_bulk_changes.db").unwrap().total_keys(), 11);
+}
+
+#[test]
+fn save_as_writes_copy() {
+    set_test_rsc!("save_as_writes_copy.db");
+    set_test_rsc!("save_as_writes_copy2.db");
+
+    let mut db = PickleDb::new("save_as_writes_copy.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &1).unwrap();
+    db.lcreate("list1");
+    db.ladd("list1", &"item").unwrap();
+    db.save_as("save_as_writes_copy2.db").unwrap();
+
+    // the copy isn't changed by later changes
+    db.set("key2", &2).unwrap();
+    let copy = PickleDb::load_read_only("save_as_writes_copy2.db").unwrap();
+    assert_eq!(copy.get::<i32>("key1").unwrap(), 1);
+    assert_eq!(copy.lget::<String>("list1", 0).unwrap(), "item");
+    assert!(!copy.exists("key2"));
+    assert!(PickleDb::load_read_only("save_as_writes_copy.db").unwrap().exists("key2"));
+
+    assert!(db.save_as("no_such_dir/save_as_writes_copy.db").is_err());
+}
+
+#[test]
+fn relocate_adopts_new_file() {
+    set_test_rsc!("relocate_adopts_new_file.db");
+    set_test_rsc!("relocate_adopts_new_file2.db");
+
+    let mut db = PickleDb::new("relocate_adopts_new_file.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &1).unwrap();
+
+    assert!(db.relocate("no_such_dir/relocate_adopts_new_file.db").is_err());
+    db.set("key2", &2).unwrap();
+    assert!(PickleDb::load_read_only("relocate_adopts_new_file.db").unwrap().exists("key2"));
+
+    db.relocate("relocate_adopts_new_file2.db").unwrap();
+    assert_eq!(PickleDb::load_read_only("relocate_adopts_new_file2.db").unwrap().total_keys(), 2);
+    db.set("key3", &3).unwrap();
+    assert!(PickleDb::load_read_only("relocate_adopts_new_file2.db").unwrap().exists("key3"));
+    assert!(!PickleDb::load_read_only("relocate_adopts_new_file.db").unwrap().exists("key3"));
+
+    // a DB that was never dumped is written to its new file too
+    drop(db);
+    let mut db = PickleDb::new("relocate_adopts_new_file3.db", PickleDbDumpPolicy::DumpUponRequest);
+    db.set("key1", &1).unwrap();
+    db.relocate("relocate_adopts_new_file.db").unwrap();
+    assert!(!Path::new("relocate_adopts_new_file3.db").exists());
+    assert_eq!(PickleDb::load_read_only("relocate_adopts_new_file.db").unwrap().total_keys(), 1);
+}
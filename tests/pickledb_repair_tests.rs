@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::Path;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::error::Error;
+
+mod common;
+
+#[test]
+fn intact_file() {
+    set_test_rsc!("intact_file.db");
+
+    let mut db = PickleDb::new("intact_file.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &1).unwrap();
+
+    let (loaded, report) = PickleDb::load_lenient("intact_file.db", PickleDbDumpPolicy::AutoDump).unwrap();
+    assert!(report.is_intact());
+    assert!(report.dropped.is_empty());
+    assert_eq!(loaded.get::<i32>("key1").unwrap(), 1);
+
+    assert!(PickleDb::repair("intact_file.db").unwrap().is_intact());
+    assert!(!Path::new("intact_file.db.damaged").exists());
+}
+
+#[test]
+fn truncated_file() {
+    set_test_rsc!("truncated_file.db");
+
+    let mut db = PickleDb::new("truncated_file.db", PickleDbDumpPolicy::DumpUponRequest);
+    for i in 0..100 {
+        db.set(&format!("key{}", i), &format!("value{}", i)).unwrap();
+    }
+    db.dump();
+    let contents = fs::read("truncated_file.db").unwrap();
+    fs::write("truncated_file.db", &contents[..contents.len() / 2]).unwrap();
+    assert!(PickleDb::load_read_only("truncated_file.db").is_err());
+
+    let (loaded, report) = PickleDb::load_lenient("truncated_file.db", PickleDbDumpPolicy::NeverDump).unwrap();
+    assert!(report.error.is_some());
+    assert!(report.recovered_keys > 40 && report.recovered_keys < 60);
+    assert_eq!(report.recovered_keys, loaded.total_keys());
+    for key in loaded.get_all() {
+        assert_eq!(loaded.get::<String>(&key).unwrap(), key.replace("key", "value"));
+    }
+    assert!(report.dropped.contains(&String::from("the end of the file, which is truncated")));
+    assert!(report.restored_from.is_none());
+}
+
+#[test]
+fn corrupted_values() {
+    set_test_rsc!("corrupted_values.db");
+
+    fs::write(
+        "corrupted_values.db",
+        r#"{"map":{"key1":1,"key2":tru,"key3":"three"},"list_map":{"list1":[1,tru,3],"list2":["a"]}}"#,
+    ).unwrap();
+    let (loaded, report) = PickleDb::load_lenient("corrupted_values.db", PickleDbDumpPolicy::NeverDump).unwrap();
+    assert!(matches!(report.error, Some(Error::Serialization(_))));
+    assert_eq!(report.dropped, vec!["key 'key2' of the map", "item 1 of list 'list1' of the lists"]);
+    assert_eq!((report.recovered_keys, report.recovered_lists), (2, 2));
+    assert_eq!(loaded.get::<String>("key3").unwrap(), "three");
+    assert_eq!(loaded.lget::<i32>("list1", 1).unwrap(), 3);
+
+    // the structure around content that can't be read is followed from the next section
+    fs::write("corrupted_values.db", r#"{"map":{"key1":1}#,"list_map":{"list1":[1]}}"#).unwrap();
+    let (loaded, report) = PickleDb::load_lenient("corrupted_values.db", PickleDbDumpPolicy::NeverDump).unwrap();
+    assert_eq!(report.dropped, vec!["part of the file, which can't be read"]);
+    assert!(loaded.exists("key1"));
+    assert_eq!(loaded.llen("list1"), 1);
+
+    // keys of values that are named like the fields of the file aren't taken for them
+    fs::write(
+        "corrupted_values.db",
+        r#"{"map":{"key1":1]],"user":{"id":0,"map":{"a":1}},"key2":{"x":0,"map":{"b":2},"list_map":{}}},"list_map":{"list1":[1]}}"#,
+    ).unwrap();
+    let (loaded, report) = PickleDb::load_lenient("corrupted_values.db", PickleDbDumpPolicy::NeverDump).unwrap();
+    assert_eq!(report.dropped, vec!["the rest of the map, which can't be read", "part of the file, which can't be read"]);
+    assert!(loaded.exists("key1"));
+    assert!(!loaded.exists("a"));
+    assert!(!loaded.exists("b"));
+    assert_eq!(loaded.llen("list1"), 1);
+}
+
+#[test]
+fn truncated_legacy_file() {
+    set_test_rsc!("truncated_legacy_file.db");
+
+    fs::write("truncated_legacy_file.db", r#"[{"key1":"1","key2":"\"two\""},{"list1":["1","2"],"list2":["3"#).unwrap();
+    let (loaded, report) = PickleDb::load_lenient("truncated_legacy_file.db", PickleDbDumpPolicy::NeverDump).unwrap();
+    assert_eq!(loaded.get::<String>("key2").unwrap(), "two");
+    assert_eq!(loaded.llen("list1"), 2);
+    assert_eq!(loaded.llen("list2"), 0);
+    assert!(report.dropped.contains(&String::from("item 0 of list 'list2' of the lists")));
+}
+
+#[test]
+fn repair_from_backup() {
+    set_test_rsc!("repair_from_backup.db");
+    set_test_rsc!("repair_from_backup.db.1");
+    set_test_rsc!("repair_from_backup.db.2");
+    set_test_rsc!("repair_from_backup.db.damaged");
+
+    let mut db = PickleDb::new("repair_from_backup.db", PickleDbDumpPolicy::AutoDump);
+    db.set_backup_rotation(2);
+    db.set("key1", &1).unwrap();
+    db.set("key1", &2).unwrap();
+    db.set("key1", &3).unwrap();
+    drop(db);
+
+    // the newest backup is damaged as well, so the one before it is used
+    fs::write("repair_from_backup.db", "\u{0}\u{0}\u{0}").unwrap();
+    fs::write("repair_from_backup.db.1", "{\"map\": {\"key1\": ").unwrap();
+    let report = PickleDb::repair("repair_from_backup.db").unwrap();
+    assert_eq!(report.restored_from.as_deref(), Some("repair_from_backup.db.2"));
+    assert_eq!(report.recovered_keys, 1);
+    assert_eq!(fs::read("repair_from_backup.db.damaged").unwrap(), b"\0\0\0");
+
+    let repaired = PickleDb::load("repair_from_backup.db", PickleDbDumpPolicy::NeverDump).unwrap();
+    assert_eq!(repaired.get::<i32>("key1").unwrap(), 1);
+    assert!(PickleDb::verify("repair_from_backup.db").is_ok());
+}
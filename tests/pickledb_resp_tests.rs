@@ -0,0 +1,200 @@
+#![cfg(feature = "resp")]
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use pickledb::resp::{RespClient, RespServer, RespValue};
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+
+mod common;
+
+/// Serve a DB on a free local port, and return a client connected to it
+fn serve(server: RespServer) -> RespClient {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || server.serve_tcp(listener));
+    RespClient::connect_tcp(addr).unwrap()
+}
+
+fn bulk(text: &str) -> RespValue {
+    RespValue::Bulk(Some(String::from(text)))
+}
+
+#[test]
+fn resp_key_commands() {
+    set_test_rsc!("resp_key_commands.db");
+
+    let mut db = PickleDb::new("resp_key_commands.db", PickleDbDumpPolicy::AutoDump);
+    db.set("num", &100).unwrap();
+    db.set("name", &String::from("john")).unwrap();
+    let mut client = serve(RespServer::new(db));
+
+    assert_eq!(client.command(&["PING"]).unwrap(), RespValue::Simple(String::from("PONG")));
+    assert_eq!(client.command(&["GET", "num"]).unwrap(), bulk("100"));
+    assert_eq!(client.command(&["get", "name"]).unwrap(), bulk("john"));
+    assert_eq!(client.command(&["GET", "missing"]).unwrap(), RespValue::Bulk(None));
+
+    assert_eq!(client.command(&["SET", "num", "200"]).unwrap(), RespValue::Simple(String::from("OK")));
+    assert_eq!(client.command(&["SET", "greeting", "hello world"]).unwrap(), RespValue::Simple(String::from("OK")));
+    assert_eq!(client.command(&["EXISTS", "num", "greeting", "missing"]).unwrap(), RespValue::Integer(2));
+    assert_eq!(client.command(&["KEYS", "n*"]).unwrap(), RespValue::Array(vec![bulk("name"), bulk("num")]));
+    assert_eq!(client.command(&["DEL", "name", "missing"]).unwrap(), RespValue::Integer(1));
+
+    assert!(matches!(client.command(&["GET"]).unwrap(), RespValue::Error(err) if err.starts_with("ERR wrong number")));
+    assert!(matches!(client.command(&["FLUSHALL"]).unwrap(), RespValue::Error(err) if err.starts_with("ERR unknown command")));
+
+    // the changes were dumped, and values sent as JSON were stored as JSON
+    let db = PickleDb::load_read_only("resp_key_commands.db").unwrap();
+    assert_eq!(db.get::<i32>("num").unwrap(), 200);
+    assert_eq!(db.get::<String>("greeting").unwrap(), "hello world");
+    assert!(!db.exists("name"));
+}
+
+#[test]
+fn resp_list_commands() {
+    set_test_rsc!("resp_list_commands.db");
+
+    let db = PickleDb::new("resp_list_commands.db", PickleDbDumpPolicy::AutoDump);
+    let mut client = serve(RespServer::new(db));
+
+    assert_eq!(client.command(&["RPUSH", "list1", "a", "b", "a", "c", "a"]).unwrap(), RespValue::Integer(5));
+    assert_eq!(client.command(&["LLEN", "list1"]).unwrap(), RespValue::Integer(5));
+    assert_eq!(client.command(&["LINDEX", "list1", "1"]).unwrap(), bulk("b"));
+    assert_eq!(client.command(&["LINDEX", "list1", "-2"]).unwrap(), bulk("c"));
+    assert_eq!(client.command(&["LINDEX", "list1", "5"]).unwrap(), RespValue::Bulk(None));
+
+    // a negative count removes matching items from the tail
+    assert_eq!(client.command(&["LREM", "list1", "-1", "a"]).unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["LPOP", "list1"]).unwrap(), bulk("a"));
+    assert_eq!(client.command(&["LREM", "list1", "0", "a"]).unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["LLEN", "list1"]).unwrap(), RespValue::Integer(2));
+
+    client.command(&["SET", "key1", "1"]).unwrap();
+    assert!(matches!(client.command(&["RPUSH", "key1", "x"]).unwrap(), RespValue::Error(err) if err.starts_with("WRONGTYPE")));
+    assert!(matches!(client.command(&["GET", "list1"]).unwrap(), RespValue::Error(err) if err.starts_with("WRONGTYPE")));
+
+    let db = PickleDb::load_read_only("resp_list_commands.db").unwrap();
+    assert_eq!(db.llen("list1"), 2);
+    assert_eq!(db.lget::<String>("list1", 0).unwrap(), "b");
+    assert_eq!(db.lget::<String>("list1", 1).unwrap(), "c");
+}
+
+#[test]
+fn resp_expire_and_save() {
+    set_test_rsc!("resp_expire_and_save.db");
+
+    let db = PickleDb::new("resp_expire_and_save.db", PickleDbDumpPolicy::DumpUponRequest);
+    let mut client = serve(RespServer::new(db));
+
+    client.command(&["SET", "key1", "1"]).unwrap();
+    client.command(&["SET", "key2", "2"]).unwrap();
+    assert_eq!(client.command(&["TTL", "key1"]).unwrap(), RespValue::Integer(-1));
+    assert_eq!(client.command(&["EXPIRE", "key1", "100"]).unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["TTL", "key1"]).unwrap(), RespValue::Integer(100));
+    assert_eq!(client.command(&["EXPIRE", "missing", "100"]).unwrap(), RespValue::Integer(0));
+
+    // an expiration that isn't positive removes the key immediately
+    assert_eq!(client.command(&["EXPIRE", "key2", "0"]).unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["TTL", "key2"]).unwrap(), RespValue::Integer(-2));
+
+    // setting a key clears its expiration
+    client.command(&["SET", "key1", "3"]).unwrap();
+    assert_eq!(client.command(&["TTL", "key1"]).unwrap(), RespValue::Integer(-1));
+
+    assert_eq!(client.command(&["SAVE"]).unwrap(), RespValue::Simple(String::from("OK")));
+    let db = PickleDb::load_read_only("resp_expire_and_save.db").unwrap();
+    assert_eq!(db.get::<i32>("key1").unwrap(), 3);
+    assert!(!db.exists("key2"));
+}
+
+#[test]
+fn resp_commands_are_single_changes() {
+    set_test_rsc!("resp_commands_are_single_changes.db");
+
+    let mut db = PickleDb::new("resp_commands_are_single_changes.db", PickleDbDumpPolicy::DumpEveryNWrites(2));
+    db.set_list_schema("nums", &serde_json::json!({"type": "number"})).unwrap();
+    assert!(db.dump());
+    let mut client = serve(RespServer::new(db));
+
+    // a list whose items are invalid isn't created
+    assert!(matches!(client.command(&["RPUSH", "nums", "x"]).unwrap(), RespValue::Error(err) if err.starts_with("ERR")));
+    assert_eq!(client.command(&["EXISTS", "nums"]).unwrap(), RespValue::Integer(0));
+
+    // every command is a single write
+    assert_eq!(client.command(&["RPUSH", "list1", "a", "a", "b"]).unwrap(), RespValue::Integer(3));
+    assert_eq!(client.command(&["LREM", "list1", "0", "a"]).unwrap(), RespValue::Integer(2));
+    assert_eq!(PickleDb::load_read_only("resp_commands_are_single_changes.db").unwrap().llen("list1"), 1);
+    client.command(&["SET", "list1", "1"]).unwrap();
+    client.command(&["SET", "key2", "2"]).unwrap();
+    assert_eq!(PickleDb::load_read_only("resp_commands_are_single_changes.db").unwrap().get::<i32>("list1").unwrap(), 1);
+
+    // a failed save reports why
+    let mut client = serve(RespServer::new(PickleDb::new("no_such_dir/resp.db", PickleDbDumpPolicy::DumpUponRequest)));
+    assert!(matches!(client.command(&["SAVE"]).unwrap(), RespValue::Error(err) if err.starts_with("ERR") && !err.contains("backups")));
+}
+
+#[test]
+fn resp_read_only() {
+    set_test_rsc!("resp_read_only.db");
+
+    let mut db = PickleDb::new("resp_read_only.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &1).unwrap();
+    let mut client = serve(RespServer::new_read_only(PickleDb::load_read_only("resp_read_only.db").unwrap()));
+
+    assert_eq!(client.command(&["GET", "key1"]).unwrap(), bulk("1"));
+    assert!(matches!(client.command(&["SET", "key1", "2"]).unwrap(), RespValue::Error(err) if err.starts_with("READONLY")));
+    assert!(matches!(client.command(&["DEL", "key1"]).unwrap(), RespValue::Error(err) if err.starts_with("READONLY")));
+    assert_eq!(client.command(&["GET", "key1"]).unwrap(), bulk("1"));
+}
+
+#[test]
+fn resp_inline_commands() {
+    set_test_rsc!("resp_inline_commands.db");
+
+    let mut db = PickleDb::new("resp_inline_commands.db", PickleDbDumpPolicy::NeverDump);
+    db.set("key1", &String::from("value1")).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = RespServer::new(db);
+    thread::spawn(move || server.serve_tcp(listener));
+
+    // commands typed in telnet aren't RESP arrays
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(b"GET key1\r\nQUIT\r\n").unwrap();
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).unwrap();
+    assert_eq!(reply, "$6\r\nvalue1\r\n+OK\r\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn resp_unix_socket() {
+    set_test_rsc!("resp_unix_socket.sock");
+
+    let mut db = PickleDb::new("resp_unix_socket.db", PickleDbDumpPolicy::NeverDump);
+    db.set("key1", &1).unwrap();
+    let listener = std::os::unix::net::UnixListener::bind("resp_unix_socket.sock").unwrap();
+    let server = RespServer::new(db);
+    thread::spawn(move || server.serve_unix(listener));
+
+    let mut client = RespClient::connect_unix("resp_unix_socket.sock").unwrap();
+    assert_eq!(client.command(&["GET", "key1"]).unwrap(), bulk("1"));
+}
+
+#[test]
+fn resp_protocol_errors() {
+    let server = RespServer::new(PickleDb::new("resp_protocol_errors.db", PickleDbDumpPolicy::NeverDump));
+    let reply = |request: &[u8]| {
+        let mut reply = Vec::new();
+        let result = server.handle_connection(request, &mut reply);
+        (result.is_err(), String::from_utf8(reply).unwrap())
+    };
+
+    assert_eq!(reply(b"*2\r\n$4\r\nECHO\r\n*1\r\n$1\r\na\r\n"), (true, String::from("-ERR Protocol error: nested arrays aren't supported\r\n")));
+    assert_eq!(reply(b"*1\r\n$4\r\nPINGxx"), (true, String::from("-ERR Protocol error: bulk string isn't followed by CRLF\r\n")));
+    assert_eq!(reply(b"*1\r\n$4\r\nPI").1, "-ERR Protocol error: bulk string is truncated\r\n");
+    // a length that isn't followed by the bytes doesn't allocate them
+    assert_eq!(reply(b"*1\r\n$536870912\r\nPING\r\n").1, "-ERR Protocol error: bulk string is truncated\r\n");
+    assert_eq!(reply(&vec![b'a'; 100 * 1024]).1, "-ERR Protocol error: line is too long\r\n");
+    assert_eq!(reply(b"*1\r\n$4\r\nPING\r\n"), (false, String::from("+PONG\r\n")));
+}
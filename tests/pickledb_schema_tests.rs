@@ -0,0 +1,262 @@
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::error::Error;
+use serde_json::json;
+
+mod common;
+
+#[macro_use]
+extern crate serde_derive;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct User {
+    name: String,
+    age: i32,
+}
+
+fn user(name: &str, age: i32) -> User {
+    User { name: String::from(name), age }
+}
+
+fn user_schema() -> serde_json::Value {
+    json!({
+        "title": "user",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string", "minLength": 1 },
+            "age": { "type": "integer", "minimum": 0 }
+        },
+        "required": ["name", "age"],
+        "additionalProperties": false
+    })
+}
+
+#[test]
+fn key_schema() {
+    set_test_rsc!("key_schema.db");
+
+    let mut db = PickleDb::new("key_schema.db", PickleDbDumpPolicy::AutoDump);
+    db.set_key_schema("user:", &user_schema()).unwrap();
+
+    db.set("user:1", &user("john", 35)).unwrap();
+
+    // a violation reports the path in the value and the value isn't set
+    match db.set("user:2", &user("jane", -1)) {
+        Err(Error::SchemaViolation { key, path, .. }) => {
+            assert_eq!(key, "user:2");
+            assert_eq!(path, "$.age");
+        },
+        _ => panic!("expected a schema violation"),
+    }
+    assert!(!db.exists("user:2"));
+
+    match db.set("user:2", &json!({"name": "jane"})) {
+        Err(Error::SchemaViolation { path, reason, .. }) => {
+            assert_eq!(path, "$");
+            assert!(reason.contains("age"));
+        },
+        _ => panic!("expected a schema violation"),
+    }
+    assert!(db.set("user:2", &json!({"name": "jane", "age": 25, "phone": "123"})).is_err());
+    assert!(db.set("user:2", &100).is_err());
+
+    // keys that don't start with the prefix aren't validated
+    db.set("other", &100).unwrap();
+
+    // the schema is stored in the DB file
+    {
+        let mut read_db = PickleDb::load_read_only("key_schema.db").unwrap();
+        assert!(read_db.set("user:3", &user("", 20)).is_err());
+        read_db.set("user:3", &user("bob", 20)).unwrap();
+    }
+
+    // values can be set freely once the schema is removed
+    assert!(db.remove_key_schema("user:"));
+    assert!(!db.remove_key_schema("user:"));
+    db.set("user:2", &100).unwrap();
+}
+
+#[test]
+fn list_schema() {
+    set_test_rsc!("list_schema.db");
+
+    let mut db = PickleDb::new("list_schema.db", PickleDbDumpPolicy::AutoDump);
+    db.lcreate("users");
+    db.set_list_schema("users", &user_schema()).unwrap();
+
+    assert!(db.ladd("users", &user("john", 35)).unwrap());
+    match db.ladd("users", &user("jane", -1)) {
+        Err(Error::SchemaViolation { key, path, .. }) => {
+            assert_eq!(key, "users");
+            assert_eq!(path, "$[1].age");
+        },
+        _ => panic!("expected a schema violation"),
+    }
+
+    // if one of the items is invalid none of them is added
+    assert!(db.lextend("users", &vec![json!({"name": "bob", "age": 30}), json!({"name": "alice"})]).is_err());
+    assert_eq!(db.llen("users"), 1);
+
+    // the typed API is validated as well
+    assert!(db.list::<User>("users").unwrap().push(&user("", 20)).is_err());
+    assert_eq!(db.llen("users"), 1);
+
+    // a schema can be attached to a list that doesn't exist yet
+    db.set_list_schema("nums", &json!({"type": "number", "multipleOf": 5})).unwrap();
+    db.lcreate("nums");
+    assert!(db.lextend("nums", &vec![5, 10, 15]).unwrap());
+    assert!(db.ladd("nums", &7).is_err());
+
+    // the schemas are stored in the DB file
+    {
+        let mut read_db = PickleDb::load_read_only("list_schema.db").unwrap();
+        assert!(read_db.ladd("nums", &3).is_err());
+    }
+
+    assert!(db.remove_list_schema("nums"));
+    assert!(db.ladd("nums", &7).unwrap());
+}
+
+#[test]
+fn combined_schemas() {
+    set_test_rsc!("combined_schemas.db");
+
+    let mut db = PickleDb::new("combined_schemas.db", PickleDbDumpPolicy::NeverDump);
+    db.set_key_schema("", &json!({
+        "anyOf": [
+            { "type": "string", "enum": ["red", "green", "blue"] },
+            { "type": "array", "items": { "type": "integer" }, "maxItems": 3, "uniqueItems": true }
+        ]
+    })).unwrap();
+
+    db.set("color", &"red").unwrap();
+    db.set("nums", &vec![1, 2, 3]).unwrap();
+    assert!(db.set("color", &"yellow").is_err());
+    assert!(db.set("nums", &vec![1, 2, 3, 4]).is_err());
+    assert!(db.set("nums", &vec![1, 1]).is_err());
+    assert!(db.set("nums", &vec![1.5]).is_err());
+    assert!(db.set("nums", &json!([1, 1.0])).is_err());
+
+    // when several prefixes match, the value has to conform to all schemas
+    db.set_key_schema("n", &json!({ "not": { "type": "string" } })).unwrap();
+    assert!(db.set("name", &"red").is_err());
+    db.set("name", &vec![7]).unwrap();
+    assert_eq!(db.get::<String>("color").unwrap(), "red");
+}
+
+#[test]
+fn fractional_multiple_of() {
+    set_test_rsc!("fractional_multiple_of.db");
+
+    let mut db = PickleDb::new("fractional_multiple_of.db", PickleDbDumpPolicy::NeverDump);
+    db.set_key_schema("price", &json!({ "type": "number", "multipleOf": 0.1 })).unwrap();
+    db.set_key_schema("weight", &json!({ "type": "number", "multipleOf": 0.01 })).unwrap();
+
+    // quotients such as 0.3 / 0.1 aren't exact integers in floating point
+    for price in &[0.3, 0.7, 1.1, 2.3, 19.9, 1234.5, -0.3, 0.0] {
+        db.set("price", price).unwrap();
+    }
+    for weight in &[0.07, 0.29, 1.15, 4.35, 10.01] {
+        db.set("weight", weight).unwrap();
+    }
+
+    assert!(db.set("price", &0.35).is_err());
+    assert!(db.set("price", &0.01).is_err());
+    assert!(db.set("weight", &1.155).is_err());
+    assert_eq!(db.get::<f64>("price").unwrap(), 0.0);
+    assert_eq!(db.get::<f64>("weight").unwrap(), 10.01);
+}
+
+#[test]
+fn invalid_schemas() {
+    set_test_rsc!("invalid_schemas.db");
+
+    let mut db = PickleDb::new("invalid_schemas.db", PickleDbDumpPolicy::NeverDump);
+
+    // unsupported keywords and malformed schemas
+    for schema in &[
+        json!({ "$ref": "#/definitions/user" }),
+        json!({ "type": "string", "pattern": "^a" }),
+        json!({ "properties": { "name": { "format": "email", "patternProperties": {} } } }),
+        json!({ "type": 5 }),
+        json!({ "minimum": "zero" }),
+        json!({ "multipleOf": 0 }),
+        json!({ "multipleOf": -2 }),
+        json!({ "properties": { "price": { "multipleOf": -0.5 } } }),
+        json!("string"),
+    ] {
+        match db.set_key_schema("key", schema) {
+            Err(Error::InvalidSchema(_)) => (),
+            _ => panic!("expected schema {} to be invalid", schema),
+        }
+        assert!(db.set_list_schema("list", schema).is_err());
+    }
+
+    // a schema isn't attached if existing values don't conform to it
+    db.set("key1", &"a string").unwrap();
+    match db.set_key_schema("key", &json!({ "type": "integer" })) {
+        Err(Error::SchemaViolation { key, path, .. }) => {
+            assert_eq!(key, "key1");
+            assert_eq!(path, "$");
+        },
+        _ => panic!("expected a schema violation"),
+    }
+    db.set("key2", &"another string").unwrap();
+
+    db.lcreate("list");
+    db.lextend("list", &vec![1, 2, -3]).unwrap();
+    match db.set_list_schema("list", &json!({ "type": "integer", "minimum": 0 })) {
+        Err(Error::SchemaViolation { path, .. }) => assert_eq!(path, "$[2]"),
+        _ => panic!("expected a schema violation"),
+    }
+    assert!(db.ladd("list", &"a string").unwrap());
+}
+
+#[test]
+fn namespace_schemas() {
+    set_test_rsc!("namespace_schemas.db");
+
+    let mut db = PickleDb::new("namespace_schemas.db", PickleDbDumpPolicy::NeverDump);
+    db.set_key_schema("user:", &user_schema()).unwrap();
+    db.set_list_schema("ages", &json!({ "type": "integer", "minimum": 0 })).unwrap();
+
+    // the schemas of the DB apply to the keys and lists of namespaces
+    let mut billing = db.namespace("billing");
+    billing.set("user:1", &user("John", 30)).unwrap();
+    match billing.set("user:2", &user("Jane", -1)) {
+        Err(Error::SchemaViolation { key, path, .. }) => {
+            assert_eq!(key, "user:2");
+            assert_eq!(path, "$.age");
+        },
+        _ => panic!("expected a schema violation"),
+    }
+    assert!(!billing.exists("user:2"));
+
+    billing.lcreate("ages");
+    assert!(billing.lextend("ages", &[1, -2]).is_err());
+    assert_eq!(billing.llen("ages"), 0);
+    assert!(billing.ladd("ages", &3).unwrap());
+
+    // a schema isn't attached if values of namespaces don't conform to it
+    db.namespace("billing").set("count", &"many").unwrap();
+    assert!(db.set_key_schema("count", &json!({ "type": "integer" })).is_err());
+    db.namespace("billing").lcreate("names");
+    db.namespace("billing").ladd("names", &1).unwrap();
+    assert!(db.set_list_schema("names", &json!({ "type": "string" })).is_err());
+}
+
+#[test]
+fn lcreate_with_checks_items_first() {
+    set_test_rsc!("lcreate_with_checks_items_first.db");
+
+    let mut db = PickleDb::new("lcreate_with_checks_items_first.db", PickleDbDumpPolicy::AutoDump);
+    db.set_list_schema("nums", &json!({"type": "number"})).unwrap();
+    db.lcreate_with("nums", &[1, 2]).unwrap();
+
+    // an invalid item keeps the list that is already set
+    assert!(matches!(db.lcreate_with("nums", &[json!(3), json!("four")]), Err(Error::SchemaViolation { .. })));
+    assert_eq!(PickleDb::load_read_only("lcreate_with_checks_items_first.db").unwrap().llen("nums"), 2);
+
+    db.lcreate_with("nums", &[3]).unwrap();
+    assert_eq!(db.lget::<i32>("nums", 0).unwrap(), 3);
+    assert_eq!(db.llen("nums"), 1);
+}
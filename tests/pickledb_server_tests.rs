@@ -0,0 +1,207 @@
+#![cfg(feature = "server")]
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use pickledb::server::HttpServer;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use serde_json::{json, Value};
+
+mod common;
+
+/// Serve a DB on a free local port, and return its address
+fn serve(server: HttpServer) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || server.serve_tcp(listener));
+    addr
+}
+
+/// Send a request, and return the status code and the JSON body of the response
+fn request(addr: SocketAddr, method: &str, path: &str, token: Option<&str>, body: Option<Value>) -> (u16, Option<Value>) {
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let auth = token.map(|token| format!("Authorization: Bearer {}\r\n", token)).unwrap_or_default();
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\nHost: localhost\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+           method, path, auth, body.len(), body).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let status = response[9..12].parse().unwrap();
+    let body = &response[response.find("\r\n\r\n").unwrap() + 4..];
+    (status, if body.is_empty() { None } else { Some(serde_json::from_str(body).unwrap()) })
+}
+
+#[test]
+fn server_keys() {
+    set_test_rsc!("server_keys.db");
+
+    let mut db = PickleDb::new("server_keys.db", PickleDbDumpPolicy::AutoDump);
+    db.set("user:1", &json!({"name": "john"})).unwrap();
+    let addr = serve(HttpServer::new(db));
+
+    assert_eq!(request(addr, "GET", "/keys/user:1", None, None), (200, Some(json!({"name": "john"}))));
+    assert_eq!(request(addr, "PUT", "/keys/user%3A2", None, Some(json!({"name": "jane"}))), (204, None));
+    assert_eq!(request(addr, "PUT", "/keys/other", None, Some(json!(5))), (204, None));
+    assert_eq!(request(addr, "GET", "/keys/user:2", None, None), (200, Some(json!({"name": "jane"}))));
+    assert_eq!(request(addr, "GET", "/keys?prefix=user%3A", None, None), (200, Some(json!(["user:1", "user:2"]))));
+    assert_eq!(request(addr, "GET", "/keys", None, None), (200, Some(json!(["other", "user:1", "user:2"]))));
+
+    assert_eq!(request(addr, "DELETE", "/keys/user:1", None, None), (204, None));
+    assert_eq!(request(addr, "GET", "/keys/user:1", None, None).0, 404);
+    assert_eq!(request(addr, "DELETE", "/keys/user:1", None, None).0, 404);
+
+    let (status, body) = request(addr, "GET", "/keys/missing", None, None);
+    assert_eq!(status, 404);
+    assert_eq!(body.unwrap()["error"], "key 'missing' not found");
+    assert_eq!(request(addr, "POST", "/keys/other", None, None).0, 405);
+    assert_eq!(request(addr, "GET", "/other", None, None).0, 404);
+
+    // the changes were dumped according to the dump policy
+    let db = PickleDb::load_read_only("server_keys.db").unwrap();
+    assert_eq!(db.get::<Value>("user:2").unwrap(), json!({"name": "jane"}));
+    assert_eq!(db.get::<i32>("other").unwrap(), 5);
+    assert!(!db.exists("user:1"));
+}
+
+#[test]
+fn server_lists() {
+    set_test_rsc!("server_lists.db");
+
+    let db = PickleDb::new("server_lists.db", PickleDbDumpPolicy::AutoDump);
+    let addr = serve(HttpServer::new(db));
+
+    assert_eq!(request(addr, "PUT", "/lists/list1", None, Some(json!([1, "two", {"three": 3}]))), (204, None));
+    assert_eq!(request(addr, "GET", "/lists/list1", None, None), (200, Some(json!([1, "two", {"three": 3}]))));
+    assert_eq!(request(addr, "PUT", "/lists/list1", None, Some(json!([4]))), (204, None));
+    assert_eq!(request(addr, "GET", "/lists/list1", None, None), (200, Some(json!([4]))));
+    assert_eq!(request(addr, "PUT", "/lists/list1", None, Some(json!({"not": "an array"}))).0, 400);
+    assert_eq!(request(addr, "GET", "/keys/list1", None, None).0, 404);
+
+    let db = PickleDb::load_read_only("server_lists.db").unwrap();
+    assert_eq!(db.llen("list1"), 1);
+    assert_eq!(db.lget::<i32>("list1", 0).unwrap(), 4);
+
+    assert_eq!(request(addr, "DELETE", "/lists/list1", None, None), (204, None));
+    assert_eq!(request(addr, "GET", "/lists/list1", None, None).0, 404);
+    assert_eq!(request(addr, "DELETE", "/lists/list1", None, None).0, 404);
+}
+
+#[test]
+fn server_dump_upon_request() {
+    set_test_rsc!("server_dump_upon_request.db");
+
+    let db = PickleDb::new("server_dump_upon_request.db", PickleDbDumpPolicy::DumpUponRequest);
+    let addr = serve(HttpServer::new(db));
+
+    assert_eq!(request(addr, "PUT", "/keys/key1", None, Some(json!("value1"))), (204, None));
+    assert!(PickleDb::load_read_only("server_dump_upon_request.db").is_err());
+
+    assert_eq!(request(addr, "POST", "/dump", None, None), (204, None));
+    let db = PickleDb::load_read_only("server_dump_upon_request.db").unwrap();
+    assert_eq!(db.get::<String>("key1").unwrap(), "value1");
+}
+
+#[test]
+fn server_invalid_list_keeps_the_old_one() {
+    set_test_rsc!("server_invalid_list_keeps_the_old_one.db");
+
+    let mut db = PickleDb::new("server_invalid_list_keeps_the_old_one.db", PickleDbDumpPolicy::AutoDump);
+    db.set_list_schema("nums", &json!({"type": "number"})).unwrap();
+    let addr = serve(HttpServer::new(db));
+
+    assert_eq!(request(addr, "PUT", "/lists/nums", None, Some(json!([1, 2]))), (204, None));
+    assert_eq!(request(addr, "PUT", "/lists/nums", None, Some(json!([3, "four"]))).0, 422);
+    assert_eq!(request(addr, "GET", "/lists/nums", None, None), (200, Some(json!([1, 2]))));
+    assert_eq!(PickleDb::load_read_only("server_invalid_list_keeps_the_old_one.db").unwrap().llen("nums"), 2);
+
+    // a failed dump reports why
+    let addr = serve(HttpServer::new(PickleDb::new("no_such_dir/server.db", PickleDbDumpPolicy::DumpUponRequest)));
+    let (status, body) = request(addr, "POST", "/dump", None, None);
+    assert_eq!(status, 500);
+    assert!(!body.unwrap()["error"].as_str().unwrap().contains("backups"));
+}
+
+#[test]
+fn server_token_and_read_only() {
+    set_test_rsc!("server_token_and_read_only.db");
+
+    let mut db = PickleDb::new("server_token_and_read_only.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &1).unwrap();
+    let mut server = HttpServer::new_read_only(PickleDb::load_read_only("server_token_and_read_only.db").unwrap());
+    server.set_token("secret");
+    let addr = serve(server);
+
+    assert_eq!(request(addr, "GET", "/keys/key1", None, None).0, 401);
+    assert_eq!(request(addr, "GET", "/keys/key1", Some("wrong"), None).0, 401);
+    assert_eq!(request(addr, "GET", "/keys/key1", Some("secret"), None), (200, Some(json!(1))));
+    assert_eq!(request(addr, "PUT", "/keys/key1", Some("secret"), Some(json!(2))).0, 403);
+    assert_eq!(request(addr, "GET", "/keys/key1", Some("secret"), None), (200, Some(json!(1))));
+}
+
+/// Send a request as it is, and return the whole response
+fn raw_request(addr: SocketAddr, request: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn server_cors() {
+    set_test_rsc!("server_cors.db");
+
+    let mut db = PickleDb::new("server_cors.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &1).unwrap();
+    let db = PickleDb::load_read_only("server_cors.db").unwrap();
+    let addr = serve(HttpServer::new(db));
+
+    // CORS is off by default
+    let response = raw_request(addr, "GET /keys/key1 HTTP/1.1\r\nHost: localhost\r\nOrigin: https://example.com\r\nConnection: close\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(!response.contains("Access-Control-Allow-Origin"));
+    assert_eq!(request(addr, "OPTIONS", "/keys/key1", None, None).0, 405);
+
+    let mut server = HttpServer::new(PickleDb::load_read_only("server_cors.db").unwrap());
+    server.set_token("secret");
+    server.set_cors_origin("https://example.com");
+    let addr = serve(server);
+
+    // the preflight doesn't carry the token
+    let response = raw_request(addr, "OPTIONS /keys/key1 HTTP/1.1\r\nHost: localhost\r\nOrigin: https://example.com\r\n\
+                                      Access-Control-Request-Method: PUT\r\nConnection: close\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 204"));
+    assert!(response.contains("Access-Control-Allow-Origin: https://example.com\r\n"));
+    assert!(response.contains("Access-Control-Allow-Methods: GET, PUT, DELETE, POST\r\n"));
+    assert!(response.contains("Access-Control-Allow-Headers: Authorization, Content-Type\r\n"));
+
+    let response = raw_request(addr, "GET /keys/key1 HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer secret\r\n\
+                                      Origin: https://example.com\r\nConnection: close\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains("Access-Control-Allow-Origin: https://example.com\r\n"));
+    let response = raw_request(addr, "GET /keys/key1 HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 401"));
+    assert!(response.contains("Access-Control-Allow-Origin: https://example.com\r\n"));
+}
+
+#[test]
+fn server_body_too_large() {
+    set_test_rsc!("server_body_too_large.db");
+
+    let addr = serve(HttpServer::new(PickleDb::new("server_body_too_large.db", PickleDbDumpPolicy::NeverDump)));
+
+    // the length is rejected before the body is read
+    let response = raw_request(addr, "PUT /keys/key1 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 1073741824\r\nConnection: close\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 413"));
+
+    // a chunked body is read only up to the limit
+    let chunk = format!("\"{}\"", "x".repeat(1024 * 1024));
+    let mut request = String::from("PUT /keys/key1 HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n");
+    for _ in 0..17 {
+        request.push_str(&format!("{:x}\r\n{}\r\n", chunk.len(), chunk));
+    }
+    request.push_str("0\r\n\r\n");
+    assert!(raw_request(addr, &request).starts_with("HTTP/1.1 413"));
+    assert_eq!(self::request(addr, "GET", "/keys/key1", None, None).0, 404);
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "shell")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use serde_json::json;
+
+mod common;
+
+/// Run the shell on a DB file with the given input lines, and return what it printed
+fn shell(db_file: &str, input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pickledb"))
+        .args(["shell", db_file])
+        .env_remove("HOME")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn shell_commands() {
+    set_test_rsc!("shell_commands.db");
+
+    let mut db = PickleDb::new("shell_commands.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &1).unwrap();
+    drop(db);
+
+    let output = shell("shell_commands.db", "\
+        GET key1\n\
+        set user:1 {\"name\": \"john doe\"}\n\
+        LCREATE list1\n\
+        LADD list1 hello world\n\
+        LADD list1 2\n\
+        LLEN list1\n\
+        LREMVALUE list1 2\n\
+        EXISTS user:1\n\
+        KEYS user:*\n\
+        GET missing\n\
+        FROB\n\
+        SAVE\n\
+        QUIT\n");
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines, vec![
+        "1", "OK", "OK", "OK", "OK", "2", "true", "true", "user:1",
+        "(error) key 'missing' not found",
+        "(error) unknown command 'FROB', please see HELP",
+        "saved",
+    ]);
+
+    let db = PickleDb::load_read_only("shell_commands.db").unwrap();
+    assert_eq!(db.get::<serde_json::Value>("user:1").unwrap(), json!({"name": "john doe"}));
+    assert_eq!(db.lget::<String>("list1", 0).unwrap(), "hello world");
+    assert_eq!(db.llen("list1"), 1);
+}
+
+#[test]
+fn shell_discards_unsaved_changes() {
+    set_test_rsc!("shell_discards_unsaved_changes.db");
+
+    let mut db = PickleDb::new("shell_discards_unsaved_changes.db", PickleDbDumpPolicy::AutoDump);
+    db.set("key1", &1).unwrap();
+    drop(db);
+
+    let output = shell("shell_discards_unsaved_changes.db", "SET key1 2\nQUIT\nQUIT\n");
+    assert!(output.contains("SAVE them or QUIT again"));
+    assert_eq!(PickleDb::load_read_only("shell_discards_unsaved_changes.db").unwrap().get::<i32>("key1").unwrap(), 1);
+
+    // so does the end of the input
+    let output = shell("shell_discards_unsaved_changes.db", "SET key1 3\n");
+    assert!(output.contains("unsaved changes were discarded"));
+    assert_eq!(PickleDb::load_read_only("shell_discards_unsaved_changes.db").unwrap().get::<i32>("key1").unwrap(), 1);
+}
+
+#[test]
+fn shell_invalid_ladd_changes_nothing() {
+    set_test_rsc!("shell_invalid_ladd_changes_nothing.db");
+
+    let mut db = PickleDb::new("shell_invalid_ladd_changes_nothing.db", PickleDbDumpPolicy::AutoDump);
+    db.set("nums", &1).unwrap();
+    db.set_list_schema("nums", &json!({"type": "number"})).unwrap();
+    drop(db);
+
+    // the list isn't created, so there's nothing to save
+    let output = shell("shell_invalid_ladd_changes_nothing.db", "LADD nums hello\nEXISTS nums\nLEXISTS nums\nQUIT\n");
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[0].starts_with("(error) "));
+    assert_eq!(&lines[1..], ["true", "false"]);
+    assert!(!output.contains("SAVE them"));
+}
@@ -0,0 +1,140 @@
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::error::Error;
+
+mod common;
+
+#[macro_use]
+extern crate serde_derive;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Rectangle {
+    width: i32,
+    length: i32,
+}
+
+#[test]
+fn typed_list() {
+    set_test_rsc!("typed_list.db");
+
+    let mut db = PickleDb::new("typed_list.db", PickleDbDumpPolicy::AutoDump);
+
+    {
+        // the list is created if it doesn't exist
+        let mut shapes = db.list::<Rectangle>("shapes").unwrap();
+        assert!(shapes.is_empty());
+
+        shapes.push(&Rectangle { width: 1, length: 2 }).unwrap();
+        shapes.extend(&[Rectangle { width: 3, length: 4 }, Rectangle { width: 5, length: 6 }]).unwrap();
+        assert_eq!(shapes.len(), 3);
+        assert_eq!(shapes.get(1).unwrap(), Rectangle { width: 3, length: 4 });
+        assert!(shapes.get(3).is_none());
+
+        // remove an item
+        assert_eq!(shapes.remove(0).unwrap(), Rectangle { width: 1, length: 2 });
+        assert!(shapes.remove(5).is_none());
+
+        let areas: Vec<i32> = shapes.iter().map(|rect| rect.width * rect.length).collect();
+        assert_eq!(areas, vec![12, 30]);
+    }
+
+    // the list is visible through the untyped API as well
+    assert!(db.lexists("shapes"));
+    assert_eq!(db.lget::<Rectangle>("shapes", 0).unwrap(), Rectangle { width: 3, length: 4 });
+
+    // an existing list is reused
+    {
+        let read_db = PickleDb::load_read_only("typed_list.db").unwrap();
+        assert_eq!(read_db.llen("shapes"), 2);
+    }
+    assert_eq!(db.list::<Rectangle>("shapes").unwrap().len(), 2);
+}
+
+#[test]
+fn typed_list_type_mismatch() {
+    set_test_rsc!("typed_list_type_mismatch.db");
+
+    let mut db = PickleDb::new("typed_list_type_mismatch.db", PickleDbDumpPolicy::AutoDump);
+
+    db.lcreate("list1");
+    db.ladd("list1", &Rectangle { width: 1, length: 2 }).unwrap();
+    db.ladd("list1", &String::from("not a rectangle")).unwrap();
+
+    // an item of a different type fails the handle creation
+    match db.list::<Rectangle>("list1") {
+        Err(Error::TypeMismatch { key, reason }) => {
+            assert_eq!(key, "list1");
+            assert!(reason.contains("position 1"));
+        },
+        _ => panic!("expected a type mismatch"),
+    }
+
+    // a key that holds a value can't be used as a list, and the value isn't overridden
+    db.set("key1", &1).unwrap();
+    assert!(db.list::<i32>("key1").is_err());
+    assert_eq!(db.get::<i32>("key1").unwrap(), 1);
+}
+
+#[test]
+fn typed_bucket() {
+    set_test_rsc!("typed_bucket.db");
+
+    let mut db = PickleDb::new("typed_bucket.db", PickleDbDumpPolicy::AutoDump);
+
+    {
+        let mut rects = db.bucket::<Rectangle>("rects").unwrap();
+        assert!(rects.is_empty());
+
+        rects.set("small", &Rectangle { width: 1, length: 1 }).unwrap();
+        rects.set("big", &Rectangle { width: 10, length: 10 }).unwrap();
+        assert_eq!(rects.len(), 2);
+        assert!(rects.contains("small"));
+        assert_eq!(rects.get("big").unwrap(), Rectangle { width: 10, length: 10 });
+        assert!(rects.get("medium").is_none());
+
+        // override a value
+        rects.set("small", &Rectangle { width: 2, length: 2 }).unwrap();
+        assert_eq!(rects.get("small").unwrap(), Rectangle { width: 2, length: 2 });
+
+        assert_eq!(rects.remove("big").unwrap().unwrap(), Rectangle { width: 10, length: 10 });
+        assert!(rects.remove("big").unwrap().is_none());
+
+        let keys: Vec<String> = rects.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec![String::from("small")]);
+    }
+
+    // reload the bucket from the file
+    {
+        let mut read_db = PickleDb::load_read_only("typed_bucket.db").unwrap();
+        let rects = read_db.bucket::<Rectangle>("rects").unwrap();
+        assert_eq!(rects.len(), 1);
+        assert_eq!(rects.get("small").unwrap(), Rectangle { width: 2, length: 2 });
+    }
+
+    // values of the wrong type and lists aren't buckets
+    assert!(db.bucket::<i32>("rects").is_err());
+    db.lcreate("list1");
+    assert!(db.bucket::<Rectangle>("list1").is_err());
+}
+
+#[test]
+fn typed_bucket_replaced_by_hook() {
+    set_test_rsc!("typed_bucket_replaced_by_hook.db");
+
+    let mut db = PickleDb::new("typed_bucket_replaced_by_hook.db", PickleDbDumpPolicy::NeverDump);
+    // a hook may replace the bucket by any value
+    db.add_before_hook(|mutation| {
+        if mutation.value().is_some_and(|value| value.get("square").is_some()) {
+            mutation.set_value(serde_json::json!("replaced"));
+        }
+        Ok(())
+    });
+
+    let mut rects = db.bucket::<Rectangle>("rects").unwrap();
+    rects.set("square", &Rectangle { width: 1, length: 1 }).unwrap();
+    assert!(rects.get("square").is_none());
+    assert!(!rects.contains("square"));
+    assert!(rects.is_empty());
+    assert_eq!(rects.iter().count(), 0);
+    assert!(matches!(rects.set("small", &Rectangle { width: 1, length: 2 }), Err(Error::TypeMismatch { .. })));
+    assert!(matches!(rects.remove("square"), Err(Error::TypeMismatch { .. })));
+}
@@ -0,0 +1,179 @@
+use std::thread;
+use std::time::Duration;
+use pickledb::{PickleDb, PickleDbDumpPolicy};
+use pickledb::watch::{ChangeEvent, ChangeKind, RecvError};
+use serde_json::json;
+
+mod common;
+
+fn event(key: &str, kind: ChangeKind, old: Option<serde_json::Value>, new: Option<serde_json::Value>) -> ChangeEvent {
+    ChangeEvent { key: String::from(key), kind, old, new, namespace: None }
+}
+
+#[test]
+fn key_events() {
+    set_test_rsc!("key_events.db");
+
+    let mut db = PickleDb::new("key_events.db", PickleDbDumpPolicy::AutoDump);
+    let sub = db.subscribe("key1");
+
+    db.set("key1", &100).unwrap();
+    db.set("key1", &String::from("hello")).unwrap();
+    db.set("key2", &1).unwrap();
+    assert!(db.rem("key1"));
+    assert!(!db.rem("key1"));
+
+    assert_eq!(sub.try_recv().unwrap(), event("key1", ChangeKind::Set, None, Some(json!(100))));
+    assert_eq!(sub.try_recv().unwrap(), event("key1", ChangeKind::Set, Some(json!(100)), Some(json!("hello"))));
+    assert_eq!(sub.try_recv().unwrap(), event("key1", ChangeKind::Removed, Some(json!("hello")), None));
+    assert_eq!(sub.try_recv(), Err(RecvError::Empty));
+
+    // a failed set isn't reported
+    db.create_unique_index("idx", "$", "key").unwrap();
+    db.set("key1", &1).unwrap_err();
+    assert_eq!(sub.try_recv(), Err(RecvError::Empty));
+}
+
+#[test]
+fn namespace_events() {
+    set_test_rsc!("namespace_events.db");
+
+    let mut db = PickleDb::new("namespace_events.db", PickleDbDumpPolicy::AutoDump);
+    let db_sub = db.subscribe("*");
+    let billing_sub = db.namespace("billing").subscribe("key*");
+
+    db.namespace("billing").set("key1", &1).unwrap();
+    db.namespace("billing").lcreate("list1");
+    db.namespace("billing").ladd("list1", &2).unwrap();
+    db.namespace("other").set("key1", &3).unwrap();
+    db.set("key1", &4).unwrap();
+    assert!(db.namespace("billing").rem("key1"));
+
+    let billing_event = |key: &str, kind, old, new| ChangeEvent { namespace: Some(String::from("billing")), ..event(key, kind, old, new) };
+    assert_eq!(billing_sub.try_recv().unwrap(), billing_event("key1", ChangeKind::Set, None, Some(json!(1))));
+    assert_eq!(billing_sub.try_recv().unwrap(), billing_event("key1", ChangeKind::Removed, Some(json!(1)), None));
+    assert_eq!(billing_sub.try_recv(), Err(RecvError::Empty));
+
+    // subscriptions of the DB receive only changes of the DB itself
+    assert_eq!(db_sub.try_recv().unwrap(), event("key1", ChangeKind::Set, None, Some(json!(4))));
+    assert_eq!(db_sub.try_recv(), Err(RecvError::Empty));
+}
+
+#[test]
+fn list_events() {
+    set_test_rsc!("list_events.db");
+
+    let mut db = PickleDb::new("list_events.db", PickleDbDumpPolicy::AutoDump);
+    let sub = db.subscribe("*");
+
+    db.set("list1", &1).unwrap();
+    db.lcreate("list1");
+    db.lextend("list1", &vec![1, 2, 3]).unwrap();
+    db.ladd("list1", &4).unwrap();
+    assert_eq!(db.lpop::<i32>("list1", 0).unwrap(), 1);
+    assert!(db.lrem_value("list1", &3));
+    assert_eq!(db.lrem_list("list1"), 2);
+
+    let kinds: Vec<(ChangeKind, Option<serde_json::Value>, Option<serde_json::Value>)> = std::iter::from_fn(|| sub.try_recv().ok())
+        .map(|event| (event.kind, event.old, event.new))
+        .collect();
+    assert_eq!(kinds, vec![
+        (ChangeKind::Set, None, Some(json!(1))),
+        (ChangeKind::ListCreated, Some(json!(1)), Some(json!([]))),
+        (ChangeKind::ListPush, None, Some(json!(1))),
+        (ChangeKind::ListPush, None, Some(json!(2))),
+        (ChangeKind::ListPush, None, Some(json!(3))),
+        (ChangeKind::ListPush, None, Some(json!(4))),
+        (ChangeKind::ListPop, Some(json!(1)), None),
+        (ChangeKind::ListRemoveValue, Some(json!(3)), None),
+        (ChangeKind::ListRemoved, Some(json!([2, 4])), None),
+    ]);
+
+    // setting a value over a list reports the list as the old value
+    db.lcreate("list2");
+    db.ladd("list2", &"a").unwrap();
+    db.set("list2", &5).unwrap();
+    let last = std::iter::from_fn(|| sub.try_recv().ok()).last().unwrap();
+    assert_eq!(last, event("list2", ChangeKind::Set, Some(json!(["a"])), Some(json!(5))));
+}
+
+#[test]
+fn subscription_patterns() {
+    set_test_rsc!("subscription_patterns.db");
+
+    let mut db = PickleDb::new("subscription_patterns.db", PickleDbDumpPolicy::NeverDump);
+    let config = db.subscribe("config.*");
+    let users = db.subscribe("user:??");
+    assert_eq!(config.pattern(), "config.*");
+
+    for key in &["config.theme", "config.", "configs", "user:12", "user:1", "user:123", "other"] {
+        db.set(key, &1).unwrap();
+    }
+
+    let config_keys: Vec<String> = std::iter::from_fn(|| config.try_recv().ok()).map(|event| event.key).collect();
+    assert_eq!(config_keys, vec!["config.theme", "config."]);
+    let user_keys: Vec<String> = std::iter::from_fn(|| users.try_recv().ok()).map(|event| event.key).collect();
+    assert_eq!(user_keys, vec!["user:12"]);
+
+    // namespaces aren't reported
+    db.namespace("ns").set("config.theme", &2).unwrap();
+    assert_eq!(config.try_recv(), Err(RecvError::Empty));
+}
+
+#[test]
+fn subscription_lag() {
+    set_test_rsc!("subscription_lag.db");
+
+    let mut db = PickleDb::new("subscription_lag.db", PickleDbDumpPolicy::NeverDump);
+    let sub = db.subscribe_with_capacity("*", 3);
+
+    for i in 0..5 {
+        db.set("key", &i).unwrap();
+    }
+
+    // the 2 oldest events were dropped
+    assert_eq!(sub.try_recv(), Err(RecvError::Lagged(2)));
+    for i in 2..5 {
+        assert_eq!(sub.try_recv().unwrap().new, Some(json!(i)));
+    }
+    assert_eq!(sub.try_recv(), Err(RecvError::Empty));
+}
+
+#[test]
+fn subscription_across_threads() {
+    set_test_rsc!("subscription_across_threads.db");
+
+    let mut db = PickleDb::new("subscription_across_threads.db", PickleDbDumpPolicy::NeverDump);
+    let sub = db.subscribe("*");
+    let dropped = db.subscribe("*");
+    drop(dropped);
+
+    let receiver = thread::spawn(move || {
+        let mut keys = Vec::new();
+        loop {
+            match sub.recv() {
+                Ok(event) => keys.push(event.key),
+                Err(RecvError::Closed) => return keys,
+                Err(err) => panic!("unexpected error: {}", err),
+            }
+        }
+    });
+
+    db.set("key1", &1).unwrap();
+    db.set("key2", &2).unwrap();
+    drop(db);
+
+    assert_eq!(receiver.join().unwrap(), vec!["key1", "key2"]);
+}
+
+#[test]
+fn recv_timeout() {
+    set_test_rsc!("recv_timeout.db");
+
+    let mut db = PickleDb::new("recv_timeout.db", PickleDbDumpPolicy::NeverDump);
+    let sub = db.subscribe("*");
+
+    assert_eq!(sub.recv_timeout(Duration::from_millis(10)), Err(RecvError::Empty));
+    db.set("key1", &1).unwrap();
+    assert_eq!(sub.recv_timeout(Duration::from_millis(10)).unwrap().key, "key1");
+}